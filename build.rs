@@ -0,0 +1,37 @@
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+fn main() {
+    let git_sha = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|sha| sha.trim().to_string())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    // The proto crate is a git dependency, its revision is recorded in the
+    // lock file as `source = "git+<url>#<sha>"`.
+    let proto_revision = std::fs::read_to_string("Cargo.lock")
+        .ok()
+        .and_then(|lock| {
+            lock.split("[[package]]")
+                .find(|package| package.contains("name = \"proto_rust\""))
+                .and_then(|package| package.lines().find(|line| line.starts_with("source")))
+                .and_then(|source| source.trim_end_matches('"').rsplit_once('#'))
+                .map(|(_, sha)| sha.chars().take(7).collect::<String>())
+        })
+        .unwrap_or_else(|| "unknown".to_string());
+
+    let build_date = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=GIT_SHA={git_sha}");
+    println!("cargo:rustc-env=PROTO_REVISION={proto_revision}");
+    println!("cargo:rustc-env=BUILD_DATE={build_date}");
+    println!("cargo:rerun-if-changed=.git/HEAD");
+    println!("cargo:rerun-if-changed=Cargo.lock");
+}
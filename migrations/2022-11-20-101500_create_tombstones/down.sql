@@ -0,0 +1 @@
+DROP TABLE tombstones
\ No newline at end of file
@@ -0,0 +1,6 @@
+CREATE TABLE tombstones
+(
+    id_entity    TEXT        NOT NULL   PRIMARY KEY,
+    entity       TEXT        NOT NULL,
+    deleted_on   TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
\ No newline at end of file
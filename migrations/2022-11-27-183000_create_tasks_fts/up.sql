@@ -0,0 +1,29 @@
+CREATE VIRTUAL TABLE tasks_fts USING fts5
+(
+    id_task UNINDEXED,
+    title,
+    body,
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+
+INSERT INTO tasks_fts (id_task, title, body)
+SELECT id_task, title, body FROM tasks;
+
+CREATE TRIGGER tasks_fts_insert
+    AFTER INSERT ON tasks
+BEGIN
+    INSERT INTO tasks_fts (id_task, title, body) VALUES (new.id_task, new.title, new.body);
+END;
+
+CREATE TRIGGER tasks_fts_update
+    AFTER UPDATE ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE tasks_fts.id_task = old.id_task;
+    INSERT INTO tasks_fts (id_task, title, body) VALUES (new.id_task, new.title, new.body);
+END;
+
+CREATE TRIGGER tasks_fts_delete
+    AFTER DELETE ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE tasks_fts.id_task = old.id_task;
+END;
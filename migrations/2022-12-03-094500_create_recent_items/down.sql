@@ -0,0 +1 @@
+DROP TABLE recent_items
\ No newline at end of file
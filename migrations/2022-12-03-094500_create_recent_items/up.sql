@@ -0,0 +1,8 @@
+CREATE TABLE recent_items
+(
+    id_item         TEXT        NOT NULL   PRIMARY KEY,
+    kind            TEXT        NOT NULL,
+    visits          INTEGER     DEFAULT 0 NOT NULL,
+    score           DOUBLE      DEFAULT 0 NOT NULL,
+    last_visited    TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1 @@
+DROP INDEX tasks_due_date_idx;
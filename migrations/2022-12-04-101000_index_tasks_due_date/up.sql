@@ -0,0 +1 @@
+CREATE INDEX tasks_due_date_idx ON tasks (due_date);
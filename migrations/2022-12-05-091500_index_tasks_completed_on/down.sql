@@ -0,0 +1 @@
+DROP INDEX tasks_completed_on_idx;
@@ -0,0 +1 @@
+CREATE INDEX tasks_completed_on_idx ON tasks (completed_on);
@@ -0,0 +1,3 @@
+DROP INDEX tasks_position_idx;
+
+ALTER TABLE tasks DROP COLUMN position;
@@ -0,0 +1,11 @@
+ALTER TABLE tasks ADD COLUMN position BIGINT NOT NULL DEFAULT 0;
+
+UPDATE tasks SET position = (
+    SELECT COUNT(*) FROM tasks AS previous
+    WHERE previous.parent_list = tasks.parent_list
+    AND (previous.created_date_time < tasks.created_date_time
+        OR (previous.created_date_time = tasks.created_date_time
+            AND previous.id_task < tasks.id_task))
+);
+
+CREATE INDEX tasks_position_idx ON tasks (parent_list, position);
@@ -0,0 +1,5 @@
+ALTER TABLE lists ADD COLUMN position BIGINT NOT NULL DEFAULT 0;
+
+UPDATE lists SET position = (
+    SELECT COUNT(*) FROM lists AS previous WHERE previous.rowid < lists.rowid
+);
@@ -0,0 +1 @@
+ALTER TABLE lists ADD COLUMN pinned BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+CREATE TABLE idempotency_keys
+(
+    key         TEXT        NOT NULL   PRIMARY KEY,
+    entity      TEXT        NOT NULL,
+    id_entity   TEXT        NOT NULL,
+    created     TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
@@ -0,0 +1,2 @@
+DROP TRIGGER remove_subtasks_on_task_delete;
+DROP TABLE subtasks;
@@ -0,0 +1,18 @@
+CREATE TABLE subtasks
+(
+    id_subtask              TEXT        NOT NULL   PRIMARY KEY,
+    id_task                 TEXT        NOT NULL,
+    title                   TEXT        NOT NULL,
+    completed               BOOLEAN     DEFAULT false NOT NULL,
+    position                BIGINT      DEFAULT 0 NOT NULL,
+    created_date_time       TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    last_modified_date_time TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX subtasks_task_idx ON subtasks (id_task, position);
+
+CREATE TRIGGER remove_subtasks_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM subtasks WHERE subtasks.id_task = old.id_task;
+END;
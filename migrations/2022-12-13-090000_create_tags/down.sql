@@ -0,0 +1,4 @@
+DROP TRIGGER remove_task_tags_on_tag_delete;
+DROP TRIGGER remove_task_tags_on_task_delete;
+DROP TABLE task_tags;
+DROP TABLE tags;
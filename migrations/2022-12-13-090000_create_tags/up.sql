@@ -0,0 +1,26 @@
+CREATE TABLE tags
+(
+    id_tag      TEXT    NOT NULL   PRIMARY KEY,
+    name        TEXT    NOT NULL   UNIQUE
+);
+
+CREATE TABLE task_tags
+(
+    id_task     TEXT    NOT NULL,
+    id_tag      TEXT    NOT NULL,
+    PRIMARY KEY (id_task, id_tag)
+);
+
+CREATE INDEX task_tags_tag_idx ON task_tags (id_tag);
+
+CREATE TRIGGER remove_task_tags_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM task_tags WHERE task_tags.id_task = old.id_task;
+END;
+
+CREATE TRIGGER remove_task_tags_on_tag_delete
+    BEFORE DELETE ON tags
+BEGIN
+    DELETE FROM task_tags WHERE task_tags.id_tag = old.id_tag;
+END;
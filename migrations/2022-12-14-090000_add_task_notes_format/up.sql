@@ -0,0 +1 @@
+ALTER TABLE tasks ADD COLUMN notes_format INTEGER NOT NULL DEFAULT 0;
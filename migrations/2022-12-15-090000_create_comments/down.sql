@@ -0,0 +1,2 @@
+DROP TRIGGER remove_comments_on_task_delete;
+DROP TABLE comments;
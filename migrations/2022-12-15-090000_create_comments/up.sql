@@ -0,0 +1,16 @@
+CREATE TABLE comments
+(
+    id_comment          TEXT        NOT NULL   PRIMARY KEY,
+    id_task             TEXT        NOT NULL,
+    author              TEXT        NOT NULL,
+    body                TEXT        NOT NULL,
+    created_date_time   TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX comments_task_idx ON comments (id_task, created_date_time);
+
+CREATE TRIGGER remove_comments_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM comments WHERE comments.id_task = old.id_task;
+END;
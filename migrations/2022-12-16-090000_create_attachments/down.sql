@@ -0,0 +1,2 @@
+DROP TRIGGER remove_attachments_on_task_delete;
+DROP TABLE attachments;
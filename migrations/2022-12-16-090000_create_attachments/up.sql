@@ -0,0 +1,19 @@
+CREATE TABLE attachments
+(
+    id_attachment       TEXT        NOT NULL   PRIMARY KEY,
+    id_task             TEXT        NOT NULL,
+    file_name           TEXT        NOT NULL,
+    mime_type           TEXT,
+    size                BIGINT      NOT NULL,
+    hash                TEXT        NOT NULL,
+    created_date_time   TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX attachments_task_idx ON attachments (id_task);
+CREATE INDEX attachments_hash_idx ON attachments (hash);
+
+CREATE TRIGGER remove_attachments_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM attachments WHERE attachments.id_task = old.id_task;
+END;
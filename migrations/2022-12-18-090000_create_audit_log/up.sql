@@ -0,0 +1,11 @@
+CREATE TABLE audit_log
+(
+    id_entry            TEXT        NOT NULL   PRIMARY KEY,
+    entity              TEXT        NOT NULL,
+    id_entity           TEXT        NOT NULL,
+    action              TEXT        NOT NULL,
+    detail              TEXT,
+    created_date_time   TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE INDEX audit_log_entity_idx ON audit_log (id_entity, created_date_time);
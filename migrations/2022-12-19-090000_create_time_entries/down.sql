@@ -0,0 +1,2 @@
+DROP TRIGGER remove_time_entries_on_task_delete;
+DROP TABLE time_entries;
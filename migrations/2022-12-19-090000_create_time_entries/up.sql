@@ -0,0 +1,18 @@
+CREATE TABLE time_entries
+(
+    id_entry            TEXT        NOT NULL   PRIMARY KEY,
+    id_task             TEXT        NOT NULL,
+    started_at          TIMESTAMP   NOT NULL,
+    stopped_at          TIMESTAMP
+);
+
+CREATE INDEX time_entries_task_idx ON time_entries (id_task, started_at);
+
+-- A task has at most one running timer.
+CREATE UNIQUE INDEX time_entries_running_idx ON time_entries (id_task) WHERE stopped_at IS NULL;
+
+CREATE TRIGGER remove_time_entries_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM time_entries WHERE time_entries.id_task = old.id_task;
+END;
@@ -0,0 +1,2 @@
+DROP TRIGGER remove_pomodoros_on_task_delete;
+DROP TABLE pomodoros;
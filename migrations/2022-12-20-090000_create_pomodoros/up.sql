@@ -0,0 +1,17 @@
+CREATE TABLE pomodoros
+(
+    id_pomodoro         TEXT        NOT NULL   PRIMARY KEY,
+    id_task             TEXT        NOT NULL,
+    length              INTEGER     NOT NULL,
+    started_at          TIMESTAMP   NOT NULL,
+    completed_at        TIMESTAMP
+);
+
+CREATE INDEX pomodoros_task_idx ON pomodoros (id_task);
+CREATE INDEX pomodoros_completed_at_idx ON pomodoros (completed_at);
+
+CREATE TRIGGER remove_pomodoros_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM pomodoros WHERE pomodoros.id_task = old.id_task;
+END;
@@ -0,0 +1 @@
+ALTER TABLE tasks ADD COLUMN estimate_minutes INTEGER;
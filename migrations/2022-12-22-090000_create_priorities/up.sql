@@ -0,0 +1,8 @@
+CREATE TABLE priorities
+(
+    level               INTEGER     NOT NULL   PRIMARY KEY,
+    name                TEXT        NOT NULL,
+    color               TEXT
+);
+
+INSERT INTO priorities (level, name) VALUES (0, 'Low'), (1, 'Normal'), (2, 'High');
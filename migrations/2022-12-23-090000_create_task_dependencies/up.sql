@@ -0,0 +1,15 @@
+CREATE TABLE task_dependencies
+(
+    id_task     TEXT    NOT NULL,
+    id_blocker  TEXT    NOT NULL,
+    PRIMARY KEY (id_task, id_blocker)
+);
+
+CREATE INDEX task_dependencies_blocker_idx ON task_dependencies (id_blocker);
+
+CREATE TRIGGER remove_task_dependencies_on_task_delete
+    BEFORE DELETE ON tasks
+BEGIN
+    DELETE FROM task_dependencies
+    WHERE task_dependencies.id_task = old.id_task OR task_dependencies.id_blocker = old.id_task;
+END;
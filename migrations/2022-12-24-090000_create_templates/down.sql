@@ -0,0 +1,5 @@
+DROP TRIGGER remove_template_tags_on_tag_delete;
+DROP TRIGGER remove_template_details_on_template_delete;
+DROP TABLE template_tags;
+DROP TABLE template_subtasks;
+DROP TABLE templates;
@@ -0,0 +1,40 @@
+CREATE TABLE templates
+(
+    id_template         TEXT        NOT NULL   PRIMARY KEY,
+    name                TEXT        NOT NULL,
+    title               TEXT        NOT NULL,
+    body                TEXT,
+    notes_format        INTEGER     NOT NULL   DEFAULT 0,
+    importance          INTEGER     NOT NULL   DEFAULT 0,
+    due_offset          BIGINT,
+    reminder_offset     BIGINT,
+    created_date_time   TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+CREATE TABLE template_subtasks
+(
+    id_template TEXT    NOT NULL,
+    position    BIGINT  NOT NULL,
+    title       TEXT    NOT NULL,
+    PRIMARY KEY (id_template, position)
+);
+
+CREATE TABLE template_tags
+(
+    id_template TEXT    NOT NULL,
+    id_tag      TEXT    NOT NULL,
+    PRIMARY KEY (id_template, id_tag)
+);
+
+CREATE TRIGGER remove_template_details_on_template_delete
+    BEFORE DELETE ON templates
+BEGIN
+    DELETE FROM template_subtasks WHERE template_subtasks.id_template = old.id_template;
+    DELETE FROM template_tags WHERE template_tags.id_template = old.id_template;
+END;
+
+CREATE TRIGGER remove_template_tags_on_tag_delete
+    BEFORE DELETE ON tags
+BEGIN
+    DELETE FROM template_tags WHERE template_tags.id_tag = old.id_tag;
+END;
@@ -0,0 +1,2 @@
+ALTER TABLE tasks DROP COLUMN emoji;
+ALTER TABLE tasks DROP COLUMN color;
@@ -0,0 +1,2 @@
+ALTER TABLE tasks ADD COLUMN color TEXT;
+ALTER TABLE tasks ADD COLUMN emoji TEXT;
@@ -0,0 +1,5 @@
+DROP INDEX tasks_latitude_idx;
+
+ALTER TABLE tasks DROP COLUMN place_name;
+ALTER TABLE tasks DROP COLUMN longitude;
+ALTER TABLE tasks DROP COLUMN latitude;
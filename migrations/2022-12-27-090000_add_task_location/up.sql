@@ -0,0 +1,5 @@
+ALTER TABLE tasks ADD COLUMN latitude DOUBLE;
+ALTER TABLE tasks ADD COLUMN longitude DOUBLE;
+ALTER TABLE tasks ADD COLUMN place_name TEXT;
+
+CREATE INDEX tasks_latitude_idx ON tasks (latitude);
@@ -0,0 +1,9 @@
+DROP TRIGGER clear_task_status_on_list_change;
+DROP TRIGGER clear_task_status_on_status_delete;
+DROP TRIGGER remove_statuses_on_list_delete;
+
+DROP INDEX tasks_id_status_idx;
+ALTER TABLE tasks DROP COLUMN id_status;
+
+DROP INDEX statuses_parent_list_idx;
+DROP TABLE statuses;
@@ -0,0 +1,35 @@
+CREATE TABLE statuses
+(
+    id_status   TEXT    NOT NULL   PRIMARY KEY,
+    parent_list TEXT    NOT NULL,
+    name        TEXT    NOT NULL,
+    position    BIGINT  NOT NULL   DEFAULT 0,
+    done        BOOLEAN NOT NULL   DEFAULT FALSE,
+    color       TEXT
+);
+
+CREATE INDEX statuses_parent_list_idx ON statuses (parent_list);
+
+ALTER TABLE tasks ADD COLUMN id_status TEXT;
+
+CREATE INDEX tasks_id_status_idx ON tasks (id_status);
+
+CREATE TRIGGER remove_statuses_on_list_delete
+    BEFORE DELETE ON lists
+BEGIN
+    DELETE FROM statuses WHERE statuses.parent_list = old.id_list;
+END;
+
+CREATE TRIGGER clear_task_status_on_status_delete
+    BEFORE DELETE ON statuses
+BEGIN
+    UPDATE tasks SET id_status = NULL WHERE tasks.id_status = old.id_status;
+END;
+
+-- Columns belong to a list, so they don't follow tasks to another one.
+CREATE TRIGGER clear_task_status_on_list_change
+    AFTER UPDATE OF parent_list ON tasks
+    WHEN new.parent_list <> old.parent_list AND new.id_status IS NOT NULL
+BEGIN
+    UPDATE tasks SET id_status = NULL WHERE tasks.id_task = new.id_task;
+END;
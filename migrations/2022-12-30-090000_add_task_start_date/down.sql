@@ -0,0 +1,3 @@
+DROP INDEX tasks_start_date_idx;
+
+ALTER TABLE tasks DROP COLUMN start_date;
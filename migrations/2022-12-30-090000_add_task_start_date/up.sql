@@ -0,0 +1,3 @@
+ALTER TABLE tasks ADD COLUMN start_date TIMESTAMP;
+
+CREATE INDEX tasks_start_date_idx ON tasks (start_date);
@@ -0,0 +1 @@
+ALTER TABLE tasks ADD COLUMN duration_minutes INTEGER;
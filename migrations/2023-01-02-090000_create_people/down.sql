@@ -0,0 +1,6 @@
+DROP TRIGGER clear_task_assignee_on_person_delete;
+
+DROP INDEX tasks_assigned_to_idx;
+ALTER TABLE tasks DROP COLUMN assigned_to;
+
+DROP TABLE people;
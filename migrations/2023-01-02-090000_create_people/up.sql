@@ -0,0 +1,15 @@
+CREATE TABLE people
+(
+    id_person   TEXT    NOT NULL   PRIMARY KEY,
+    name        TEXT    NOT NULL   UNIQUE
+);
+
+ALTER TABLE tasks ADD COLUMN assigned_to TEXT;
+
+CREATE INDEX tasks_assigned_to_idx ON tasks (assigned_to);
+
+CREATE TRIGGER clear_task_assignee_on_person_delete
+    BEFORE DELETE ON people
+BEGIN
+    UPDATE tasks SET assigned_to = NULL WHERE tasks.assigned_to = old.id_person;
+END;
@@ -0,0 +1 @@
+ALTER TABLE tasks ADD COLUMN archived BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,7 @@
+DROP TRIGGER lift_group_contents_on_group_delete;
+
+DROP INDEX lists_id_group_idx;
+ALTER TABLE lists DROP COLUMN id_group;
+
+DROP INDEX list_groups_id_parent_idx;
+DROP TABLE list_groups;
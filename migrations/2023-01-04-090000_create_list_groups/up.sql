@@ -0,0 +1,21 @@
+CREATE TABLE list_groups
+(
+    id_group    TEXT    NOT NULL   PRIMARY KEY,
+    name        TEXT    NOT NULL,
+    id_parent   TEXT,
+    position    BIGINT  NOT NULL   DEFAULT 0
+);
+
+CREATE INDEX list_groups_id_parent_idx ON list_groups (id_parent);
+
+ALTER TABLE lists ADD COLUMN id_group TEXT;
+
+CREATE INDEX lists_id_group_idx ON lists (id_group);
+
+-- What was inside a removed group moves up a level instead of being lost.
+CREATE TRIGGER lift_group_contents_on_group_delete
+    BEFORE DELETE ON list_groups
+BEGIN
+    UPDATE list_groups SET id_parent = old.id_parent WHERE list_groups.id_parent = old.id_group;
+    UPDATE lists SET id_group = old.id_parent WHERE lists.id_group = old.id_group;
+END;
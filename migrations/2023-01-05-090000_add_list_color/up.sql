@@ -0,0 +1 @@
+ALTER TABLE lists ADD COLUMN color TEXT;
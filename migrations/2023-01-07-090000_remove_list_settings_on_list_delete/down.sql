@@ -0,0 +1 @@
+DROP TRIGGER remove_list_settings_on_list_delete;
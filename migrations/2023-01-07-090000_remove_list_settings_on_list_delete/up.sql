@@ -0,0 +1,6 @@
+-- View settings of a list live in the settings table under a key of its own.
+CREATE TRIGGER remove_list_settings_on_list_delete
+    BEFORE DELETE ON lists
+BEGIN
+    DELETE FROM settings WHERE settings.key = 'list_settings:' || old.id_list;
+END;
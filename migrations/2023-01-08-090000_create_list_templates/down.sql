@@ -0,0 +1,6 @@
+DROP TRIGGER remove_list_template_tasks_on_template_delete;
+DROP TRIGGER remove_list_template_tasks_on_list_template_delete;
+
+DROP INDEX list_template_tasks_id_template_idx;
+DROP TABLE list_template_tasks;
+DROP TABLE list_templates;
@@ -0,0 +1,37 @@
+CREATE TABLE list_templates
+(
+    id_list_template    TEXT        NOT NULL   PRIMARY KEY,
+    name                TEXT        NOT NULL,
+    list_name           TEXT        NOT NULL,
+    icon_name           TEXT,
+    provider            TEXT        NOT NULL,
+    color               TEXT,
+    description         TEXT,
+    created_date_time   TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+-- Each task of a list template is kept as a task template of its own.
+CREATE TABLE list_template_tasks
+(
+    id_list_template    TEXT    NOT NULL,
+    position            BIGINT  NOT NULL,
+    id_template         TEXT    NOT NULL,
+    PRIMARY KEY (id_list_template, position)
+);
+
+CREATE INDEX list_template_tasks_id_template_idx ON list_template_tasks (id_template);
+
+CREATE TRIGGER remove_list_template_tasks_on_list_template_delete
+    BEFORE DELETE ON list_templates
+BEGIN
+    DELETE FROM templates WHERE templates.id_template IN
+        (SELECT id_template FROM list_template_tasks
+         WHERE list_template_tasks.id_list_template = old.id_list_template);
+    DELETE FROM list_template_tasks WHERE list_template_tasks.id_list_template = old.id_list_template;
+END;
+
+CREATE TRIGGER remove_list_template_tasks_on_template_delete
+    BEFORE DELETE ON templates
+BEGIN
+    DELETE FROM list_template_tasks WHERE list_template_tasks.id_template = old.id_template;
+END;
@@ -0,0 +1 @@
+ALTER TABLE lists ADD COLUMN protected BOOLEAN NOT NULL DEFAULT 0;
@@ -0,0 +1,8 @@
+DROP TRIGGER tasks_fts_update;
+
+CREATE TRIGGER tasks_fts_update
+    AFTER UPDATE OF id_task, title, body ON tasks
+BEGIN
+    DELETE FROM tasks_fts WHERE tasks_fts.id_task = old.id_task;
+    INSERT INTO tasks_fts (id_task, title, body) VALUES (new.id_task, new.title, new.body);
+END;
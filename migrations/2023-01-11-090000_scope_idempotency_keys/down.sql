@@ -0,0 +1,13 @@
+CREATE TABLE idempotency_keys_unscoped
+(
+    key         TEXT        NOT NULL   PRIMARY KEY,
+    entity      TEXT        NOT NULL,
+    id_entity   TEXT        NOT NULL,
+    created     TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL
+);
+
+INSERT OR IGNORE INTO idempotency_keys_unscoped (key, entity, id_entity, created)
+SELECT key, substr(method, length('create_') + 1), id_entity, created FROM idempotency_keys;
+
+DROP TABLE idempotency_keys;
+ALTER TABLE idempotency_keys_unscoped RENAME TO idempotency_keys;
@@ -0,0 +1,18 @@
+-- Keys are scoped to the method they were sent with, and remember a hash
+-- of the payload so a reused key can't replay an unrelated request. Keys
+-- recorded before have no hash and are matched on the key alone.
+CREATE TABLE idempotency_keys_scoped
+(
+    key           TEXT        NOT NULL,
+    method        TEXT        NOT NULL,
+    payload_hash  TEXT,
+    id_entity     TEXT        NOT NULL,
+    created       TIMESTAMP   DEFAULT CURRENT_TIMESTAMP NOT NULL,
+    PRIMARY KEY (key, method)
+);
+
+INSERT INTO idempotency_keys_scoped (key, method, id_entity, created)
+SELECT key, 'create_' || entity, id_entity, created FROM idempotency_keys;
+
+DROP TABLE idempotency_keys;
+ALTER TABLE idempotency_keys_scoped RENAME TO idempotency_keys;
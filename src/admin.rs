@@ -0,0 +1,171 @@
+use proto_rust::admin::admin_server::Admin;
+use proto_rust::admin::{
+    ActiveStream, ActiveStreamsResponse, AdminResponse, AnonymizedExportResponse,
+    ReplicationStatusResponse, ShutdownRequest, UpdateCheckResponse, VersionInfoResponse,
+};
+use proto_rust::provider::Empty;
+use tonic::{Request, Response, Status};
+
+use std::time::Duration;
+
+use crate::anonymize::export_anonymized;
+use crate::config::UpdateConfig;
+use crate::database::{establish_connection, retry_busy};
+use crate::lifecycle::Lifecycle;
+use crate::replication::SharedReplicationState;
+use crate::search::rebuild_index;
+use crate::supervisor::Supervisor;
+use crate::version::{self, check_for_update};
+
+#[derive(Debug, Default)]
+pub struct AdminService {
+    pub replication: SharedReplicationState,
+    pub supervisor: Supervisor,
+    pub lifecycle: Lifecycle,
+    pub updates: Option<UpdateConfig>,
+}
+
+#[tonic::async_trait]
+impl Admin for AdminService {
+    async fn replication_status(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ReplicationStatusResponse>, Status> {
+        let state = self.replication.lock().unwrap().clone();
+        Ok(Response::new(ReplicationStatusResponse {
+            enabled: state.enabled,
+            target: state.target,
+            last_replicated_at: state.last_replicated_at.map(|d| d.timestamp()),
+            replicated_bytes: state.replicated_bytes,
+            last_error: state.last_error,
+        }))
+    }
+
+    async fn rebuild_search_index(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<AdminResponse>, Status> {
+        let language = request.into_inner();
+        let mut response = AdminResponse::default();
+
+        let send_request =
+            || -> anyhow::Result<()> { rebuild_index(&mut establish_connection()?, &language) };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.successful = true;
+                response.message = "Search index rebuilt successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn get_active_streams(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ActiveStreamsResponse>, Status> {
+        let streams = self
+            .supervisor
+            .active()
+            .into_iter()
+            .map(|task| ActiveStream {
+                id: task.id,
+                name: task.name.to_string(),
+                kind: task.kind.as_str().to_string(),
+                started_at: task.started_at.timestamp(),
+            })
+            .collect();
+        Ok(Response::new(ActiveStreamsResponse { streams }))
+    }
+
+    async fn pause(&self, _request: Request<Empty>) -> Result<Response<AdminResponse>, Status> {
+        self.lifecycle.pause();
+        Ok(Response::new(AdminResponse {
+            successful: true,
+            message: "Provider paused.".to_string(),
+        }))
+    }
+
+    async fn resume(&self, _request: Request<Empty>) -> Result<Response<AdminResponse>, Status> {
+        self.lifecycle.resume();
+        Ok(Response::new(AdminResponse {
+            successful: true,
+            message: "Provider resumed.".to_string(),
+        }))
+    }
+
+    async fn shutdown(
+        &self,
+        request: Request<ShutdownRequest>,
+    ) -> Result<Response<AdminResponse>, Status> {
+        let grace_period = Duration::from_secs(request.into_inner().grace_period_secs.into());
+        self.lifecycle.request_shutdown(grace_period);
+        Ok(Response::new(AdminResponse {
+            successful: true,
+            message: "Provider shutting down.".to_string(),
+        }))
+    }
+
+    async fn get_version_info(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<VersionInfoResponse>, Status> {
+        Ok(Response::new(VersionInfoResponse {
+            version: version::VERSION.to_string(),
+            git_sha: version::GIT_SHA.to_string(),
+            proto_revision: version::PROTO_REVISION.to_string(),
+            build_date: version::build_date(),
+        }))
+    }
+
+    async fn check_for_update(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<UpdateCheckResponse>, Status> {
+        let mut response = UpdateCheckResponse {
+            current_version: version::VERSION.to_string(),
+            ..Default::default()
+        };
+
+        let Some(updates) = &self.updates else {
+            response.message = "Update checks are not configured.".to_string();
+            return Ok(Response::new(response));
+        };
+
+        match check_for_update(updates).await {
+            Ok(Some(release)) => {
+                response.successful = true;
+                response.message = format!("Local provider {} available.", release.version);
+                response.update_available = true;
+                response.latest_version = Some(release.version);
+                response.download_url = release.url;
+            }
+            Ok(None) => {
+                response.successful = true;
+                response.message = "Local provider is up to date.".to_string();
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn export_anonymized(
+        &self,
+        request: Request<u64>,
+    ) -> Result<Response<AnonymizedExportResponse>, Status> {
+        let seed = request.into_inner();
+        let mut response = AnonymizedExportResponse::default();
+
+        match export_anonymized(seed) {
+            Ok(path) => {
+                response.successful = true;
+                response.message = "Anonymized copy exported successfully.".to_string();
+                response.path = path.display().to_string();
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+}
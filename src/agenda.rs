@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::AgendaBlock;
+
+use crate::metadata::RequestContext;
+use crate::models::QueryableTask;
+use crate::schema::tasks;
+
+/// Longest block a task can take, so blocks that start before a range can
+/// still be found by looking back this far.
+pub const MAX_DURATION_MINUTES: i32 = 24 * 60;
+
+/// The blocks of time tasks take between `start` and `end`, in order.
+///
+/// A task with a reminder and a duration takes that long from its reminder.
+/// Other tasks take the whole day they're due.
+pub fn blocks(
+    connection: &mut SqliteConnection,
+    context: &RequestContext,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+) -> Result<Vec<AgendaBlock>> {
+    let look_back = start
+        .checked_sub_signed(Duration::minutes(MAX_DURATION_MINUTES.into()))
+        .context("The range starts before the earliest supported date.")?;
+    let candidates: Vec<QueryableTask> = tasks::table
+        .filter(tasks::archived.eq(false))
+        .filter(
+            tasks::due_date
+                .ge(look_back)
+                .and(tasks::due_date.lt(end))
+                .or(tasks::reminder_date
+                    .ge(look_back)
+                    .and(tasks::reminder_date.lt(end))),
+        )
+        .load(connection)?;
+
+    let mut blocks: Vec<AgendaBlock> = candidates
+        .into_iter()
+        .filter_map(|task| {
+            let (from, to, all_day) = match (
+                task.is_reminder_on,
+                task.reminder_date,
+                task.duration_minutes,
+            ) {
+                // Blocks ending past the latest representable date can't
+                // be shown, nor can the day after the last one.
+                (true, Some(reminder), Some(minutes)) => (
+                    reminder,
+                    reminder.checked_add_signed(Duration::minutes(minutes.into()))?,
+                    false,
+                ),
+                _ => {
+                    // The day it's due where it was set, not where the user is.
+                    let zone = context.in_zone(task.zone());
+                    let day = zone.local_date(task.due_date?);
+                    let from = zone.start_of_day(day);
+                    (from, zone.start_of_day(day.succ_opt()?), true)
+                }
+            };
+            // Zero length blocks still show up at their start.
+            let overlaps = from < end && (to > start || from >= start);
+            overlaps.then(|| AgendaBlock {
+                start: from.timestamp(),
+                end: to.timestamp(),
+                all_day,
+                task: Some(task.into()),
+            })
+        })
+        .collect();
+    blocks.sort_by_key(|block| (block.start, !block.all_day, block.end));
+    Ok(blocks)
+}
@@ -0,0 +1,294 @@
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use diesel::connection::SimpleConnection;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::database::{database_url, establish_connection};
+use crate::schema::{
+    attachments, audit_log, comments, list_groups, list_templates, lists, people, statuses,
+    subtasks, tags, tasks, template_subtasks, templates,
+};
+
+/// What links are replaced with, they often name people and accounts.
+const FAKE_URL: &str = "https://example.com/";
+
+const WORDS: &[&str] = &[
+    "lorem",
+    "ipsum",
+    "dolor",
+    "sit",
+    "amet",
+    "consectetur",
+    "adipiscing",
+    "elit",
+    "sed",
+    "do",
+    "eiusmod",
+    "tempor",
+    "incididunt",
+    "ut",
+    "labore",
+    "et",
+    "dolore",
+    "magna",
+    "aliqua",
+    "enim",
+    "ad",
+    "minim",
+    "veniam",
+    "quis",
+    "nostrud",
+    "exercitation",
+    "ullamco",
+    "laboris",
+    "nisi",
+    "aliquip",
+    "ex",
+    "ea",
+    "commodo",
+    "consequat",
+];
+
+/// Writes a copy of the database next to the original where every title,
+/// note, checklist item, comment, attachment file name, tag, list name and
+/// audit detail is replaced by fake text, and links point to example.com.
+/// Dates, counts and relations are left untouched, so the copy reproduces
+/// the shape of the user's data.
+///
+/// The same `seed` always produces the same text for the same database.
+pub fn export_anonymized(seed: u64) -> Result<PathBuf> {
+    let database = PathBuf::from(database_url()?);
+    let target = database.with_file_name(format!("anonymized-{seed}.db"));
+    if target.exists() {
+        std::fs::remove_file(&target)?;
+    }
+    let path = target
+        .to_str()
+        .context("Failed to convert path to string")?
+        .to_string();
+    diesel::sql_query(format!("VACUUM INTO '{}'", path.replace('\'', "''")))
+        .execute(&mut establish_connection()?)
+        .context("Failed to copy the database")?;
+
+    let mut connection =
+        SqliteConnection::establish(&path).context("Error connecting to the copy")?;
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        type TaskRow = (
+            String,
+            String,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+        );
+        let task_rows: Vec<TaskRow> = tasks::table
+            .select((
+                tasks::id_task,
+                tasks::title,
+                tasks::body,
+                tasks::place_name,
+                tasks::url,
+            ))
+            .load(connection)?;
+        for (id, task_title, task_body, task_place, task_url) in task_rows {
+            diesel::update(tasks::table.filter(tasks::id_task.eq(&id)))
+                .set((
+                    tasks::title.eq(fake_text(seed, &id, &task_title)),
+                    tasks::body.eq(task_body.map(|b| fake_text(seed, &format!("{id}:body"), &b))),
+                    tasks::place_name
+                        .eq(task_place.map(|p| fake_text(seed, &format!("{id}:place"), &p))),
+                    tasks::url.eq(task_url.map(|_| FAKE_URL)),
+                ))
+                .execute(connection)?;
+        }
+        // Coordinates can't be made up without losing what made them
+        // interesting, and they say where the user lives.
+        diesel::update(tasks::table)
+            .set((
+                tasks::latitude.eq(None::<f64>),
+                tasks::longitude.eq(None::<f64>),
+            ))
+            .execute(connection)?;
+
+        let subtask_rows: Vec<(String, String)> = subtasks::table
+            .select((subtasks::id_subtask, subtasks::title))
+            .load(connection)?;
+        for (id, subtask_title) in subtask_rows {
+            diesel::update(subtasks::table.filter(subtasks::id_subtask.eq(&id)))
+                .set(subtasks::title.eq(fake_text(seed, &id, &subtask_title)))
+                .execute(connection)?;
+        }
+
+        let comment_rows: Vec<(String, String, String)> = comments::table
+            .select((comments::id_comment, comments::author, comments::body))
+            .load(connection)?;
+        for (id, comment_author, comment_body) in comment_rows {
+            diesel::update(comments::table.filter(comments::id_comment.eq(&id)))
+                .set((
+                    comments::author.eq(fake_text(seed, &format!("{id}:author"), &comment_author)),
+                    comments::body.eq(fake_text(seed, &id, &comment_body)),
+                ))
+                .execute(connection)?;
+        }
+
+        let attachment_rows: Vec<(String, String)> = attachments::table
+            .select((attachments::id_attachment, attachments::file_name))
+            .load(connection)?;
+        for (id, attachment_name) in attachment_rows {
+            diesel::update(attachments::table.filter(attachments::id_attachment.eq(&id)))
+                .set(attachments::file_name.eq(fake_text(seed, &id, &attachment_name)))
+                .execute(connection)?;
+        }
+
+        let tag_rows: Vec<(String, String)> = tags::table
+            .select((tags::id_tag, tags::name))
+            .load(connection)?;
+        // Tag names are unique, and fake text of a word or two often isn't.
+        for (index, (id, tag_name)) in tag_rows.into_iter().enumerate() {
+            let fake_name = format!("{}-{index}", fake_text(seed, &id, &tag_name));
+            diesel::update(tags::table.filter(tags::id_tag.eq(&id)))
+                .set(tags::name.eq(fake_name))
+                .execute(connection)?;
+        }
+
+        let group_rows: Vec<(String, String)> = list_groups::table
+            .select((list_groups::id_group, list_groups::name))
+            .load(connection)?;
+        for (id, group_name) in group_rows {
+            diesel::update(list_groups::table.filter(list_groups::id_group.eq(&id)))
+                .set(list_groups::name.eq(fake_text(seed, &id, &group_name)))
+                .execute(connection)?;
+        }
+
+        let person_rows: Vec<(String, String)> = people::table
+            .select((people::id_person, people::name))
+            .load(connection)?;
+        // Names are unique too.
+        for (index, (id, person_name)) in person_rows.into_iter().enumerate() {
+            let fake_name = format!("{}-{index}", fake_text(seed, &id, &person_name));
+            diesel::update(people::table.filter(people::id_person.eq(&id)))
+                .set(people::name.eq(fake_name))
+                .execute(connection)?;
+        }
+
+        let template_rows: Vec<(String, String, String, Option<String>)> = templates::table
+            .select((
+                templates::id_template,
+                templates::name,
+                templates::title,
+                templates::body,
+            ))
+            .load(connection)?;
+        for (id, template_name, template_title, template_body) in template_rows {
+            diesel::update(templates::table.filter(templates::id_template.eq(&id)))
+                .set((
+                    templates::name.eq(fake_text(seed, &format!("{id}:name"), &template_name)),
+                    templates::title.eq(fake_text(seed, &id, &template_title)),
+                    templates::body
+                        .eq(template_body.map(|b| fake_text(seed, &format!("{id}:body"), &b))),
+                ))
+                .execute(connection)?;
+        }
+
+        let template_subtask_rows: Vec<(String, i64, String)> =
+            template_subtasks::table.load(connection)?;
+        for (id, template_position, subtask_title) in template_subtask_rows {
+            let key = format!("{id}:{template_position}");
+            diesel::update(
+                template_subtasks::table
+                    .filter(template_subtasks::id_template.eq(&id))
+                    .filter(template_subtasks::position.eq(template_position)),
+            )
+            .set(template_subtasks::title.eq(fake_text(seed, &key, &subtask_title)))
+            .execute(connection)?;
+        }
+
+        let list_template_rows: Vec<(String, String, String, Option<String>)> =
+            list_templates::table
+                .select((
+                    list_templates::id_list_template,
+                    list_templates::name,
+                    list_templates::list_name,
+                    list_templates::description,
+                ))
+                .load(connection)?;
+        for (id, template_name, template_list_name, template_description) in list_template_rows {
+            diesel::update(list_templates::table.filter(list_templates::id_list_template.eq(&id)))
+                .set((
+                    list_templates::name.eq(fake_text(seed, &format!("{id}:name"), &template_name)),
+                    list_templates::list_name.eq(fake_text(seed, &id, &template_list_name)),
+                    list_templates::description.eq(template_description
+                        .map(|d| fake_text(seed, &format!("{id}:description"), &d))),
+                ))
+                .execute(connection)?;
+        }
+
+        let status_rows: Vec<(String, String)> = statuses::table
+            .select((statuses::id_status, statuses::name))
+            .load(connection)?;
+        for (id, status_name) in status_rows {
+            diesel::update(statuses::table.filter(statuses::id_status.eq(&id)))
+                .set(statuses::name.eq(fake_text(seed, &id, &status_name)))
+                .execute(connection)?;
+        }
+
+        let list_rows: Vec<(String, String, Option<String>)> = lists::table
+            .select((lists::id_list, lists::name, lists::description))
+            .load(connection)?;
+        for (id, list_name, list_description) in list_rows {
+            diesel::update(lists::table.filter(lists::id_list.eq(&id)))
+                .set((
+                    lists::name.eq(fake_text(seed, &id, &list_name)),
+                    lists::description
+                        .eq(list_description
+                            .map(|d| fake_text(seed, &format!("{id}:description"), &d))),
+                ))
+                .execute(connection)?;
+        }
+
+        let audit_rows: Vec<(String, Option<String>)> = audit_log::table
+            .select((audit_log::id_entry, audit_log::detail))
+            .load(connection)?;
+        for (id, audit_detail) in audit_rows {
+            diesel::update(audit_log::table.filter(audit_log::id_entry.eq(&id)))
+                .set(audit_log::detail.eq(audit_detail.map(|d| fake_text(seed, &id, &d))))
+                .execute(connection)?;
+        }
+        Ok(())
+    })?;
+    // The search index keeps the original words in its segments until it is
+    // rebuilt, then VACUUM drops the pages that still hold them.
+    connection.batch_execute("INSERT INTO tasks_fts(tasks_fts) VALUES('rebuild'); VACUUM;")?;
+
+    Ok(target)
+}
+
+/// Replaces every word of `text` with a made up one, keeping line breaks
+/// and the number of words.
+fn fake_text(seed: u64, key: &str, text: &str) -> String {
+    let mut state = key
+        .bytes()
+        .fold(seed ^ 0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    text.lines()
+        .map(|line| {
+            line.split_whitespace()
+                .map(|_| {
+                    state = next(state);
+                    WORDS[(state % WORDS.len() as u64) as usize]
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// SplitMix64 step.
+fn next(state: u64) -> u64 {
+    let mut z = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
@@ -0,0 +1,22 @@
+use anyhow::{bail, Result};
+use diesel::{OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::schema::people;
+
+/// Fails unless the task is unassigned or `assigned_to` names a person
+/// that exists. Run it in the transaction that stores the task, so the
+/// person can't be deleted in between.
+pub fn check(connection: &mut SqliteConnection, assigned_to: Option<&str>) -> Result<()> {
+    let Some(person) = assigned_to else {
+        return Ok(());
+    };
+    let found: Option<String> = people::table
+        .find(person)
+        .select(people::id_person)
+        .first(connection)
+        .optional()?;
+    if found.is_none() {
+        bail!("`assigned_to` {person} isn't a known person");
+    }
+    Ok(())
+}
@@ -0,0 +1,121 @@
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime};
+
+use anyhow::{bail, Context, Result};
+use diesel::{QueryDsl, RunQueryDsl, SqliteConnection};
+use sha2::{Digest, Sha256};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+use uuid::Uuid;
+
+use crate::database::database_url;
+use crate::schema::attachments;
+
+/// Size of the messages attachments are downloaded in.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Largest file that can be attached, so an upload can't fill the disk.
+pub const MAX_SIZE: u64 = 100 * 1024 * 1024;
+
+/// Prefix of blobs that are still being uploaded.
+const TEMPORARY_PREFIX: &str = "upload-";
+
+/// Files younger than this may belong to an upload whose row isn't
+/// committed yet, so garbage collection skips them.
+const GRACE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Directory next to the database where attachment contents are stored,
+/// each in a file named after its SHA-256 hash so identical files are only
+/// kept once.
+pub fn store_dir() -> Result<PathBuf> {
+    let dir = PathBuf::from(database_url()?).with_file_name("attachments");
+    std::fs::create_dir_all(&dir)?;
+    Ok(dir)
+}
+
+pub fn blob_path(hash: &str) -> Result<PathBuf> {
+    Ok(store_dir()?.join(hash))
+}
+
+/// Writes an uploaded file to the store while hashing it.
+pub struct BlobWriter {
+    file: File,
+    path: PathBuf,
+    hasher: Sha256,
+    size: u64,
+    finished: bool,
+}
+
+impl BlobWriter {
+    pub async fn new() -> Result<Self> {
+        let path = store_dir()?.join(format!("{TEMPORARY_PREFIX}{}", Uuid::new_v4()));
+        let file = File::create(&path)
+            .await
+            .context("Failed to create attachment file.")?;
+        Ok(Self {
+            file,
+            path,
+            hasher: Sha256::new(),
+            size: 0,
+            finished: false,
+        })
+    }
+
+    /// Appends `data`, failing once the file grows past `MAX_SIZE`. The
+    /// partial file is removed when the writer is dropped.
+    pub async fn write(&mut self, data: &[u8]) -> Result<()> {
+        self.size += data.len() as u64;
+        if self.size > MAX_SIZE {
+            bail!(
+                "The attachment is larger than {} MiB.",
+                MAX_SIZE / 1024 / 1024
+            );
+        }
+        self.hasher.update(data);
+        self.file.write_all(data).await?;
+        Ok(())
+    }
+
+    /// Moves the file to its place in the store and returns its hash and
+    /// size.
+    pub async fn finish(mut self) -> Result<(String, u64)> {
+        self.file.sync_all().await?;
+        let hash = format!("{:x}", self.hasher.clone().finalize());
+        tokio::fs::rename(&self.path, blob_path(&hash)?).await?;
+        self.finished = true;
+        Ok((hash, self.size))
+    }
+}
+
+impl Drop for BlobWriter {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+}
+
+/// Deletes stored files no attachment refers to anymore and returns how
+/// many were removed. Recent files are left alone.
+pub fn remove_unreferenced(connection: &mut SqliteConnection) -> Result<usize> {
+    let referenced: HashSet<String> = attachments::table
+        .select(attachments::hash)
+        .load::<String>(connection)?
+        .into_iter()
+        .collect();
+    let mut removed = 0;
+    for entry in std::fs::read_dir(store_dir()?)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        let age = SystemTime::now()
+            .duration_since(entry.metadata()?.modified()?)
+            .unwrap_or_default();
+        if age < GRACE_PERIOD || referenced.contains(&name) {
+            continue;
+        }
+        std::fs::remove_file(entry.path())?;
+        removed += 1;
+    }
+    Ok(removed)
+}
@@ -0,0 +1,30 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{ExpressionMethods, RunQueryDsl, SqliteConnection};
+use uuid::Uuid;
+
+use crate::schema::audit_log;
+
+pub const REMINDER_SNOOZED: &str = "reminder_snoozed";
+
+/// Appends an entry to the audit trail, which keeps a record of actions
+/// taken on an entity besides plain edits.
+pub fn record(
+    connection: &mut SqliteConnection,
+    entity: &str,
+    id: &str,
+    action: &str,
+    detail: Option<String>,
+) -> Result<()> {
+    diesel::insert_into(audit_log::table)
+        .values((
+            audit_log::id_entry.eq(Uuid::new_v4().to_string()),
+            audit_log::entity.eq(entity),
+            audit_log::id_entity.eq(id),
+            audit_log::action.eq(action),
+            audit_log::detail.eq(detail),
+            audit_log::created_date_time.eq(Utc::now().naive_utc()),
+        ))
+        .execute(connection)?;
+    Ok(())
+}
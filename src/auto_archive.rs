@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::{ChangeKind, TaskStatus};
+
+use crate::database::{establish_connection, retry_busy};
+use crate::events::Changes;
+use crate::lifecycle::Lifecycle;
+use crate::list_settings;
+use crate::models::QueryableTask;
+use crate::schema::tasks;
+
+const ARCHIVE_PERIOD: Duration = Duration::from_secs(60 * 60);
+
+/// Longest a list can wait before archiving its completed tasks.
+pub const MAX_ARCHIVE_AFTER_DAYS: i32 = 10 * 365;
+
+/// Archives the completed tasks of every list with an `archive_after_days`
+/// policy once they have been completed for that long, and returns them.
+pub fn sweep(connection: &mut SqliteConnection) -> Result<Vec<QueryableTask>> {
+    let policies = list_settings::archive_policies(connection)?;
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let now = Utc::now().naive_utc();
+        let mut archived = vec![];
+        for (list, days) in policies {
+            // Policies stored before they were bounded may reach past the
+            // earliest representable date, nothing is that old.
+            let Some(cutoff) = now.checked_sub_signed(chrono::Duration::days(days as i64)) else {
+                continue;
+            };
+            let expired = tasks::table
+                .filter(tasks::parent_list.eq(&list))
+                .filter(tasks::status.eq(TaskStatus::Completed as i32))
+                .filter(tasks::completed_on.le(cutoff))
+                .filter(tasks::archived.eq(false))
+                .select(tasks::id_task);
+            let ids: Vec<String> = expired.load(connection)?;
+            if ids.is_empty() {
+                continue;
+            }
+            diesel::update(tasks::table.filter(tasks::id_task.eq_any(&ids)))
+                .set((
+                    tasks::archived.eq(true),
+                    tasks::last_modified_date_time.eq(now),
+                ))
+                .execute(connection)?;
+            let moved: Vec<QueryableTask> = tasks::table
+                .filter(tasks::id_task.eq_any(&ids))
+                .load(connection)?;
+            archived.extend(moved);
+        }
+        Ok(archived)
+    })
+}
+
+/// Sweeps completed tasks into the archive every hour, skipping sweeps
+/// while the provider is paused.
+pub async fn schedule(changes: Changes, lifecycle: Lifecycle) -> Result<()> {
+    let mut interval = tokio::time::interval(ARCHIVE_PERIOD);
+    loop {
+        interval.tick().await;
+        if lifecycle.is_paused() {
+            continue;
+        }
+        let archived =
+            tokio::task::spawn_blocking(|| retry_busy(|| sweep(&mut establish_connection()?)));
+        match archived.await {
+            Ok(Ok(archived)) => {
+                if !archived.is_empty() {
+                    tracing::info!("Archived {} completed tasks.", archived.len());
+                }
+                for task in archived {
+                    changes.task(ChangeKind::TaskUpdated, task.into());
+                }
+            }
+            Ok(Err(err)) => tracing::error!("Failed to archive completed tasks: {err}"),
+            Err(err) => tracing::error!("The archive sweep stopped unexpectedly: {err}"),
+        }
+    }
+}
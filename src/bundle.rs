@@ -0,0 +1,194 @@
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::{List, Subtask, Tag, Task};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::inbox;
+use crate::models::{QueryableList, QueryableSubtask, QueryableTag, QueryableTask};
+use crate::ordering::next_list_position;
+use crate::priorities;
+use crate::schema::{lists, subtasks, tags, task_tags, tasks};
+use crate::validation::Validate;
+
+/// Revision of the bundle format, bumped when older plugins can't read it.
+const FORMAT: u32 = 1;
+
+/// A list and everything needed to recreate it on another machine. Board
+/// columns, assignees, comments and attachments stay behind, they only make
+/// sense in the database they came from.
+#[derive(Serialize, Deserialize)]
+struct Contents {
+    format: u32,
+    exported_at: NaiveDateTime,
+    list: QueryableList,
+    tasks: Vec<BundledTask>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundledTask {
+    task: QueryableTask,
+    subtasks: Vec<QueryableSubtask>,
+    /// Tag names, ids differ between databases.
+    tags: Vec<String>,
+}
+
+/// What is handed over: the contents exactly as they were written and their
+/// SHA-256 digest, so a bundle that was cut short or damaged on the way is
+/// refused instead of imported half right. The digest isn't keyed, anyone
+/// can edit a bundle and recompute it, so imports check the contents too.
+#[derive(Serialize, Deserialize)]
+struct Bundle {
+    contents: String,
+    /// Called `signature` by the first plugins that wrote bundles.
+    #[serde(alias = "signature")]
+    digest: String,
+}
+
+fn digest(contents: &str) -> String {
+    format!("{:x}", Sha256::digest(contents.as_bytes()))
+}
+
+/// Holds an imported entity to the same rules as one sent in a request.
+fn check(entity: &impl Validate) -> Result<()> {
+    entity
+        .validate()
+        .map_err(|status| anyhow!("The bundle isn't valid: {}", status.message()))
+}
+
+/// Packs `list` and its tasks into a JSON bundle.
+pub fn export(
+    connection: &mut SqliteConnection,
+    list: &str,
+    provider: &str,
+    locale: &str,
+) -> Result<String> {
+    let list: QueryableList = if inbox::is_inbox(list) {
+        inbox::list(provider, locale)
+    } else {
+        lists::table
+            .find(list)
+            .first(connection)
+            .context("The list doesn't exist.")?
+    };
+    let children: Vec<QueryableTask> = tasks::table
+        .filter(tasks::parent_list.eq(&list.id_list))
+        .order((tasks::position.asc(), tasks::created_date_time.asc()))
+        .load(connection)?;
+
+    let mut bundled = vec![];
+    for task in children {
+        let checklist = subtasks::table
+            .filter(subtasks::id_task.eq(&task.id_task))
+            .order(subtasks::position.asc())
+            .load(connection)?;
+        let tagged = task_tags::table
+            .filter(task_tags::id_task.eq(&task.id_task))
+            .select(task_tags::id_tag);
+        let names = tags::table
+            .filter(tags::id_tag.eq_any(tagged))
+            .select(tags::name)
+            .load(connection)?;
+        bundled.push(BundledTask {
+            task,
+            subtasks: checklist,
+            tags: names,
+        });
+    }
+
+    let contents = serde_json::to_string(&Contents {
+        format: FORMAT,
+        exported_at: Utc::now().naive_utc(),
+        list,
+        tasks: bundled,
+    })?;
+    let digest = digest(&contents);
+    Ok(serde_json::to_string(&Bundle { contents, digest })?)
+}
+
+/// Recreates the list in `bundle` under new ids, so importing the same
+/// bundle twice gives two lists. Tags are matched by name and created when
+/// missing.
+pub fn import(
+    connection: &mut SqliteConnection,
+    bundle: &str,
+) -> Result<(QueryableList, Vec<QueryableTask>)> {
+    let bundle: Bundle = serde_json::from_str(bundle).context("The bundle isn't valid.")?;
+    if digest(&bundle.contents) != bundle.digest {
+        bail!("The bundle is damaged, export it again.");
+    }
+    let contents: Contents =
+        serde_json::from_str(&bundle.contents).context("The bundle isn't valid.")?;
+    if contents.format > FORMAT {
+        bail!("The bundle was made by a newer version, update to import it.");
+    }
+
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let mut list = contents.list.duplicate(&contents.list.name);
+        list.position = next_list_position(connection)?;
+        list.archived = false;
+        list.pinned = false;
+        list.id_group = None;
+        list.protected = false;
+        check(&List::from(list.clone()))?;
+        diesel::insert_into(lists::table)
+            .values(&list)
+            .execute(connection)?;
+
+        let mut known: Vec<QueryableTag> = tags::table.load(connection)?;
+        let mut created = vec![];
+        for bundled in contents.tasks {
+            let mut task = bundled.task.copy_to_list(&list.id_list);
+            task.assigned_to = None;
+            task.last_modified_date_time = Utc::now().naive_utc();
+            check(&Task::from(task.clone()))?;
+            priorities::check(connection, task.importance)?;
+            diesel::insert_into(tasks::table)
+                .values(&task)
+                .execute(connection)?;
+
+            let checklist: Vec<QueryableSubtask> = bundled
+                .subtasks
+                .iter()
+                .map(|subtask| subtask.copy_to_task(&task.id_task))
+                .collect();
+            for subtask in &checklist {
+                check(&Subtask::from(subtask.clone()))?;
+            }
+            diesel::insert_into(subtasks::table)
+                .values(&checklist)
+                .execute(connection)?;
+
+            for name in bundled.tags {
+                let tag = match known
+                    .iter()
+                    .find(|tag| tag.name.eq_ignore_ascii_case(&name))
+                {
+                    Some(tag) => tag.clone(),
+                    None => {
+                        let tag = QueryableTag {
+                            id_tag: Uuid::new_v4().to_string(),
+                            name,
+                        };
+                        check(&Tag::from(tag.clone()))?;
+                        diesel::insert_into(tags::table)
+                            .values(&tag)
+                            .execute(connection)?;
+                        known.push(tag.clone());
+                        tag
+                    }
+                };
+                diesel::insert_or_ignore_into(task_tags::table)
+                    .values((
+                        task_tags::id_task.eq(&task.id_task),
+                        task_tags::id_tag.eq(&tag.id_tag),
+                    ))
+                    .execute(connection)?;
+            }
+            created.push(task);
+        }
+        Ok((list, created))
+    })
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use anyhow::{Context, Result};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::attachments::remove_unreferenced;
+use crate::database::{establish_connection, retry_busy};
+use crate::idempotency::remove_expired;
+use crate::inbox::INBOX_ID;
+use crate::lifecycle::Lifecycle;
+use crate::models::{LIST_ENTITY, TASK_ENTITY};
+use crate::schema::{
+    attachments, comments, list_template_tasks, list_templates, lists, pomodoros, recent_items,
+    statuses, subtasks, tags, task_dependencies, task_tags, tasks, template_subtasks,
+    template_tags, templates, time_entries,
+};
+
+const CLEANUP_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Removes rows that point at entities which no longer exist and returns
+/// how many rows were removed from each table.
+pub fn remove_orphans(connection: &mut SqliteConnection) -> Result<HashMap<String, i64>> {
+    let mut removed = HashMap::new();
+
+    let orphaned_tasks = diesel::delete(
+        tasks::table
+            .filter(tasks::parent_list.ne(INBOX_ID))
+            .filter(tasks::parent_list.ne_all(lists::table.select(lists::id_list))),
+    )
+    .execute(connection)
+    .context("Failed to remove orphaned tasks.")?;
+    removed.insert("tasks".to_string(), orphaned_tasks as i64);
+
+    // The search index isn't in the schema, its rows are matched by hand.
+    let orphaned_search_rows =
+        diesel::sql_query("DELETE FROM tasks_fts WHERE id_task NOT IN (SELECT id_task FROM tasks)")
+            .execute(connection)?;
+    removed.insert("tasks_fts".to_string(), orphaned_search_rows as i64);
+
+    let orphaned_subtasks = diesel::delete(
+        subtasks::table.filter(subtasks::id_task.ne_all(tasks::table.select(tasks::id_task))),
+    )
+    .execute(connection)?;
+    removed.insert("subtasks".to_string(), orphaned_subtasks as i64);
+
+    let orphaned_comments = diesel::delete(
+        comments::table.filter(comments::id_task.ne_all(tasks::table.select(tasks::id_task))),
+    )
+    .execute(connection)?;
+    removed.insert("comments".to_string(), orphaned_comments as i64);
+
+    let orphaned_attachments = diesel::delete(
+        attachments::table.filter(attachments::id_task.ne_all(tasks::table.select(tasks::id_task))),
+    )
+    .execute(connection)?;
+    removed.insert("attachments".to_string(), orphaned_attachments as i64);
+    let unreferenced_files = remove_unreferenced(connection)?;
+    removed.insert("attachment_files".to_string(), unreferenced_files as i64);
+
+    let orphaned_time_entries = diesel::delete(
+        time_entries::table
+            .filter(time_entries::id_task.ne_all(tasks::table.select(tasks::id_task))),
+    )
+    .execute(connection)?;
+    removed.insert("time_entries".to_string(), orphaned_time_entries as i64);
+
+    let orphaned_pomodoros = diesel::delete(
+        pomodoros::table.filter(pomodoros::id_task.ne_all(tasks::table.select(tasks::id_task))),
+    )
+    .execute(connection)?;
+    removed.insert("pomodoros".to_string(), orphaned_pomodoros as i64);
+
+    let orphaned_dependencies = diesel::delete(
+        task_dependencies::table.filter(
+            task_dependencies::id_task
+                .ne_all(tasks::table.select(tasks::id_task))
+                .or(task_dependencies::id_blocker.ne_all(tasks::table.select(tasks::id_task))),
+        ),
+    )
+    .execute(connection)?;
+    removed.insert(
+        "task_dependencies".to_string(),
+        orphaned_dependencies as i64,
+    );
+
+    let orphaned_task_tags = diesel::delete(
+        task_tags::table.filter(
+            task_tags::id_task
+                .ne_all(tasks::table.select(tasks::id_task))
+                .or(task_tags::id_tag.ne_all(tags::table.select(tags::id_tag))),
+        ),
+    )
+    .execute(connection)?;
+    removed.insert("task_tags".to_string(), orphaned_task_tags as i64);
+
+    let orphaned_statuses = diesel::delete(
+        statuses::table.filter(statuses::parent_list.ne_all(lists::table.select(lists::id_list))),
+    )
+    .execute(connection)?;
+    removed.insert("statuses".to_string(), orphaned_statuses as i64);
+
+    let orphaned_template_subtasks = diesel::delete(template_subtasks::table.filter(
+        template_subtasks::id_template.ne_all(templates::table.select(templates::id_template)),
+    ))
+    .execute(connection)?;
+    removed.insert(
+        "template_subtasks".to_string(),
+        orphaned_template_subtasks as i64,
+    );
+
+    let orphaned_template_tags = diesel::delete(
+        template_tags::table.filter(
+            template_tags::id_template
+                .ne_all(templates::table.select(templates::id_template))
+                .or(template_tags::id_tag.ne_all(tags::table.select(tags::id_tag))),
+        ),
+    )
+    .execute(connection)?;
+    removed.insert("template_tags".to_string(), orphaned_template_tags as i64);
+
+    let orphaned_list_template_tasks = diesel::delete(
+        list_template_tasks::table.filter(
+            list_template_tasks::id_list_template
+                .ne_all(list_templates::table.select(list_templates::id_list_template))
+                .or(list_template_tasks::id_template
+                    .ne_all(templates::table.select(templates::id_template))),
+        ),
+    )
+    .execute(connection)?;
+    removed.insert(
+        "list_template_tasks".to_string(),
+        orphaned_list_template_tasks as i64,
+    );
+
+    let orphaned_recent_tasks = diesel::delete(
+        recent_items::table
+            .filter(recent_items::kind.eq(TASK_ENTITY))
+            .filter(recent_items::id_item.ne_all(tasks::table.select(tasks::id_task))),
+    )
+    .execute(connection)?;
+    let orphaned_recent_lists = diesel::delete(
+        recent_items::table
+            .filter(recent_items::kind.eq(LIST_ENTITY))
+            .filter(recent_items::id_item.ne_all(lists::table.select(lists::id_list))),
+    )
+    .execute(connection)?;
+    removed.insert(
+        "recent_items".to_string(),
+        (orphaned_recent_tasks + orphaned_recent_lists) as i64,
+    );
+
+    let expired_keys = remove_expired(connection)?;
+    removed.insert("idempotency_keys".to_string(), expired_keys as i64);
+
+    if removed.values().any(|count| *count > 0) {
+        diesel::sql_query("VACUUM").execute(connection)?;
+    }
+
+    Ok(removed)
+}
+
+/// Periodically runs [`remove_orphans`] for as long as the server is alive,
+/// skipping runs while the provider is paused.
+pub async fn schedule(lifecycle: Lifecycle) -> Result<()> {
+    let mut interval = tokio::time::interval(CLEANUP_PERIOD);
+    loop {
+        interval.tick().await;
+        if lifecycle.is_paused() {
+            continue;
+        }
+        let removed = tokio::task::spawn_blocking(|| {
+            retry_busy(|| remove_orphans(&mut establish_connection()?))
+        });
+        match removed.await {
+            Ok(Ok(removed)) => tracing::info!("Orphaned data cleanup finished: {removed:?}"),
+            Ok(Err(err)) => tracing::error!("Orphaned data cleanup failed: {err}"),
+            Err(err) => tracing::error!("The orphaned data cleanup stopped unexpectedly: {err}"),
+        }
+    }
+}
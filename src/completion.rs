@@ -0,0 +1,109 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::{CompletionSettings, TaskStatus};
+
+use crate::models::QueryableTask;
+use crate::recurrence::spawn_next;
+use crate::schema::{subtasks, tasks};
+use crate::settings;
+
+/// Whether completing a task checks off its whole checklist.
+const COMPLETE_SUBTASKS_KEY: &str = "complete_subtasks_with_task";
+
+/// Whether checking off the last open subtask completes the task.
+const COMPLETE_TASK_KEY: &str = "complete_task_with_subtasks";
+
+/// Both cascades are off until turned on.
+pub fn read(connection: &mut SqliteConnection) -> Result<CompletionSettings> {
+    Ok(CompletionSettings {
+        complete_subtasks: flag(connection, COMPLETE_SUBTASKS_KEY)?,
+        complete_task: flag(connection, COMPLETE_TASK_KEY)?,
+    })
+}
+
+pub fn write(connection: &mut SqliteConnection, value: &CompletionSettings) -> Result<()> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        settings::set(
+            connection,
+            COMPLETE_SUBTASKS_KEY,
+            &value.complete_subtasks.to_string(),
+        )?;
+        settings::set(
+            connection,
+            COMPLETE_TASK_KEY,
+            &value.complete_task.to_string(),
+        )
+    })
+}
+
+/// Checks off the open subtasks of `task`, which was just completed, when
+/// the cascade is on.
+pub fn complete_subtasks(connection: &mut SqliteConnection, task: &str) -> Result<()> {
+    if !flag(connection, COMPLETE_SUBTASKS_KEY)? {
+        return Ok(());
+    }
+    diesel::update(
+        subtasks::table
+            .filter(subtasks::id_task.eq(task))
+            .filter(subtasks::completed.eq(false)),
+    )
+    .set((
+        subtasks::completed.eq(true),
+        subtasks::last_modified_date_time.eq(Utc::now().naive_utc()),
+    ))
+    .execute(connection)?;
+    Ok(())
+}
+
+/// Follows up on `task` having just been completed: checks off its
+/// checklist when the cascade is on and creates the next occurrence when it
+/// repeats, which is returned. Runs in the caller's transaction so neither
+/// happens without the other.
+pub fn completed(
+    connection: &mut SqliteConnection,
+    task: &QueryableTask,
+) -> Result<Option<QueryableTask>> {
+    complete_subtasks(connection, &task.id_task)?;
+    spawn_next(connection, task)
+}
+
+/// Completes `task` once every subtask is checked off, when the cascade is
+/// on. Returns the next occurrence when the task repeats.
+pub fn complete_task(
+    connection: &mut SqliteConnection,
+    task: &str,
+) -> Result<Option<QueryableTask>> {
+    if !flag(connection, COMPLETE_TASK_KEY)? {
+        return Ok(None);
+    }
+    let open: i64 = subtasks::table
+        .filter(subtasks::id_task.eq(task))
+        .filter(subtasks::completed.eq(false))
+        .count()
+        .get_result(connection)?;
+    if open > 0 {
+        return Ok(None);
+    }
+    let now = Utc::now().naive_utc();
+    let completed = diesel::update(
+        tasks::table
+            .find(task)
+            .filter(tasks::status.ne(TaskStatus::Completed as i32)),
+    )
+    .set((
+        tasks::status.eq(TaskStatus::Completed as i32),
+        tasks::completed_on.eq(now),
+        tasks::last_modified_date_time.eq(now),
+    ))
+    .execute(connection)?;
+    if completed == 0 {
+        return Ok(None);
+    }
+    let stored: QueryableTask = tasks::table.find(task).first(connection)?;
+    spawn_next(connection, &stored)
+}
+
+fn flag(connection: &mut SqliteConnection, key: &str) -> Result<bool> {
+    Ok(settings::get(connection, key)?.is_some_and(|value| value == "true"))
+}
@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use chrono_tz::Tz;
+use libset::project::Project;
+use serde::{Deserialize, Serialize};
+use tonic::transport::{Certificate, Identity, ServerTlsConfig};
+
+const CONFIG_NAME: &str = "config.json";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Language of the user's tasks, used to tune the search index.
+    pub language: String,
+    /// IANA timezone used to decide what "today" is, UTC when unset.
+    pub timezone: Option<String>,
+    pub replication: Option<ReplicationConfig>,
+    pub updates: Option<UpdateConfig>,
+    pub streaming: StreamingConfig,
+    /// Address the server listens on over TCP.
+    pub address: String,
+    pub tls: Option<TlsConfig>,
+    pub rate_limit: Option<RateLimitConfig>,
+    /// Address of the HTTP listener that exports Prometheus metrics, off
+    /// when unset.
+    pub metrics_address: Option<String>,
+    /// Creates a personal list and puts a few onboarding tasks in it and in
+    /// the inbox the first time the plugin starts on an empty database.
+    pub welcome_lists: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            language: "en".to_string(),
+            timezone: None,
+            replication: None,
+            updates: None,
+            streaming: StreamingConfig::default(),
+            address: "[::1]:7007".to_string(),
+            tls: None,
+            rate_limit: None,
+            metrics_address: None,
+            welcome_lists: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TlsConfig {
+    /// PEM encoded certificate chain of the server.
+    pub cert: PathBuf,
+    /// PEM encoded private key of the server.
+    pub key: PathBuf,
+    /// PEM encoded CA that client certificates must be signed by. When set,
+    /// clients without a valid certificate are rejected.
+    #[serde(default)]
+    pub client_ca: Option<PathBuf>,
+}
+
+/// Token bucket applied to every peer, per method.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Rate {
+    pub requests_per_second: f64,
+    pub burst: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Rate used for methods without their own entry.
+    #[serde(flatten)]
+    pub default: Rate,
+    /// Rates for specific methods, keyed by their full path such as
+    /// `/provider.Provider/ReadAllTasks`.
+    #[serde(default)]
+    pub methods: HashMap<String, Rate>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamingConfig {
+    /// Messages buffered per stream before the server waits for the client.
+    pub channel_capacity: usize,
+    /// Upper bound for the chunk size clients can ask for.
+    pub max_chunk_size: usize,
+}
+
+impl Default for StreamingConfig {
+    fn default() -> Self {
+        Self {
+            channel_capacity: 4,
+            max_chunk_size: 500,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplicationConfig {
+    /// Directory that receives a copy of the database.
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+    /// S3-compatible bucket that receives a copy of the database.
+    #[serde(default)]
+    pub s3: Option<S3Config>,
+    #[serde(default = "default_replication_interval")]
+    pub interval_secs: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3Config {
+    pub endpoint: String,
+    pub bucket: String,
+    #[serde(default = "default_region")]
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    #[serde(default)]
+    pub prefix: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateConfig {
+    /// Path or http(s) URL of the release manifest, a JSON document such as
+    /// `{"version": "0.4.2", "url": "https://..."}`.
+    pub manifest: String,
+}
+
+fn default_replication_interval() -> u64 {
+    10
+}
+
+fn default_region() -> String {
+    "us-east-1".to_string()
+}
+
+impl Config {
+    /// Reads the configuration file from the plugin's project directory,
+    /// falling back to the defaults when it hasn't been written yet.
+    pub fn load() -> Result<Self> {
+        let path = Project::open("dev", "edfloreshz", "local-plugin")
+            .ok()
+            .and_then(|project| project.path())
+            .map(|path| path.join(CONFIG_NAME));
+
+        match path {
+            Some(path) if path.exists() => {
+                let contents = std::fs::read_to_string(&path)?;
+                serde_json::from_str(&contents).context("Failed to parse the configuration file")
+            }
+            _ => Ok(Config::default()),
+        }
+    }
+
+    /// Server TLS settings built from the configured certificate and key.
+    pub fn tls(&self) -> Result<Option<ServerTlsConfig>> {
+        let Some(tls) = &self.tls else {
+            return Ok(None);
+        };
+        let cert = std::fs::read(&tls.cert)
+            .with_context(|| format!("Failed to read {}", tls.cert.display()))?;
+        let key = std::fs::read(&tls.key)
+            .with_context(|| format!("Failed to read {}", tls.key.display()))?;
+        let mut config = ServerTlsConfig::new().identity(Identity::from_pem(cert, key));
+        if let Some(client_ca) = &tls.client_ca {
+            let ca = std::fs::read(client_ca)
+                .with_context(|| format!("Failed to read {}", client_ca.display()))?;
+            config = config.client_ca_root(Certificate::from_pem(ca));
+        }
+        Ok(Some(config))
+    }
+
+    pub fn timezone(&self) -> Result<Tz> {
+        match &self.timezone {
+            Some(timezone) => timezone
+                .parse()
+                .map_err(|err| anyhow::anyhow!("Invalid timezone {timezone}: {err}")),
+            None => Ok(Tz::UTC),
+        }
+    }
+}
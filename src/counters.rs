@@ -0,0 +1,116 @@
+use anyhow::Result;
+use diesel::sql_types::{BigInt, Bool, Integer, Nullable, Text, Timestamp};
+use diesel::{QueryableByName, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::{ListCounter, TaskStatus};
+
+use crate::inbox::{self, INBOX_ID};
+use crate::metadata::RequestContext;
+use crate::models::QueryableList;
+
+/// The inbox is counted like the stored lists while it holds tasks, or when
+/// it's asked for.
+const COUNTERS_QUERY: &str = "
+    SELECT lists.id_list AS id_list,
+           COUNT(tasks.id_task) AS total,
+           COALESCE(SUM(tasks.status = ?1), 0) AS completed,
+           COALESCE(SUM(tasks.status != ?1), 0) AS incomplete,
+           COALESCE(SUM(tasks.status != ?1 AND tasks.due_date < ?2), 0) AS overdue,
+           COALESCE(SUM(tasks.status != ?1 AND tasks.due_date >= ?2 AND tasks.due_date < ?3), 0)
+               AS due_today
+    FROM (SELECT id_list FROM lists
+          UNION ALL
+          SELECT '' WHERE ?4 = ''
+              OR EXISTS (SELECT 1 FROM tasks WHERE parent_list = '' AND NOT archived)) AS lists
+    LEFT JOIN tasks ON tasks.parent_list = lists.id_list AND NOT tasks.archived
+    WHERE ?4 IS NULL OR lists.id_list = ?4
+    GROUP BY lists.id_list";
+
+/// Task counts of a single list. Overdue and due today only count tasks that
+/// are not completed yet.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ListCounters {
+    #[diesel(sql_type = Text)]
+    pub id_list: String,
+    #[diesel(sql_type = BigInt)]
+    pub total: i64,
+    #[diesel(sql_type = BigInt)]
+    pub completed: i64,
+    #[diesel(sql_type = BigInt)]
+    pub incomplete: i64,
+    #[diesel(sql_type = BigInt)]
+    pub overdue: i64,
+    #[diesel(sql_type = BigInt)]
+    pub due_today: i64,
+}
+
+impl From<ListCounters> for ListCounter {
+    fn from(value: ListCounters) -> Self {
+        ListCounter {
+            list_id: value.id_list,
+            total: value.total,
+            completed: value.completed,
+            incomplete: value.incomplete,
+            overdue: value.overdue,
+            due_today: value.due_today,
+        }
+    }
+}
+
+/// Lists joined against their counters, in sidebar order.
+fn lists_with_counters_query() -> String {
+    format!(
+        "SELECT lists.*,
+                counters.total, counters.completed, counters.incomplete,
+                counters.overdue, counters.due_today
+         FROM lists
+         JOIN ({COUNTERS_QUERY}) AS counters ON counters.id_list = lists.id_list
+         WHERE ?5 OR NOT lists.archived
+         ORDER BY lists.pinned DESC, lists.position ASC"
+    )
+}
+
+/// Counts the tasks of every list, or only of `list` when given, in a
+/// single aggregate query.
+pub fn list_counters(
+    connection: &mut SqliteConnection,
+    context: &RequestContext,
+    list: Option<&str>,
+) -> Result<Vec<ListCounters>> {
+    let today = context.today();
+    let start = context.start_of_day(today);
+    let end = context.start_of_day(today + chrono::Duration::days(1));
+    let counters = diesel::sql_query(COUNTERS_QUERY)
+        .bind::<Integer, _>(TaskStatus::Completed as i32)
+        .bind::<Timestamp, _>(start)
+        .bind::<Timestamp, _>(end)
+        .bind::<Nullable<Text>, _>(list)
+        .load::<ListCounters>(connection)?;
+    Ok(counters)
+}
+
+/// Every list along with its counters, skipping archived lists unless
+/// `include_archived` is set, in a single statement. The inbox comes first
+/// while it holds tasks.
+pub fn lists_with_counters(
+    connection: &mut SqliteConnection,
+    context: &RequestContext,
+    provider: &str,
+    include_archived: bool,
+) -> Result<Vec<(QueryableList, ListCounters)>> {
+    let today = context.today();
+    let start = context.start_of_day(today);
+    let end = context.start_of_day(today + chrono::Duration::days(1));
+    let mut results = diesel::sql_query(lists_with_counters_query())
+        .bind::<Integer, _>(TaskStatus::Completed as i32)
+        .bind::<Timestamp, _>(start)
+        .bind::<Timestamp, _>(end)
+        .bind::<Nullable<Text>, _>(None::<&str>)
+        .bind::<Bool, _>(include_archived)
+        .load::<(QueryableList, ListCounters)>(connection)?;
+    if inbox::has_tasks(connection)? {
+        if let Some(counters) = list_counters(connection, context, Some(INBOX_ID))?.pop() {
+            results.insert(0, (inbox::list(provider, &context.locale), counters));
+        }
+    }
+    Ok(results)
+}
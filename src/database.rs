@@ -1,11 +1,30 @@
 use crate::diesel_migrations::MigrationHarness;
+use std::sync::Mutex;
+use std::time::Duration;
+
 use anyhow::{Context, Result};
-use diesel::{Connection, SqliteConnection};
+use diesel::result::Error as DieselError;
+use diesel::{Connection, RunQueryDsl, SqliteConnection};
 use diesel_migrations::EmbeddedMigrations;
 use libset::{format::FileFormat, new_file, project::Project};
+use rand::Rng;
+
+use crate::metrics;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
-const DATABASE_NAME: &str = "done_database.db";
+pub const DATABASE_NAME: &str = "done_database.db";
+
+/// Attempts made before giving up on a busy database.
+const BUSY_ATTEMPTS: u32 = 5;
+/// Delay before the first retry, doubled on every attempt.
+const BUSY_BACKOFF: Duration = Duration::from_millis(20);
+/// How long SQLite waits for a lock held by another connection before
+/// reporting the database as busy. Kept short since handlers wait on the
+/// runtime, the retries spread the rest of the wait out.
+const BUSY_TIMEOUT_MS: u32 = 200;
+
+/// Whether this process already brought the schema up to date.
+static MIGRATED: Mutex<bool> = Mutex::new(false);
 
 fn migrate_database() -> Result<()> {
     let local_plugin_project = Project::new("dev", "edfloreshz", "local-plugin")
@@ -33,7 +52,7 @@ fn migration_status() -> Result<()> {
     Ok(())
 }
 
-fn database_url() -> Result<String> {
+pub fn database_url() -> Result<String> {
     let database_url = Project::open("dev", "edfloreshz", "local-plugin")?
         .path()
         .context("The project has not been created")?
@@ -57,6 +76,54 @@ pub fn establish_connection() -> Result<SqliteConnection> {
 
     let mut connection =
         SqliteConnection::establish(url.as_str()).context("Error connecting to database")?;
-    connection.run_pending_migrations(MIGRATIONS).unwrap();
+    metrics::connection_opened();
+    diesel::sql_query(format!("PRAGMA busy_timeout = {BUSY_TIMEOUT_MS}"))
+        .execute(&mut connection)
+        .context("Failed to configure the connection")?;
+
+    let mut migrated = MIGRATED.lock().unwrap_or_else(|err| err.into_inner());
+    if !*migrated {
+        connection
+            .run_pending_migrations(MIGRATIONS)
+            .map_err(|err| anyhow::anyhow!("Failed to run migrations: {err}"))?;
+        *migrated = true;
+    }
     Ok(connection)
 }
+
+/// Whether `error` was caused by another connection holding a lock on the
+/// database, e.g. a backup job or a second process.
+fn is_busy(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        matches!(
+            cause.downcast_ref::<DieselError>(),
+            Some(DieselError::DatabaseError(_, info))
+                if info.message().contains("database is locked")
+                    || info.message().contains("database table is locked")
+                    || info.message().contains("database is busy")
+        )
+    })
+}
+
+/// Runs `operation`, running it again with jittered exponential backoff
+/// while the database is busy.
+///
+/// SQLite's busy timeout waits for locks first, the retries cover what it
+/// can't, such as a transaction that had to be rolled back because another
+/// connection started writing first. Together they give up after about a
+/// second and a half, which is how long a handler can hold its worker.
+pub fn retry_busy<T>(mut operation: impl FnMut() -> Result<T>) -> Result<T> {
+    let mut backoff = BUSY_BACKOFF;
+    for _ in 1..BUSY_ATTEMPTS {
+        match operation() {
+            Err(err) if is_busy(&err) => {
+                let jitter = rand::thread_rng().gen_range(0..=backoff.as_millis() as u64);
+                tracing::debug!("Database is busy, retrying: {err}");
+                std::thread::sleep(backoff + Duration::from_millis(jitter));
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    operation()
+}
@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Result};
+use diesel::dsl::sql;
+use diesel::sql_types::{Bool, Integer};
+use diesel::{BoolExpressionMethods, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::TaskStatus;
+
+use crate::models::QueryableTask;
+use crate::schema::{task_dependencies, tasks};
+
+/// Tasks `task` directly waits on.
+fn blockers(connection: &mut SqliteConnection, task: &str) -> Result<Vec<String>> {
+    let blockers = task_dependencies::table
+        .filter(task_dependencies::id_task.eq(task))
+        .select(task_dependencies::id_blocker)
+        .load(connection)?;
+    Ok(blockers)
+}
+
+/// Whether `task` waits on `target`, directly or through other tasks.
+fn depends_on(connection: &mut SqliteConnection, task: &str, target: &str) -> Result<bool> {
+    let mut visited = HashSet::new();
+    let mut pending = vec![task.to_string()];
+    while let Some(current) = pending.pop() {
+        if current == target {
+            return Ok(true);
+        }
+        if visited.insert(current.clone()) {
+            pending.extend(blockers(connection, &current)?);
+        }
+    }
+    Ok(false)
+}
+
+/// Makes `task` wait on `blocker`, refusing dependencies that would make
+/// a task wait on itself.
+pub fn add(connection: &mut SqliteConnection, task: &str, blocker: &str) -> Result<()> {
+    if task == blocker {
+        bail!("A task can't depend on itself.");
+    }
+    for id in [task, blocker] {
+        tasks::table
+            .find(id)
+            .select(tasks::id_task)
+            .first::<String>(connection)
+            .with_context(|| format!("The task {id} doesn't exist."))?;
+    }
+    if depends_on(connection, blocker, task)? {
+        bail!("The dependency would create a cycle.");
+    }
+    diesel::insert_or_ignore_into(task_dependencies::table)
+        .values((
+            task_dependencies::id_task.eq(task),
+            task_dependencies::id_blocker.eq(blocker),
+        ))
+        .execute(connection)?;
+    Ok(())
+}
+
+pub fn remove(connection: &mut SqliteConnection, task: &str, blocker: &str) -> Result<()> {
+    let removed = diesel::delete(
+        task_dependencies::table
+            .filter(task_dependencies::id_task.eq(task))
+            .filter(task_dependencies::id_blocker.eq(blocker)),
+    )
+    .execute(connection)?;
+    if removed == 0 {
+        bail!("The dependency doesn't exist.");
+    }
+    Ok(())
+}
+
+/// Open tasks that wait on at least one other open task.
+pub fn blocked_tasks(connection: &mut SqliteConnection) -> Result<Vec<QueryableTask>> {
+    let results = tasks::table
+        .filter(tasks::status.ne(TaskStatus::Completed as i32))
+        .filter(tasks::archived.eq(false))
+        .filter(
+            sql::<Bool>(
+                "tasks.id_task IN (SELECT dependency.id_task FROM task_dependencies dependency \
+                 JOIN tasks blocker ON blocker.id_task = dependency.id_blocker \
+                 WHERE blocker.status != ",
+            )
+            .bind::<Integer, _>(TaskStatus::Completed as i32)
+            .sql(")"),
+        )
+        .order(tasks::created_date_time.asc())
+        .load(connection)?;
+    Ok(results)
+}
+
+/// Moves the dependencies of the merged `duplicates` over to `primary`,
+/// dropping the ones that would now form a cycle.
+pub fn reassign(
+    connection: &mut SqliteConnection,
+    duplicates: &[String],
+    primary: &str,
+) -> Result<()> {
+    let moved: Vec<(String, String)> = task_dependencies::table
+        .filter(
+            task_dependencies::id_task
+                .eq_any(duplicates)
+                .or(task_dependencies::id_blocker.eq_any(duplicates)),
+        )
+        .select((task_dependencies::id_task, task_dependencies::id_blocker))
+        .load(connection)?;
+    diesel::delete(
+        task_dependencies::table.filter(
+            task_dependencies::id_task
+                .eq_any(duplicates)
+                .or(task_dependencies::id_blocker.eq_any(duplicates)),
+        ),
+    )
+    .execute(connection)?;
+
+    let replace = |id: String| {
+        if duplicates.contains(&id) {
+            primary.to_string()
+        } else {
+            id
+        }
+    };
+    for (task, blocker) in moved {
+        let (task, blocker) = (replace(task), replace(blocker));
+        if task != blocker && !depends_on(connection, &blocker, &task)? {
+            diesel::insert_or_ignore_into(task_dependencies::table)
+                .values((
+                    task_dependencies::id_task.eq(&task),
+                    task_dependencies::id_blocker.eq(&blocker),
+                ))
+                .execute(connection)?;
+        }
+    }
+    Ok(())
+}
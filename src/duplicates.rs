@@ -0,0 +1,75 @@
+use anyhow::Result;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::TaskStatus;
+
+use crate::models::QueryableTask;
+use crate::schema::tasks;
+
+/// How alike two titles must be, from 0 to 1, to be reported.
+const THRESHOLD: f64 = 0.8;
+
+/// Most candidates returned for a single task.
+const MAX_CANDIDATES: usize = 5;
+
+/// Incomplete tasks of the same list whose title looks like the title of
+/// `task`, most alike first.
+pub fn candidates(
+    connection: &mut SqliteConnection,
+    task: &QueryableTask,
+) -> Result<Vec<QueryableTask>> {
+    let title = normalize(&task.title);
+    if title.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let open: Vec<QueryableTask> = tasks::table
+        .filter(tasks::parent_list.eq(&task.parent_list))
+        .filter(tasks::status.ne(TaskStatus::Completed as i32))
+        .filter(tasks::archived.eq(false))
+        .load(connection)?;
+
+    let mut scored: Vec<(f64, QueryableTask)> = open
+        .into_iter()
+        .map(|candidate| (similarity(&title, &normalize(&candidate.title)), candidate))
+        .filter(|(score, _)| *score >= THRESHOLD)
+        .collect();
+    scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+    Ok(scored
+        .into_iter()
+        .take(MAX_CANDIDATES)
+        .map(|(_, candidate)| candidate)
+        .collect())
+}
+
+/// Lowercases `title` and collapses punctuation and runs of whitespace, so
+/// "Buy milk!" and "buy  milk" compare equal.
+fn normalize(title: &str) -> String {
+    title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Edit distance between `a` and `b` scaled to 1 for equal strings and 0
+/// for entirely different ones.
+fn similarity(a: &str, b: &str) -> f64 {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, x) in a.iter().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, y) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(x != y);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    1.0 - previous[b.len()] as f64 / longest as f64
+}
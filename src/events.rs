@@ -0,0 +1,50 @@
+use chrono::Utc;
+use proto_rust::provider::{ChangeEvent, ChangeKind, List, Task};
+use tokio::sync::broadcast;
+
+/// Events buffered per subscriber before it starts missing changes.
+pub const CAPACITY: usize = 256;
+
+/// Fans out a [`ChangeEvent`] to every `watch_changes` stream whenever a
+/// mutation commits.
+#[derive(Debug, Clone)]
+pub struct Changes {
+    sender: broadcast::Sender<ChangeEvent>,
+}
+
+impl Default for Changes {
+    fn default() -> Self {
+        let (sender, _) = broadcast::channel(CAPACITY);
+        Self { sender }
+    }
+}
+
+impl Changes {
+    pub fn subscribe(&self) -> broadcast::Receiver<ChangeEvent> {
+        self.sender.subscribe()
+    }
+
+    pub fn task(&self, kind: ChangeKind, task: Task) {
+        self.publish(kind, task.id.clone(), Some(task), None);
+    }
+
+    pub fn list(&self, kind: ChangeKind, list: List) {
+        self.publish(kind, list.id.clone(), None, Some(list));
+    }
+
+    /// Publishes a change for which only the id of the entity is known.
+    pub fn id(&self, kind: ChangeKind, id: impl Into<String>) {
+        self.publish(kind, id.into(), None, None);
+    }
+
+    fn publish(&self, kind: ChangeKind, id: String, task: Option<Task>, list: Option<List>) {
+        // Sending only fails when nobody is watching.
+        let _ = self.sender.send(ChangeEvent {
+            kind: kind as i32,
+            id,
+            task,
+            list,
+            timestamp: Utc::now().timestamp(),
+        });
+    }
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use chrono::Utc;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection};
+use tonic::metadata::MetadataMap;
+
+use crate::models::QueryableRecentItem;
+use crate::schema::recent_items;
+
+/// Metadata key the host sets on reads that were triggered by the user,
+/// as opposed to background refreshes.
+const USER_ACTION_KEY: &str = "x-user-action";
+
+pub fn is_user_action(metadata: &MetadataMap) -> bool {
+    metadata
+        .get(USER_ACTION_KEY)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value == "true" || value == "1")
+        .unwrap_or_default()
+}
+
+/// Bumps the frecency score of an item.
+pub fn record_access(connection: &mut SqliteConnection, id: &str, kind: &str) -> Result<()> {
+    let now = Utc::now().naive_utc();
+    let mut item = recent_items::table
+        .find(id)
+        .first::<QueryableRecentItem>(connection)
+        .optional()?
+        .unwrap_or_else(|| QueryableRecentItem::new(id, kind, now));
+    item.visit(now);
+
+    diesel::insert_into(recent_items::table)
+        .values(&item)
+        .on_conflict(recent_items::id_item)
+        .do_update()
+        .set(&item)
+        .execute(connection)?;
+    Ok(())
+}
+
+/// Returns the `limit` items with the highest frecency.
+pub fn recent_items(
+    connection: &mut SqliteConnection,
+    limit: usize,
+) -> Result<Vec<(QueryableRecentItem, f64)>> {
+    let now = Utc::now().naive_utc();
+    let mut items: Vec<(QueryableRecentItem, f64)> = recent_items::table
+        .order(recent_items::last_visited.desc())
+        .load::<QueryableRecentItem>(connection)?
+        .into_iter()
+        .map(|item| {
+            let score = item.frecency(now);
+            (item, score)
+        })
+        .collect();
+    items.sort_by(|a, b| b.1.total_cmp(&a.1));
+    items.truncate(limit);
+    Ok(items)
+}
@@ -0,0 +1,86 @@
+use anyhow::{bail, Context, Result};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::models::QueryableListGroup;
+use crate::ordering::next_group_position;
+use crate::schema::{list_groups, lists};
+
+/// Every list group, in sidebar order.
+pub fn read_all(connection: &mut SqliteConnection) -> Result<Vec<QueryableListGroup>> {
+    let groups = list_groups::table
+        .order(list_groups::position.asc())
+        .load(connection)?;
+    Ok(groups)
+}
+
+/// Adds `group` after the last one.
+pub fn create(
+    connection: &mut SqliteConnection,
+    mut group: QueryableListGroup,
+) -> Result<QueryableListGroup> {
+    if let Some(parent) = &group.id_parent {
+        exists(connection, parent)?;
+    }
+    group.position = next_group_position(connection)?;
+    diesel::insert_into(list_groups::table)
+        .values(&group)
+        .execute(connection)
+        .context("Failed to create group.")?;
+    Ok(group)
+}
+
+/// Renames `group` and moves it under another parent. Positions are left
+/// alone, they are changed through `reorder_groups`.
+pub fn update(
+    connection: &mut SqliteConnection,
+    group: &QueryableListGroup,
+) -> Result<QueryableListGroup> {
+    exists(connection, &group.id_group)?;
+    // Walks up from the new parent, which must not lead back to the group.
+    let mut ancestor = group.id_parent.clone();
+    while let Some(id) = ancestor {
+        if id == group.id_group {
+            bail!("A group can't be nested inside itself.");
+        }
+        ancestor = list_groups::table
+            .find(&id)
+            .select(list_groups::id_parent)
+            .first(connection)
+            .optional()?
+            .context("The parent group doesn't exist.")?;
+    }
+
+    diesel::update(list_groups::table.find(&group.id_group))
+        .set((
+            list_groups::name.eq(&group.name),
+            list_groups::id_parent.eq(&group.id_parent),
+        ))
+        .execute(connection)
+        .context("Failed to update group.")?;
+    let updated = list_groups::table.find(&group.id_group).first(connection)?;
+    Ok(updated)
+}
+
+/// Removes `group`, moving its lists and groups up a level. Returns the
+/// lists that moved.
+pub fn remove(connection: &mut SqliteConnection, group: &str) -> Result<Vec<String>> {
+    let moved = lists::table
+        .filter(lists::id_group.eq(group))
+        .select(lists::id_list)
+        .load(connection)?;
+    let removed = diesel::delete(list_groups::table.find(group)).execute(connection)?;
+    if removed == 0 {
+        bail!("The group doesn't exist.");
+    }
+    Ok(moved)
+}
+
+/// Fails unless `group` exists.
+pub fn exists(connection: &mut SqliteConnection, group: &str) -> Result<()> {
+    list_groups::table
+        .find(group)
+        .select(list_groups::id_group)
+        .first::<String>(connection)
+        .with_context(|| format!("The group {group} doesn't exist."))?;
+    Ok(())
+}
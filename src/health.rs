@@ -0,0 +1,49 @@
+use std::time::Duration;
+
+use anyhow::Result;
+use diesel::connection::SimpleConnection;
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+
+use crate::database::establish_connection;
+use crate::lifecycle::Lifecycle;
+
+/// Name the provider service is reported under.
+pub const PROVIDER_SERVICE: &str = "provider.Provider";
+
+const CHECK_PERIOD: Duration = Duration::from_secs(10);
+
+/// Keeps the reported health in line with the database and the lifecycle.
+///
+/// The provider is not serving until the database has been opened and
+/// migrated, whenever it can't be reached afterwards, and while the host
+/// has it paused. The server as a whole follows the database only.
+pub async fn monitor(mut reporter: HealthReporter, lifecycle: Lifecycle) -> Result<()> {
+    let mut interval = tokio::time::interval(CHECK_PERIOD);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {}
+            _ = lifecycle.pause_changed() => {}
+        }
+
+        let database = match establish_connection()
+            .and_then(|mut connection| Ok(connection.batch_execute("SELECT 1;")?))
+        {
+            Ok(()) => ServingStatus::Serving,
+            Err(err) => {
+                tracing::warn!("Database unreachable: {err}");
+                ServingStatus::NotServing
+            }
+        };
+        let provider = if lifecycle.is_paused() {
+            ServingStatus::NotServing
+        } else {
+            database
+        };
+
+        reporter.set_service_status("", database).await;
+        reporter
+            .set_service_status(PROVIDER_SERVICE, provider)
+            .await;
+    }
+}
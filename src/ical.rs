@@ -0,0 +1,191 @@
+//! Exports a list as an RFC 5545 calendar of to-dos, so it can be imported
+//! into calendar applications.
+
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{ExpressionMethods, JoinOnDsl, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::TaskStatus;
+
+use crate::inbox;
+use crate::models::{QueryableList, QueryableTask};
+use crate::priorities;
+use crate::recurrence::Rule;
+use crate::schema::{lists, tags, task_tags, tasks};
+
+const PRODUCT_ID: &str = "-//edfloreshz//Done Local Plugin//EN";
+
+/// Longest content line in octets, longer ones are folded.
+const MAX_LINE: usize = 75;
+
+/// Builds the lines of a calendar, escaping values and folding long lines.
+#[derive(Default)]
+struct Calendar {
+    contents: String,
+}
+
+impl Calendar {
+    /// Adds a property whose value is written as is.
+    fn raw(&mut self, name: &str, value: &str) {
+        let line = format!("{name}:{value}");
+        let mut octets = 0;
+        for c in line.chars() {
+            if octets + c.len_utf8() > MAX_LINE {
+                // Continuation lines start with a space, which counts.
+                self.contents.push_str("\r\n ");
+                octets = 1;
+            }
+            self.contents.push(c);
+            octets += c.len_utf8();
+        }
+        self.contents.push_str("\r\n");
+    }
+
+    /// Adds a text property.
+    fn text(&mut self, name: &str, value: &str) {
+        self.raw(name, &escape(value));
+    }
+
+    fn date_time(&mut self, name: &str, value: NaiveDateTime) {
+        self.raw(name, &value.format("%Y%m%dT%H%M%SZ").to_string());
+    }
+}
+
+/// Escapes the characters RFC 5545 reserves in text values.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace("\r\n", "\\n")
+        .replace(['\r', '\n'], "\\n")
+}
+
+/// RFC 5545 ranks priorities from 1, the highest, to 9, the lowest. The
+/// configured levels are spread over that range, `levels` being sorted
+/// lowest first.
+fn priority(levels: &[i32], importance: i32) -> u32 {
+    let Some(rank) = levels.iter().position(|level| *level == importance) else {
+        return 0;
+    };
+    if levels.len() < 2 {
+        return 0;
+    }
+    let highest = (levels.len() - 1) as f64;
+    9 - (rank as f64 * 8.0 / highest).round() as u32
+}
+
+/// The tasks of `list` that aren't archived as a `VCALENDAR` of `VTODO`
+/// components, with their dates, priority, status, repetition, tags and
+/// reminder.
+pub fn export(
+    connection: &mut SqliteConnection,
+    list: &str,
+    provider: &str,
+    locale: &str,
+) -> Result<String> {
+    let list: QueryableList = if inbox::is_inbox(list) {
+        inbox::list(provider, locale)
+    } else {
+        lists::table
+            .find(list)
+            .first(connection)
+            .context("The list doesn't exist.")?
+    };
+    let children: Vec<QueryableTask> = tasks::table
+        .filter(tasks::parent_list.eq(&list.id_list))
+        .filter(tasks::archived.eq(false))
+        .order((tasks::position.asc(), tasks::created_date_time.asc()))
+        .load(connection)?;
+    let mut levels: Vec<i32> = priorities::read(connection)?
+        .into_iter()
+        .map(|priority| priority.level)
+        .collect();
+    levels.sort_unstable();
+    let mut names: HashMap<String, Vec<String>> = HashMap::new();
+    let tagged: Vec<(String, String)> = task_tags::table
+        .inner_join(tags::table.on(tags::id_tag.eq(task_tags::id_tag)))
+        .inner_join(tasks::table.on(tasks::id_task.eq(task_tags::id_task)))
+        .filter(tasks::parent_list.eq(&list.id_list))
+        .filter(tasks::archived.eq(false))
+        .select((task_tags::id_task, tags::name))
+        .order(tags::name.asc())
+        .load(connection)?;
+    for (task, name) in tagged {
+        names.entry(task).or_default().push(name);
+    }
+
+    let now = Utc::now().naive_utc();
+    let mut calendar = Calendar::default();
+    calendar.raw("BEGIN", "VCALENDAR");
+    calendar.raw("VERSION", "2.0");
+    calendar.raw("PRODID", PRODUCT_ID);
+    calendar.text("X-WR-CALNAME", &list.name);
+    for task in children {
+        calendar.raw("BEGIN", "VTODO");
+        calendar.text("UID", &format!("{}@local-plugin", task.id_task));
+        calendar.date_time("DTSTAMP", now);
+        calendar.date_time("CREATED", task.created_date_time);
+        calendar.date_time("LAST-MODIFIED", task.last_modified_date_time);
+        calendar.text("SUMMARY", &task.title);
+        if let Some(body) = &task.body {
+            calendar.text("DESCRIPTION", body);
+        }
+        let rule = task
+            .recurrence_rule
+            .as_deref()
+            .and_then(|rule| rule.parse::<Rule>().ok());
+        // Repeating to-dos need a start for their series, the due date is
+        // where it starts.
+        let start = task.start_date.or(rule.as_ref().and(task.due_date));
+        if let Some(start) = start {
+            calendar.date_time("DTSTART", start);
+        }
+        if let Some(due) = task.due_date {
+            calendar.date_time("DUE", due);
+        }
+        if let Some(rule) = &rule {
+            calendar.raw("RRULE", &rule.to_string());
+        }
+        let priority = priority(&levels, task.importance);
+        if priority > 0 {
+            calendar.raw("PRIORITY", &priority.to_string());
+        }
+        if task.status == TaskStatus::Completed as i32 {
+            calendar.raw("STATUS", "COMPLETED");
+            calendar.raw("PERCENT-COMPLETE", "100");
+            if let Some(completed) = task.completed_on {
+                calendar.date_time("COMPLETED", completed);
+            }
+        } else {
+            calendar.raw("STATUS", "NEEDS-ACTION");
+        }
+        if let Some(names) = names.get(&task.id_task) {
+            let names: Vec<String> = names.iter().map(|name| escape(name)).collect();
+            calendar.raw("CATEGORIES", &names.join(","));
+        }
+        if let Some(url) = &task.url {
+            calendar.raw("URL", url);
+        }
+        if let Some(place) = &task.place_name {
+            calendar.text("LOCATION", place);
+        }
+        if let (Some(latitude), Some(longitude)) = (task.latitude, task.longitude) {
+            calendar.raw("GEO", &format!("{latitude};{longitude}"));
+        }
+        if let (true, Some(reminder)) = (task.is_reminder_on, task.reminder_date) {
+            calendar.raw("BEGIN", "VALARM");
+            calendar.raw("ACTION", "DISPLAY");
+            calendar.text("DESCRIPTION", &task.title);
+            calendar.raw(
+                "TRIGGER;VALUE=DATE-TIME",
+                &reminder.format("%Y%m%dT%H%M%SZ").to_string(),
+            );
+            calendar.raw("END", "VALARM");
+        }
+        calendar.raw("END", "VTODO");
+    }
+    calendar.raw("END", "VCALENDAR");
+    Ok(calendar.contents)
+}
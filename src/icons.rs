@@ -0,0 +1,123 @@
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use once_cell::sync::Lazy;
+
+/// Extensions an icon theme may ship icons in.
+const EXTENSIONS: &[&str] = &["png", "svg", "xpm"];
+
+/// Themes nest icons as `<theme>/<size>/<context>/<icon>`, a few extra
+/// levels cover scaled directories without walking the whole tree.
+const MAX_DEPTH: usize = 5;
+
+/// Names of the icons installed on the system, read once since themes rarely
+/// change while the plugin runs.
+static INSTALLED: Lazy<BTreeSet<String>> = Lazy::new(|| {
+    let mut names = BTreeSet::new();
+    for dir in base_directories() {
+        collect(&dir, 0, &mut names);
+    }
+    tracing::debug!("Found {} installed icons", names.len());
+    names
+});
+
+/// Where icon themes are looked up, following the freedesktop icon theme
+/// specification: `~/.icons`, `icons` under each data directory and
+/// `/usr/share/pixmaps`.
+fn base_directories() -> Vec<PathBuf> {
+    let home = env::var_os("HOME").map(PathBuf::from);
+    let data_home = env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| home.as_ref().map(|home| home.join(".local/share")));
+    let data_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|dirs| !dirs.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+
+    let mut directories = vec![];
+    if let Some(home) = home {
+        directories.push(home.join(".icons"));
+    }
+    directories.extend(data_home.map(|dir| dir.join("icons")));
+    directories.extend(
+        env::split_paths(&data_dirs)
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.join("icons")),
+    );
+    directories.push(PathBuf::from("/usr/share/pixmaps"));
+    directories
+}
+
+fn collect(dir: &Path, depth: usize, names: &mut BTreeSet<String>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            if depth < MAX_DEPTH {
+                collect(&path, depth + 1, names);
+            }
+            continue;
+        }
+        let icon = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .filter(|extension| EXTENSIONS.contains(extension))
+            .and(path.file_stem())
+            .and_then(|stem| stem.to_str());
+        if let Some(icon) = icon {
+            names.insert(icon.to_string());
+        }
+    }
+}
+
+/// Reads the installed icons ahead of the first lookup, which would
+/// otherwise walk every theme directory while answering a request.
+pub fn preload() {
+    Lazy::force(&INSTALLED);
+}
+
+/// Icon names are ASCII words joined by dashes, such as
+/// `folder-music-symbolic`, or application ids like `dev.edfloreshz.Done`.
+pub fn well_formed(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .split('-')
+            .all(|word| !word.is_empty() && word.chars().all(is_icon_char))
+}
+
+fn is_icon_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
+
+/// Whether `name` can be drawn. Like toolkits do, a name that isn't
+/// installed falls back on its shorter forms, so `folder-music-symbolic`
+/// is found through `folder-music` or `folder`. When no theme can be found
+/// at all, as in sandboxes that hide them, any well formed name is taken.
+pub fn exists(name: &str) -> bool {
+    if !well_formed(name) {
+        return false;
+    }
+    if INSTALLED.is_empty() {
+        return true;
+    }
+    let mut candidate = name;
+    loop {
+        if INSTALLED.contains(candidate) {
+            return true;
+        }
+        match candidate.rsplit_once('-') {
+            Some((shorter, _)) => candidate = shorter,
+            None => return false,
+        }
+    }
+}
+
+/// The installed icon names, sorted, for frontends to offer in a picker.
+pub fn available() -> Vec<String> {
+    INSTALLED.iter().cloned().collect()
+}
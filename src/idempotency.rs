@@ -0,0 +1,81 @@
+use anyhow::{bail, Result};
+use chrono::{Duration, Utc};
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection};
+use prost::Message;
+use sha2::{Digest, Sha256};
+use tonic::metadata::MetadataMap;
+
+use crate::schema::idempotency_keys;
+
+/// Metadata key the host sets on create requests it may retry, so a retry
+/// of a request that already went through doesn't create a second entity.
+const IDEMPOTENCY_KEY: &str = "x-idempotency-key";
+
+/// How long keys are remembered, far longer than any host retries for.
+const RETENTION_HOURS: i64 = 24;
+
+pub fn idempotency_key(metadata: &MetadataMap) -> Option<String> {
+    metadata
+        .get(IDEMPOTENCY_KEY)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Hash of the encoded request, so a replay can be told apart from another
+/// request that reuses its key.
+pub fn payload_hash(payload: &impl Message) -> String {
+    format!("{:x}", Sha256::digest(payload.encode_to_vec()))
+}
+
+/// Id of the entity created by an earlier `method` request sent with `key`.
+/// Fails when that request carried a different payload.
+pub fn replayed(
+    connection: &mut SqliteConnection,
+    key: &str,
+    method: &str,
+    hash: &str,
+) -> Result<Option<String>> {
+    let found: Option<(Option<String>, String)> = idempotency_keys::table
+        .find((key, method))
+        .select((idempotency_keys::payload_hash, idempotency_keys::id_entity))
+        .first(connection)
+        .optional()?;
+    match found {
+        Some((Some(stored), _)) if stored != hash => {
+            bail!("The idempotency key was already used for a different request.")
+        }
+        Some((_, id)) => Ok(Some(id)),
+        None => Ok(None),
+    }
+}
+
+/// Records that the `method` request sent with `key` and a payload hashing
+/// to `hash` created `id`.
+pub fn remember(
+    connection: &mut SqliteConnection,
+    key: &str,
+    method: &str,
+    hash: &str,
+    id: &str,
+) -> Result<()> {
+    diesel::insert_into(idempotency_keys::table)
+        .values((
+            idempotency_keys::key.eq(key),
+            idempotency_keys::method.eq(method),
+            idempotency_keys::payload_hash.eq(hash),
+            idempotency_keys::id_entity.eq(id),
+            idempotency_keys::created.eq(Utc::now().naive_utc()),
+        ))
+        .execute(connection)?;
+    Ok(())
+}
+
+/// Forgets keys older than the retention period.
+pub fn remove_expired(connection: &mut SqliteConnection) -> Result<usize> {
+    let threshold = Utc::now().naive_utc() - Duration::hours(RETENTION_HOURS);
+    let removed =
+        diesel::delete(idempotency_keys::table.filter(idempotency_keys::created.lt(threshold)))
+            .execute(connection)?;
+    Ok(removed)
+}
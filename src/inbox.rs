@@ -0,0 +1,48 @@
+use anyhow::Result;
+use diesel::dsl::{exists, select};
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::models::QueryableList;
+use crate::schema::tasks;
+
+/// Tasks with an empty `parent_list` belong to the inbox, a list that isn't
+/// stored, so tasks can be captured before a list is picked.
+pub const INBOX_ID: &str = "";
+
+pub fn is_inbox(list: &str) -> bool {
+    list == INBOX_ID
+}
+
+/// The name of the inbox in `locale`, English for languages without one.
+pub fn name(locale: &str) -> &'static str {
+    let language = locale.split(['-', '_']).next().unwrap_or_default();
+    match language.to_lowercase().as_str() {
+        "es" => "Bandeja de entrada",
+        "fr" => "Boîte de réception",
+        "de" => "Eingang",
+        "pt" => "Caixa de entrada",
+        _ => "Inbox",
+    }
+}
+
+/// The inbox as a list named in `locale`, pinned above the stored lists and
+/// protected since there's nothing to delete.
+pub fn list(provider: &str, locale: &str) -> QueryableList {
+    let mut list = QueryableList::new(name(locale), Some("📥".to_string()), provider.to_string());
+    list.id_list = INBOX_ID.to_string();
+    list.pinned = true;
+    list.protected = true;
+    list
+}
+
+/// Whether any task that isn't archived is waiting in the inbox. The inbox
+/// is only listed next to the stored lists while it holds tasks.
+pub fn has_tasks(connection: &mut SqliteConnection) -> Result<bool> {
+    let waiting = select(exists(
+        tasks::table
+            .filter(tasks::parent_list.eq(INBOX_ID))
+            .filter(tasks::archived.eq(false)),
+    ))
+    .get_result(connection)?;
+    Ok(waiting)
+}
@@ -0,0 +1,83 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{watch, Notify};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+
+/// Grace period used when the process is interrupted instead of being
+/// shut down by the host.
+pub const DEFAULT_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+/// Lets the host pause, resume and shut down the plugin over the Admin
+/// service.
+#[derive(Debug, Clone)]
+pub struct Lifecycle {
+    paused: Arc<AtomicBool>,
+    paused_changed: Arc<Notify>,
+    shutdown: Arc<watch::Sender<Option<Duration>>>,
+}
+
+impl Default for Lifecycle {
+    fn default() -> Self {
+        let (shutdown, _) = watch::channel(None);
+        Self {
+            paused: Arc::new(AtomicBool::new(false)),
+            paused_changed: Arc::new(Notify::new()),
+            shutdown: Arc::new(shutdown),
+        }
+    }
+}
+
+impl Lifecycle {
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::SeqCst);
+        self.paused_changed.notify_waiters();
+    }
+
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::SeqCst);
+        self.paused_changed.notify_waiters();
+    }
+
+    /// Resolves the next time the provider is paused or resumed.
+    pub async fn pause_changed(&self) {
+        self.paused_changed.notified().await
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::SeqCst)
+    }
+
+    /// Asks the server to stop, giving running streams `grace_period` to
+    /// finish before they are cancelled.
+    pub fn request_shutdown(&self, grace_period: Duration) {
+        self.shutdown.send_replace(Some(grace_period));
+    }
+
+    /// Resolves once a shutdown has been requested.
+    pub async fn shutdown_requested(&self) {
+        let mut receiver = self.shutdown.subscribe();
+        while receiver.borrow_and_update().is_none() {
+            if receiver.changed().await.is_err() {
+                return;
+            }
+        }
+    }
+
+    /// Grace period requested by the host, or the default one.
+    pub fn grace_period(&self) -> Duration {
+        self.shutdown.borrow().unwrap_or(DEFAULT_GRACE_PERIOD)
+    }
+}
+
+/// Turns provider requests away while the plugin is paused.
+impl Interceptor for Lifecycle {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        if self.is_paused() {
+            return Err(Status::unavailable("The provider is paused"));
+        }
+        Ok(request)
+    }
+}
@@ -0,0 +1,212 @@
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use proto_rust::provider::LinkPreview;
+use reqwest::header::LOCATION;
+use reqwest::redirect::Policy;
+use reqwest::{Response, Url};
+
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Redirects followed before giving up on a page.
+const MAX_REDIRECTS: usize = 5;
+
+/// The title and icon are in the head, so there's no need to download
+/// whole pages.
+const MAX_PAGE_BYTES: usize = 512 * 1024;
+
+/// Downloads the start of the page at `url` and picks its title and icon.
+pub async fn fetch(url: &str) -> Result<LinkPreview> {
+    let mut location = Url::parse(url)?;
+    let mut fetched = None;
+    for _ in 0..=MAX_REDIRECTS {
+        let response = get(&location).await?;
+        if !response.status().is_redirection() {
+            fetched = Some(response);
+            break;
+        }
+        let target = response
+            .headers()
+            .get(LOCATION)
+            .context("The page redirects nowhere.")?
+            .to_str()?;
+        location = location.join(target)?;
+    }
+    let mut response = fetched
+        .context("The page redirects too many times.")?
+        .error_for_status()?;
+    // The icon is relative to where the redirects end.
+    let base = response.url().clone();
+
+    let mut page = vec![];
+    while let Some(chunk) = response.chunk().await? {
+        page.extend_from_slice(&chunk);
+        if page.len() >= MAX_PAGE_BYTES {
+            break;
+        }
+    }
+    let page = String::from_utf8_lossy(&page);
+    if page.trim().is_empty() {
+        bail!("The page is empty.");
+    }
+
+    Ok(LinkPreview {
+        url: url.to_string(),
+        title: title(&page),
+        favicon: favicon(&page, &base).map(|icon| icon.to_string()),
+    })
+}
+
+/// Requests `url` without following redirects, so every hop is checked.
+/// Links reach this machine through sync too, so they must not be able to
+/// make it query itself or its network. The connection goes to the address
+/// that was checked, in case the name resolves differently a second time.
+async fn get(url: &Url) -> Result<Response> {
+    if !["http", "https"].contains(&url.scheme()) {
+        bail!("Only web pages can be previewed.");
+    }
+    let port = url
+        .port_or_known_default()
+        .context("The link has no port.")?;
+    let mut client = reqwest::Client::builder()
+        .timeout(FETCH_TIMEOUT)
+        .redirect(Policy::none());
+    let host = url.host_str().context("The link has no host.")?;
+    // IPv6 hosts are written in brackets.
+    match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(address) => public(address)?,
+        Err(_) => {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+            let first = *addresses.first().context("The host can't be found.")?;
+            for address in &addresses {
+                public(address.ip())?;
+            }
+            client = client.resolve(host, first);
+        }
+    }
+    Ok(client.build()?.get(url.clone()).send().await?)
+}
+
+/// Fails for addresses that aren't reachable from the internet, such as
+/// loopback, private and link-local ones.
+fn public(address: IpAddr) -> Result<()> {
+    let private = match address {
+        IpAddr::V4(address) => {
+            let [first, second, ..] = address.octets();
+            address.is_private()
+                || address.is_loopback()
+                || address.is_link_local()
+                || address.is_broadcast()
+                || address.is_documentation()
+                || address.is_unspecified()
+                || address.is_multicast()
+                || first == 0
+                || first >= 240
+                // Shared address space of carrier-grade NAT, 100.64.0.0/10.
+                || (first == 100 && second & 0xc0 == 64)
+        }
+        IpAddr::V6(address) => {
+            if let Some(mapped) = address.to_ipv4_mapped() {
+                return public(IpAddr::V4(mapped));
+            }
+            let first = address.segments()[0];
+            address.is_loopback()
+                || address.is_unspecified()
+                || address.is_multicast()
+                // Unique local, fc00::/7, and link-local, fe80::/10.
+                || first & 0xfe00 == 0xfc00
+                || first & 0xffc0 == 0xfe80
+        }
+    };
+    if private {
+        bail!("Links to private addresses can't be previewed.");
+    }
+    Ok(())
+}
+
+/// The `og:title` of the page, falling back to its `<title>`.
+fn title(page: &str) -> Option<String> {
+    let from_meta = tags(page, "meta")
+        .find(|tag| attribute(tag, "property").is_some_and(|value| value == "og:title"))
+        .and_then(|tag| attribute(tag, "content"));
+    let title = from_meta.or_else(|| {
+        let lower = page.to_ascii_lowercase();
+        let start = lower.find("<title")?;
+        let start = start + lower[start..].find('>')? + 1;
+        let end = start + lower[start..].find("</title")?;
+        Some(page[start..end].to_string())
+    })?;
+    let title = decode(title.split_whitespace().collect::<Vec<_>>().join(" "));
+    (!title.is_empty()).then_some(title)
+}
+
+/// The icon the page declares, or `/favicon.ico` on its host.
+fn favicon(page: &str, base: &Url) -> Option<Url> {
+    let declared = tags(page, "link")
+        .find(|tag| {
+            attribute(tag, "rel").is_some_and(|rel| {
+                rel.to_ascii_lowercase()
+                    .split_whitespace()
+                    .any(|rel| rel == "icon")
+            })
+        })
+        .and_then(|tag| attribute(tag, "href"));
+    base.join(&decode(
+        declared.unwrap_or_else(|| "/favicon.ico".to_string()),
+    ))
+    .ok()
+}
+
+/// Every `<name ...>` opening tag in `page`, without the brackets.
+fn tags<'a>(page: &'a str, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+    let opening = format!("<{name}");
+    page.match_indices('<').filter_map(move |(start, _)| {
+        let tag = page.get(start..)?;
+        if !tag
+            .get(..opening.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&opening))
+        {
+            return None;
+        }
+        let end = tag.find('>')?;
+        Some(&tag[1..end])
+    })
+}
+
+/// The value of the attribute `name` in the inside of a tag.
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let mut from = 0;
+    while let Some(found) = lower[from..].find(name) {
+        let start = from + found;
+        from = start + name.len();
+        let preceded = lower[..start].ends_with(char::is_whitespace);
+        let rest = lower[from..].trim_start();
+        if !preceded || !rest.starts_with('=') {
+            continue;
+        }
+        let value = tag[tag.len() - rest.len() + 1..].trim_start();
+        return match value.chars().next()? {
+            quote @ ('"' | '\'') => {
+                let value = &value[1..];
+                Some(value[..value.find(quote)?].to_string())
+            }
+            _ => value
+                .split(|c: char| c.is_whitespace() || c == '/')
+                .next()
+                .map(str::to_string),
+        };
+    }
+    None
+}
+
+/// Undoes the escaping that shows up in titles and links.
+fn decode(text: String) -> String {
+    text.replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&#x27;", "'")
+        .replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&amp;", "&")
+}
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::{QueryDsl, RunQueryDsl, SqliteConnection, TextExpressionMethods};
+use proto_rust::provider::ListSettings;
+use serde::{Deserialize, Serialize};
+
+use crate::inbox::is_inbox;
+use crate::models::QueryableTask;
+use crate::schema::{lists, settings as stored_settings};
+use crate::settings;
+
+/// How a list is shown and the defaults of its new tasks, stored as JSON in
+/// the settings table so new options don't need a migration.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct ViewSettings {
+    sort_order: i32,
+    hide_completed: bool,
+    group_by: i32,
+    default_due_offset: Option<i64>,
+    default_reminder_offset: Option<i64>,
+    archive_after_days: Option<i32>,
+}
+
+const KEY_PREFIX: &str = "list_settings:";
+
+/// Longest default offset in seconds, ten years either way.
+pub const MAX_OFFSET: i64 = 10 * 365 * 24 * 60 * 60;
+
+fn key(list: &str) -> String {
+    format!("{KEY_PREFIX}{list}")
+}
+
+/// The view settings of `list`, the defaults when they were never changed.
+pub fn read(connection: &mut SqliteConnection, list: &str) -> Result<ListSettings> {
+    exists(connection, list)?;
+    let stored = stored(connection, list)?;
+    Ok(ListSettings {
+        list_id: list.to_string(),
+        sort_order: stored.sort_order,
+        hide_completed: stored.hide_completed,
+        group_by: stored.group_by,
+        default_due_offset: stored.default_due_offset,
+        default_reminder_offset: stored.default_reminder_offset,
+        archive_after_days: stored.archive_after_days,
+    })
+}
+
+pub fn write(connection: &mut SqliteConnection, value: &ListSettings) -> Result<()> {
+    exists(connection, &value.list_id)?;
+    let stored = ViewSettings {
+        sort_order: value.sort_order,
+        hide_completed: value.hide_completed,
+        group_by: value.group_by,
+        default_due_offset: value.default_due_offset,
+        default_reminder_offset: value.default_reminder_offset,
+        archive_after_days: value.archive_after_days,
+    };
+    settings::set(
+        connection,
+        &key(&value.list_id),
+        &serde_json::to_string(&stored)?,
+    )
+}
+
+/// Fills in the due date and reminder of a new `task` from the defaults of
+/// its list, leaving the ones it was created with alone. The reminder
+/// default only applies to tasks that end up with a due date.
+pub fn apply_defaults(connection: &mut SqliteConnection, task: &mut QueryableTask) -> Result<()> {
+    let stored = stored(connection, &task.parent_list)?;
+    if task.due_date.is_none() {
+        task.due_date = stored
+            .default_due_offset
+            .and_then(|offset| shift(Utc::now().naive_utc(), offset));
+    }
+    if task.reminder_date.is_none() {
+        if let Some(reminder) = task
+            .due_date
+            .zip(stored.default_reminder_offset)
+            .and_then(|(due, offset)| shift(due, offset))
+        {
+            task.reminder_date = Some(reminder);
+            task.is_reminder_on = true;
+        }
+    }
+    Ok(())
+}
+
+/// `date` moved by `offset` seconds, `None` for offsets out of range.
+fn shift(date: NaiveDateTime, offset: i64) -> Option<NaiveDateTime> {
+    if !(-MAX_OFFSET..=MAX_OFFSET).contains(&offset) {
+        return None;
+    }
+    date.checked_add_signed(Duration::seconds(offset))
+}
+
+/// Whether `list` is set to hide its completed tasks.
+pub fn hides_completed(connection: &mut SqliteConnection, list: &str) -> Result<bool> {
+    Ok(stored(connection, list)?.hide_completed)
+}
+
+/// Lists that archive their completed tasks, with the days they wait.
+pub fn archive_policies(connection: &mut SqliteConnection) -> Result<Vec<(String, i32)>> {
+    let rows: Vec<(String, String)> = stored_settings::table
+        .filter(stored_settings::key.like(format!("{KEY_PREFIX}%")))
+        .load(connection)?;
+    let policies = rows
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let list = key.strip_prefix(KEY_PREFIX)?.to_string();
+            let stored: ViewSettings = serde_json::from_str(&value).ok()?;
+            Some((list, stored.archive_after_days?))
+        })
+        .collect();
+    Ok(policies)
+}
+
+fn stored(connection: &mut SqliteConnection, list: &str) -> Result<ViewSettings> {
+    let stored = match settings::get(connection, &key(list))? {
+        Some(value) => serde_json::from_str(&value).unwrap_or_default(),
+        None => ViewSettings::default(),
+    };
+    Ok(stored)
+}
+
+fn exists(connection: &mut SqliteConnection, list: &str) -> Result<()> {
+    if is_inbox(list) {
+        return Ok(());
+    }
+    lists::table
+        .find(list)
+        .select(lists::id_list)
+        .first::<String>(connection)
+        .context("The list doesn't exist.")?;
+    Ok(())
+}
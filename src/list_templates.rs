@@ -0,0 +1,134 @@
+use anyhow::{bail, Context, Result};
+use chrono_tz::Tz;
+use diesel::{Connection, ExpressionMethods, JoinOnDsl, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::ListTemplate;
+
+use crate::models::{QueryableList, QueryableListTemplate, QueryableTask, QueryableTemplate};
+use crate::ordering::next_list_position;
+use crate::schema::{list_template_tasks, list_templates, lists, tasks, templates};
+
+/// Saves `list` and its tasks, in their order, as a template called `name`.
+/// Archived tasks are left out.
+pub fn save(connection: &mut SqliteConnection, list: &str, name: String) -> Result<ListTemplate> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let list: QueryableList = lists::table
+            .find(list)
+            .first(connection)
+            .context("The list doesn't exist.")?;
+        let template = QueryableListTemplate::from_list(&list, name);
+        diesel::insert_into(list_templates::table)
+            .values(&template)
+            .execute(connection)?;
+
+        let children: Vec<QueryableTask> = tasks::table
+            .filter(tasks::parent_list.eq(&list.id_list))
+            .filter(tasks::archived.eq(false))
+            .order((
+                tasks::pinned.desc(),
+                tasks::position.asc(),
+                tasks::created_date_time.asc(),
+            ))
+            .load(connection)?;
+        for (position, task) in children.iter().enumerate() {
+            let task_template =
+                crate::templates::capture(connection, task, task.title.clone(), None)?;
+            diesel::insert_into(list_template_tasks::table)
+                .values((
+                    list_template_tasks::id_list_template.eq(&template.id_list_template),
+                    list_template_tasks::position.eq(position as i64),
+                    list_template_tasks::id_template.eq(&task_template.id_template),
+                ))
+                .execute(connection)?;
+        }
+
+        to_proto(connection, template)
+    })
+}
+
+/// Every list template, by name.
+pub fn read_all(connection: &mut SqliteConnection) -> Result<Vec<ListTemplate>> {
+    let all: Vec<QueryableListTemplate> = list_templates::table
+        .order(list_templates::name.asc())
+        .load(connection)?;
+    all.into_iter()
+        .map(|template| to_proto(connection, template))
+        .collect()
+}
+
+/// Deletes a list template along with the templates of its tasks.
+pub fn remove(connection: &mut SqliteConnection, template: &str) -> Result<()> {
+    let removed = diesel::delete(list_templates::table.find(template)).execute(connection)?;
+    if removed == 0 {
+        bail!("The template doesn't exist.");
+    }
+    Ok(())
+}
+
+/// Creates a list from a template, called `name` or what the original list
+/// was called, with its tasks in the saved order and their dates counted
+/// from now.
+pub fn instantiate(
+    connection: &mut SqliteConnection,
+    template: &str,
+    name: Option<String>,
+    timezone: Tz,
+) -> Result<(QueryableList, Vec<QueryableTask>)> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let template: QueryableListTemplate = list_templates::table
+            .find(template)
+            .first(connection)
+            .context("The template doesn't exist.")?;
+
+        let mut list = QueryableList::new(
+            name.as_deref().unwrap_or(&template.list_name),
+            template.icon_name.clone(),
+            template.provider.clone(),
+        );
+        list.color = template.color.clone();
+        list.description = template.description.clone();
+        list.position = next_list_position(connection)?;
+        diesel::insert_into(lists::table)
+            .values(&list)
+            .execute(connection)?;
+
+        let task_templates: Vec<String> = list_template_tasks::table
+            .filter(list_template_tasks::id_list_template.eq(&template.id_list_template))
+            .order(list_template_tasks::position.asc())
+            .select(list_template_tasks::id_template)
+            .load(connection)?;
+        let children = task_templates
+            .iter()
+            .map(|task| crate::templates::instantiate(connection, task, &list.id_list, timezone))
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok((list, children))
+    })
+}
+
+fn to_proto(
+    connection: &mut SqliteConnection,
+    template: QueryableListTemplate,
+) -> Result<ListTemplate> {
+    let task_templates: Vec<QueryableTemplate> = list_template_tasks::table
+        .inner_join(
+            templates::table.on(templates::id_template.eq(list_template_tasks::id_template)),
+        )
+        .filter(list_template_tasks::id_list_template.eq(&template.id_list_template))
+        .order(list_template_tasks::position.asc())
+        .select(templates::all_columns)
+        .load(connection)?;
+    let tasks = task_templates
+        .into_iter()
+        .map(|task| crate::templates::to_proto(connection, task))
+        .collect::<Result<_>>()?;
+    Ok(ListTemplate {
+        id: template.id_list_template,
+        name: template.name,
+        list_name: template.list_name,
+        icon: template.icon_name,
+        color: template.color,
+        description: template.description,
+        tasks,
+        created_date_time: template.created_date_time.timestamp(),
+    })
+}
@@ -0,0 +1,50 @@
+use anyhow::Result;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::TaskStatus;
+
+use crate::models::QueryableTask;
+use crate::schema::tasks;
+
+/// Mean radius of the earth, in meters.
+const EARTH_RADIUS: f64 = 6_371_000.0;
+
+/// Meters in a degree of latitude.
+const METERS_PER_DEGREE: f64 = EARTH_RADIUS * std::f64::consts::PI / 180.0;
+
+/// Open tasks placed within `radius` meters of a point, nearest first.
+pub fn tasks_near(
+    connection: &mut SqliteConnection,
+    latitude: f64,
+    longitude: f64,
+    radius: f64,
+) -> Result<Vec<QueryableTask>> {
+    // The index narrows it down to a band of latitudes, the exact distance
+    // is worked out here.
+    let band = radius / METERS_PER_DEGREE;
+    let candidates: Vec<QueryableTask> = tasks::table
+        .filter(tasks::status.ne(TaskStatus::Completed as i32))
+        .filter(tasks::archived.eq(false))
+        .filter(tasks::latitude.between(latitude - band, latitude + band))
+        .filter(tasks::longitude.is_not_null())
+        .load(connection)?;
+
+    let mut near: Vec<(f64, QueryableTask)> = candidates
+        .into_iter()
+        .filter_map(|task| {
+            let distance = distance(latitude, longitude, task.latitude?, task.longitude?);
+            (distance <= radius).then_some((distance, task))
+        })
+        .collect();
+    near.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+    Ok(near.into_iter().map(|(_, task)| task).collect())
+}
+
+/// Great circle distance between two points, in meters.
+fn distance(from_latitude: f64, from_longitude: f64, to_latitude: f64, to_longitude: f64) -> f64 {
+    let (from_latitude, to_latitude) = (from_latitude.to_radians(), to_latitude.to_radians());
+    let latitude_delta = to_latitude - from_latitude;
+    let longitude_delta = (to_longitude - from_longitude).to_radians();
+    let haversine = (latitude_delta / 2.0).sin().powi(2)
+        + from_latitude.cos() * to_latitude.cos() * (longitude_delta / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS * haversine.sqrt().asin()
+}
@@ -0,0 +1,188 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Instant;
+
+use tonic::body::BoxBody;
+use tonic::codegen::http::{HeaderMap, HeaderValue, Request, Response};
+use tonic::codegen::{Body as HttpBody, BoxFuture, Bytes};
+use tonic::transport::Body;
+use tonic::Status;
+use tower::{Layer, Service};
+use tracing::{Instrument, Span};
+use uuid::Uuid;
+
+use crate::metrics;
+
+/// Header used to correlate the logs of a request with the host's.
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+const GRPC_STATUS_HEADER: &str = "grpc-status";
+
+/// Logged when a response ends without saying how, e.g. when the client
+/// hangs up halfway through a stream.
+const UNKNOWN_STATUS: &str = "unknown";
+
+/// Logs one structured line per RPC with its method, latency and outcome.
+///
+/// The request id sent by the host is reused, or a new one is assigned, and
+/// echoed back in the response headers. Everything logged while the request
+/// is handled carries it. Request payloads are never logged since tasks may
+/// hold private notes.
+#[derive(Debug, Clone, Default)]
+pub struct LoggingLayer;
+
+impl<S> Layer<S> for LoggingLayer {
+    type Service = Logging<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Logging { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Logging<S> {
+    inner: S,
+}
+
+impl<S> Service<Request<Body>> for Logging<S>
+where
+    S: Service<Request<Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Error: std::fmt::Display,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: Request<Body>) -> Self::Future {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .cloned()
+            .unwrap_or_else(|| {
+                HeaderValue::from_str(&Uuid::new_v4().to_string())
+                    .expect("UUIDs are valid header values")
+            });
+        request
+            .headers_mut()
+            .insert(REQUEST_ID_HEADER, request_id.clone());
+
+        let method = request.uri().path().to_string();
+        let span = tracing::info_span!(
+            "rpc",
+            request_id = request_id.to_str().unwrap_or_default(),
+            method,
+        );
+
+        // The clone might not be ready, keep the instance that was polled.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(
+            async move {
+                let started = Instant::now();
+                let result = inner.call(request).await;
+                let latency = started.elapsed();
+                let latency_ms = latency.as_millis() as u64;
+                match result {
+                    Ok(mut response) => {
+                        response.headers_mut().insert(REQUEST_ID_HEADER, request_id);
+                        // Calls that fail right away put their status in the
+                        // headers, the others send it in the trailers once
+                        // the whole body went out.
+                        if let Some(grpc_status) = status(response.headers()) {
+                            metrics::record_rpc(&method, &grpc_status, latency);
+                            tracing::info!(latency_ms, grpc_status, "Request handled");
+                            return Ok(response);
+                        }
+                        Ok(response.map(|inner| {
+                            BoxBody::new(Logged {
+                                inner,
+                                method,
+                                started,
+                                span: Span::current(),
+                                logged: false,
+                            })
+                        }))
+                    }
+                    Err(err) => {
+                        tracing::error!(latency_ms, "Request failed: {err}");
+                        Err(err)
+                    }
+                }
+            }
+            .instrument(span),
+        )
+    }
+}
+
+fn status(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get(GRPC_STATUS_HEADER)
+        .and_then(|status| status.to_str().ok())
+        .map(str::to_string)
+}
+
+/// A response body that logs the request once its trailers are sent, with
+/// the latency of the whole response, streams included.
+struct Logged {
+    inner: BoxBody,
+    method: String,
+    started: Instant,
+    span: Span,
+    logged: bool,
+}
+
+impl Logged {
+    fn log(&mut self, grpc_status: &str) {
+        if std::mem::replace(&mut self.logged, true) {
+            return;
+        }
+        let _entered = self.span.enter();
+        let latency = self.started.elapsed();
+        let latency_ms = latency.as_millis() as u64;
+        metrics::record_rpc(&self.method, grpc_status, latency);
+        tracing::info!(latency_ms, grpc_status, "Request handled");
+    }
+}
+
+impl HttpBody for Logged {
+    type Data = Bytes;
+    type Error = Status;
+
+    fn poll_data(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Self::Data, Self::Error>>> {
+        Pin::new(&mut self.inner).poll_data(cx)
+    }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let trailers = Pin::new(&mut self.inner).poll_trailers(cx);
+        if let Poll::Ready(result) = &trailers {
+            let grpc_status = match result {
+                Ok(Some(trailers)) => status(trailers),
+                _ => None,
+            };
+            self.log(grpc_status.as_deref().unwrap_or(UNKNOWN_STATUS));
+        }
+        trailers
+    }
+
+    fn is_end_stream(&self) -> bool {
+        self.inner.is_end_stream()
+    }
+}
+
+impl Drop for Logged {
+    fn drop(&mut self) {
+        self.log(UNKNOWN_STATUS);
+    }
+}
@@ -2,34 +2,211 @@ extern crate diesel;
 #[macro_use]
 extern crate diesel_migrations;
 
+use proto_rust::admin::admin_server::AdminServer;
 use proto_rust::provider::provider_server::ProviderServer;
+use tonic::codec::CompressionEncoding;
+use tonic::service::interceptor::InterceptedService;
 use tonic::transport::Server;
+use tonic_health::ServingStatus;
 
+mod admin;
+mod agenda;
+mod anonymize;
+mod assignees;
+mod attachments;
+mod audit;
+mod auto_archive;
+mod bundle;
+mod cleanup;
+mod completion;
+mod config;
+mod counters;
 mod database;
+mod dependencies;
+mod duplicates;
+mod events;
+mod frecency;
+mod groups;
+mod health;
+mod ical;
+mod icons;
+mod idempotency;
+mod inbox;
+mod lifecycle;
+#[cfg(feature = "link-preview")]
+mod link_preview;
+mod list_settings;
+mod list_templates;
+mod location;
+mod logging;
+mod metadata;
+mod metrics;
 mod models;
+#[cfg(feature = "notify")]
+mod notifications;
+mod ordering;
+mod priorities;
+mod progress;
+mod query;
+mod quick_add;
+mod rate_limit;
+mod recurrence;
+mod reminders;
+mod replication;
 mod schema;
+mod search;
 mod service;
+mod settings;
 mod setup;
+mod statistics;
+mod statuses;
+mod supervisor;
+mod sync;
+mod templates;
+mod transport;
+mod validation;
+mod version;
+mod welcome;
 
+use admin::AdminService;
+use config::Config;
+use events::Changes;
+use lifecycle::Lifecycle;
+use logging::LoggingLayer;
+use rate_limit::RateLimitLayer;
+use replication::Replicator;
 use service::LocalService;
+use supervisor::Supervisor;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let addr = "[::1]:7007".parse()?;
+    let uds_path = match transport::uds_path() {
+        Ok(path) => path,
+        Err(err) => {
+            eprintln!("{err}");
+            std::process::exit(2);
+        }
+    };
+    let config = Config::load()?;
+    let addr = config.address.parse()?;
+    let supervisor = Supervisor::default();
+    let lifecycle = Lifecycle::default();
+    let changes = Changes::default();
 
     let local_service = LocalService {
         id: "Local".to_string(),
         name: "Local".to_string(),
         description: "Stores tasks on your computer.".to_string(),
         icon: "user-home-symbolic".to_string(),
+        timezone: config.timezone()?,
+        supervisor: supervisor.clone(),
+        changes: changes.clone(),
+        streaming: config.streaming,
     };
 
     setup::init();
 
-    Server::builder()
-        .add_service(ProviderServer::new(local_service))
-        .serve(addr)
-        .await?;
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter
+        .set_service_status(health::PROVIDER_SERVICE, ServingStatus::NotServing)
+        .await;
+    supervisor.spawn_job(
+        "health",
+        health::monitor(health_reporter, lifecycle.clone()),
+    );
+
+    tokio::task::spawn_blocking(icons::preload);
+    supervisor.spawn_job("cleanup", cleanup::schedule(lifecycle.clone()));
+    supervisor.spawn_job(
+        "reminders",
+        reminders::schedule(changes.clone(), lifecycle.clone()),
+    );
+    supervisor.spawn_job(
+        "auto-archive",
+        auto_archive::schedule(changes, lifecycle.clone()),
+    );
+
+    if let Some(address) = &config.metrics_address {
+        supervisor.spawn_job("metrics", metrics::serve(address.parse()?));
+    }
+
+    let admin_service = AdminService {
+        replication: Default::default(),
+        supervisor: supervisor.clone(),
+        lifecycle: lifecycle.clone(),
+        updates: config.updates.clone(),
+    };
+
+    if let Err(err) = database::establish_connection()
+        .and_then(|mut connection| search::ensure_language(&mut connection, &config.language))
+    {
+        tracing::error!("Failed to prepare the search index: {err}");
+    }
+
+    if config.welcome_lists {
+        let seeded = database::establish_connection().and_then(|mut connection| {
+            welcome::seed(&mut connection, &config.language, &local_service.id)
+        });
+        match seeded {
+            Ok(true) => tracing::info!("Created the welcome lists."),
+            Ok(false) => {}
+            Err(err) => tracing::error!("Failed to create the welcome lists: {err}"),
+        }
+    }
+
+    if let Some(replication) = &config.replication {
+        let state = admin_service.replication.clone();
+        match Replicator::new(replication, state, lifecycle.clone()) {
+            Ok(replicator) => supervisor.spawn_job("replication", replicator.run()),
+            Err(err) => tracing::error!("Failed to start replication: {err}"),
+        }
+    }
+
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(proto_rust::FILE_DESCRIPTOR_SET)
+        .build()?;
+
+    // Task streams are highly repetitive text, compress them for clients
+    // that accept it.
+    let provider_service = ProviderServer::new(local_service)
+        .send_compressed(CompressionEncoding::Gzip)
+        .accept_compressed(CompressionEncoding::Gzip);
+
+    let mut server = Server::builder();
+    if let Some(tls) = config.tls()? {
+        server = server.tls_config(tls)?;
+    }
+
+    let router = server
+        .layer(LoggingLayer)
+        .layer(RateLimitLayer::new(config.rate_limit.clone()))
+        .add_service(health_service)
+        .add_service(reflection_service)
+        .add_service(InterceptedService::new(provider_service, lifecycle.clone()))
+        .add_service(AdminServer::new(admin_service));
+
+    let shutdown = async {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {}
+            _ = lifecycle.shutdown_requested() => {}
+        }
+    };
+
+    match uds_path {
+        Some(path) => {
+            tracing::info!("Listening on {}", path.display());
+            let incoming = transport::bind_uds(&path)?;
+            router
+                .serve_with_incoming_shutdown(incoming, shutdown)
+                .await?;
+            std::fs::remove_file(&path).ok();
+        }
+        None => router.serve_with_shutdown(addr, shutdown).await?,
+    }
+
+    tracing::info!("Shutting down, waiting for running tasks to finish.");
+    supervisor.shutdown(lifecycle.grace_period()).await;
+    setup::shutdown();
 
     Ok(())
 }
@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use chrono::{NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+use tokio::time::Instant;
+use tonic::metadata::MetadataMap;
+use tonic::Status;
+
+use crate::version::{negotiate, MIN_API_VERSION};
+
+/// BCP 47 language tag of the user, e.g. `es-MX`.
+pub const LOCALE_KEY: &str = "x-locale";
+/// IANA timezone of the user, e.g. `America/Mexico_City`.
+pub const TIMEZONE_KEY: &str = "x-timezone";
+
+/// API revision agreed on through `negotiate`.
+pub const API_VERSION_KEY: &str = "x-api-version";
+
+/// Number of tasks the client wants in each streamed message.
+pub const CHUNK_SIZE_KEY: &str = "x-chunk-size";
+
+/// Set to `true` to get possible duplicates back instead of a new task.
+/// `create_task` takes a bare `Task`, which has no room for request options
+/// that aren't part of the task itself.
+pub const DETECT_DUPLICATES_KEY: &str = "x-detect-duplicates";
+
+/// Standard gRPC header carrying how long the client is willing to wait.
+const TIMEOUT_KEY: &str = "grpc-timeout";
+
+/// The gRPC spec allows at most eight digits in a timeout value.
+const MAX_TIMEOUT_DIGITS: usize = 8;
+
+const DEFAULT_LOCALE: &str = "en";
+
+/// Chunk size requested by the client, if it opted into chunked streams.
+pub fn chunk_size(metadata: &MetadataMap) -> Option<usize> {
+    metadata.get(CHUNK_SIZE_KEY)?.to_str().ok()?.parse().ok()
+}
+
+/// Whether the client asked `create_task` to check for duplicates first.
+pub fn detect_duplicates(metadata: &MetadataMap) -> bool {
+    metadata
+        .get(DETECT_DUPLICATES_KEY)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.eq_ignore_ascii_case("true"))
+}
+
+/// Instant after which the client stops waiting for a response, read from
+/// the `grpc-timeout` header, e.g. `500m` or `30S`. Malformed timeouts, or
+/// ones too far off to represent, are treated as no deadline.
+pub fn deadline(metadata: &MetadataMap) -> Option<Instant> {
+    let timeout = metadata.get(TIMEOUT_KEY)?.to_str().ok()?;
+    let (value, unit) = timeout.split_at(timeout.len().checked_sub(1)?);
+    if value.len() > MAX_TIMEOUT_DIGITS {
+        return None;
+    }
+    let value: u64 = value.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(value.checked_mul(60 * 60)?),
+        "M" => Duration::from_secs(value.checked_mul(60)?),
+        "S" => Duration::from_secs(value),
+        "m" => Duration::from_millis(value),
+        "u" => Duration::from_micros(value),
+        "n" => Duration::from_nanos(value),
+        _ => return None,
+    };
+    Instant::now().checked_add(timeout)
+}
+
+/// Locale, timezone and API revision a request should be served with, read
+/// from the standard request metadata keys.
+#[derive(Debug, Clone)]
+pub struct RequestContext {
+    pub locale: String,
+    pub timezone: Tz,
+    /// Hosts that never negotiated are served the oldest revision.
+    pub api_version: u32,
+}
+
+impl RequestContext {
+    /// Reads the context of a request, using `timezone` when the caller
+    /// didn't send one.
+    #[allow(clippy::result_large_err)]
+    pub fn from_metadata(metadata: &MetadataMap, timezone: Tz) -> Result<Self, Status> {
+        let mut context = RequestContext {
+            locale: DEFAULT_LOCALE.to_string(),
+            timezone,
+            api_version: MIN_API_VERSION,
+        };
+
+        if let Some(locale) = metadata.get(LOCALE_KEY) {
+            let locale = locale
+                .to_str()
+                .map_err(|_| Status::invalid_argument("Invalid x-locale metadata"))?;
+            if !locale.is_empty() {
+                context.locale = locale.to_string();
+            }
+        }
+
+        if let Some(timezone) = metadata.get(TIMEZONE_KEY) {
+            context.timezone = timezone
+                .to_str()
+                .ok()
+                .and_then(|timezone| timezone.parse::<Tz>().ok())
+                .ok_or_else(|| Status::invalid_argument("Invalid x-timezone metadata"))?;
+        }
+
+        if let Some(api_version) = metadata.get(API_VERSION_KEY) {
+            let requested = api_version
+                .to_str()
+                .ok()
+                .and_then(|api_version| api_version.parse::<u32>().ok())
+                .ok_or_else(|| Status::invalid_argument("Invalid x-api-version metadata"))?;
+            context.api_version = negotiate(requested)
+                .ok_or_else(|| Status::failed_precondition("Unsupported API version"))?;
+        }
+
+        Ok(context)
+    }
+
+    /// The same context in `timezone`, for dates that were set elsewhere.
+    pub fn in_zone(&self, timezone: Option<Tz>) -> RequestContext {
+        RequestContext {
+            timezone: timezone.unwrap_or(self.timezone),
+            ..self.clone()
+        }
+    }
+
+    /// The current date in the user's timezone.
+    pub fn today(&self) -> NaiveDate {
+        Utc::now().with_timezone(&self.timezone).date_naive()
+    }
+
+    /// The current wall clock time in the user's timezone.
+    pub fn now(&self) -> NaiveDateTime {
+        Utc::now().with_timezone(&self.timezone).naive_local()
+    }
+
+    /// The UTC instant of a wall clock time in the user's timezone.
+    pub fn to_utc(&self, local: NaiveDateTime) -> NaiveDateTime {
+        self.timezone
+            .from_local_datetime(&local)
+            .earliest()
+            .map(|instant| instant.naive_utc())
+            .unwrap_or(local)
+    }
+
+    /// The date a UTC instant falls on in the user's timezone.
+    pub fn local_date(&self, instant: NaiveDateTime) -> NaiveDate {
+        Utc.from_utc_datetime(&instant)
+            .with_timezone(&self.timezone)
+            .date_naive()
+    }
+
+    /// The UTC instant at which `date` starts in the user's timezone, which
+    /// is how dates are stored in the database.
+    pub fn start_of_day(&self, date: NaiveDate) -> NaiveDateTime {
+        let midnight = date.and_hms_opt(0, 0, 0).unwrap();
+        self.timezone
+            .from_local_datetime(&midnight)
+            .earliest()
+            .map(|start| start.naive_utc())
+            .unwrap_or(midnight)
+    }
+}
@@ -0,0 +1,101 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use anyhow::Result;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, StatusCode};
+use once_cell::sync::Lazy;
+use prometheus::{
+    exponential_buckets, register_histogram_vec, register_int_counter, register_int_counter_vec,
+    register_int_gauge, Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge, TextEncoder,
+};
+
+static RPC_REQUESTS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "local_plugin_rpc_requests_total",
+        "RPCs handled, by method and gRPC status.",
+        &["method", "grpc_status"]
+    )
+    .unwrap()
+});
+
+static RPC_LATENCY: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "local_plugin_rpc_latency_seconds",
+        "Time until an RPC finished, streams included.",
+        &["method"]
+    )
+    .unwrap()
+});
+
+static STREAM_MESSAGES: Lazy<HistogramVec> = Lazy::new(|| {
+    register_histogram_vec!(
+        "local_plugin_stream_messages",
+        "Messages sent by each server stream.",
+        &["method"],
+        exponential_buckets(1.0, 4.0, 8).unwrap()
+    )
+    .unwrap()
+});
+
+static ACTIVE_STREAMS: Lazy<IntGauge> = Lazy::new(|| {
+    register_int_gauge!("local_plugin_active_streams", "Server streams running.").unwrap()
+});
+
+static DB_CONNECTIONS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "local_plugin_db_connections_total",
+        "Database connections opened, the plugin opens one per request."
+    )
+    .unwrap()
+});
+
+pub fn record_rpc(method: &str, grpc_status: &str, latency: Duration) {
+    RPC_REQUESTS.with_label_values(&[method, grpc_status]).inc();
+    RPC_LATENCY
+        .with_label_values(&[method])
+        .observe(latency.as_secs_f64());
+}
+
+pub fn record_stream(method: &str, messages: usize) {
+    STREAM_MESSAGES
+        .with_label_values(&[method])
+        .observe(messages as f64);
+}
+
+pub fn stream_started() {
+    ACTIVE_STREAMS.inc();
+}
+
+pub fn stream_finished() {
+    ACTIVE_STREAMS.dec();
+}
+
+pub fn connection_opened() {
+    DB_CONNECTIONS.inc();
+}
+
+/// Serves the metrics in the Prometheus text format on `/metrics`.
+pub async fn serve(address: SocketAddr) -> Result<()> {
+    let make_service = make_service_fn(|_| async {
+        Ok::<_, hyper::Error>(service_fn(|request: Request<Body>| async move {
+            let response = match (request.method(), request.uri().path()) {
+                (&Method::GET, "/metrics") => {
+                    let mut buffer = vec![];
+                    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+                    Response::new(Body::from(buffer))
+                }
+                _ => Response::builder()
+                    .status(StatusCode::NOT_FOUND)
+                    .body(Body::empty())?,
+            };
+            Ok::<_, anyhow::Error>(response)
+        }))
+    });
+
+    tracing::info!("Serving metrics on http://{address}/metrics");
+    hyper::Server::try_bind(&address)?
+        .serve(make_service)
+        .await?;
+    Ok(())
+}
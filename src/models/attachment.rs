@@ -0,0 +1,61 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Attachment;
+use uuid::Uuid;
+
+use crate::schema::attachments;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = attachments)]
+pub struct QueryableAttachment {
+    pub id_attachment: String,
+    pub id_task: String,
+    pub file_name: String,
+    pub mime_type: Option<String>,
+    pub size: i64,
+    pub hash: String,
+    pub created_date_time: NaiveDateTime,
+}
+
+impl QueryableAttachment {
+    /// Returns a copy of this attachment that belongs to another task. Both
+    /// share the stored file.
+    pub fn copy_to_task(&self, task: &str) -> Self {
+        Self {
+            id_attachment: Uuid::new_v4().to_string(),
+            id_task: task.to_string(),
+            created_date_time: Utc::now().naive_utc(),
+            ..self.clone()
+        }
+    }
+}
+
+impl From<QueryableAttachment> for Attachment {
+    fn from(value: QueryableAttachment) -> Self {
+        Attachment {
+            id: value.id_attachment,
+            task_id: value.id_task,
+            file_name: value.file_name,
+            mime_type: value.mime_type,
+            size: value.size,
+            hash: value.hash,
+            created_date_time: value.created_date_time.timestamp(),
+        }
+    }
+}
+
+/// Describes an upload; the size and hash are taken from the received
+/// contents rather than from the client.
+impl From<Attachment> for QueryableAttachment {
+    fn from(value: Attachment) -> Self {
+        Self {
+            id_attachment: value.id,
+            id_task: value.task_id,
+            file_name: value.file_name,
+            mime_type: value.mime_type,
+            size: 0,
+            hash: String::new(),
+            created_date_time: Utc::now().naive_utc(),
+        }
+    }
+}
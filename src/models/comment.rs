@@ -0,0 +1,40 @@
+use chrono::NaiveDateTime;
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Comment;
+
+use crate::schema::comments;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = comments)]
+pub struct QueryableComment {
+    pub id_comment: String,
+    pub id_task: String,
+    pub author: String,
+    pub body: String,
+    pub created_date_time: NaiveDateTime,
+}
+
+impl From<QueryableComment> for Comment {
+    fn from(value: QueryableComment) -> Self {
+        Comment {
+            id: value.id_comment,
+            task_id: value.id_task,
+            author: value.author,
+            body: value.body,
+            created_date_time: value.created_date_time.timestamp(),
+        }
+    }
+}
+
+impl From<Comment> for QueryableComment {
+    fn from(value: Comment) -> Self {
+        Self {
+            id_comment: value.id,
+            id_task: value.task_id,
+            author: value.author,
+            body: value.body,
+            created_date_time: NaiveDateTime::from_timestamp_opt(value.created_date_time, 0)
+                .unwrap(),
+        }
+    }
+}
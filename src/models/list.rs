@@ -1,11 +1,12 @@
-use diesel::{Insertable, Queryable};
+use diesel::{AsChangeset, Insertable, Queryable, QueryableByName};
 use proto_rust::provider::List;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::schema::lists;
+use crate::version;
 
-#[derive(Serialize, Deserialize, Debug, Clone, Queryable, Insertable)]
+#[derive(Serialize, Deserialize, Debug, Clone, Queryable, QueryableByName, Insertable)]
 #[diesel(table_name = lists)]
 pub struct QueryableList {
     pub id_list: String,
@@ -13,6 +14,62 @@ pub struct QueryableList {
     pub is_owner: bool,
     pub icon_name: Option<String>,
     pub provider: String,
+    #[serde(default)]
+    pub position: i64,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub pinned: bool,
+    /// Sidebar group the list is nested in, see `list_groups`.
+    #[serde(default)]
+    pub id_group: Option<String>,
+    /// `#RRGGBB` tint of the list header and sidebar entry.
+    #[serde(default)]
+    pub color: Option<String>,
+    /// What the list is for, shown under its name.
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Protected lists can't be deleted or merged into another list.
+    #[serde(default)]
+    pub protected: bool,
+}
+
+/// Columns of a stored list to overwrite, where `None` keeps the stored
+/// value, so lists from hosts that predate a feature don't reset it.
+/// Protection is left alone, it's only changed through `set_list_protected`.
+#[derive(Debug, Clone, AsChangeset)]
+#[diesel(table_name = lists)]
+pub struct ListChanges {
+    pub name: String,
+    pub is_owner: bool,
+    pub icon_name: Option<Option<String>>,
+    pub provider: String,
+    pub position: Option<i64>,
+    pub archived: Option<bool>,
+    pub pinned: Option<bool>,
+    pub id_group: Option<Option<String>>,
+    pub color: Option<Option<String>>,
+    pub description: Option<Option<String>>,
+}
+
+impl ListChanges {
+    /// Every column of `list` a host speaking `api_version` knows about.
+    pub fn new(list: &QueryableList, api_version: u32) -> Self {
+        let supports = |feature| version::supports(api_version, feature);
+        let list = list.clone();
+        Self {
+            name: list.name,
+            is_owner: list.is_owner,
+            icon_name: Some(list.icon_name),
+            provider: list.provider,
+            position: supports("list-ordering").then_some(list.position),
+            archived: supports("list-archive").then_some(list.archived),
+            pinned: supports("list-pinning").then_some(list.pinned),
+            id_group: supports("list-groups").then_some(list.id_group),
+            color: supports("list-colors").then_some(list.color),
+            description: supports("list-descriptions").then_some(list.description),
+        }
+    }
 }
 
 impl QueryableList {
@@ -23,6 +80,23 @@ impl QueryableList {
             is_owner: true,
             icon_name,
             provider: list_provider,
+            position: 0,
+            archived: false,
+            pinned: false,
+            id_group: None,
+            color: None,
+            description: None,
+            protected: false,
+        }
+    }
+
+    /// Returns an unprotected copy of this list with a new id and name.
+    pub fn duplicate(&self, display_name: &str) -> Self {
+        Self {
+            id_list: Uuid::new_v4().to_string(),
+            name: display_name.to_string(),
+            protected: false,
+            ..self.clone()
         }
     }
 }
@@ -35,6 +109,13 @@ impl From<QueryableList> for List {
             is_owner: value.is_owner,
             icon: value.icon_name,
             provider: value.provider,
+            position: value.position,
+            archived: value.archived,
+            pinned: value.pinned,
+            group_id: value.id_group,
+            color: value.color,
+            description: value.description,
+            protected: value.protected,
         }
     }
 }
@@ -47,6 +128,13 @@ impl From<List> for QueryableList {
             is_owner: task.is_owner,
             icon_name: task.icon,
             provider: task.provider,
+            position: task.position,
+            archived: task.archived,
+            pinned: task.pinned,
+            id_group: task.group_id,
+            color: task.color,
+            description: task.description,
+            protected: task.protected,
         }
     }
 }
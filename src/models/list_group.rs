@@ -0,0 +1,36 @@
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::ListGroup;
+
+use crate::schema::list_groups;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = list_groups)]
+pub struct QueryableListGroup {
+    pub id_group: String,
+    pub name: String,
+    /// Group this one is nested in, top level when `None`.
+    pub id_parent: Option<String>,
+    pub position: i64,
+}
+
+impl From<QueryableListGroup> for ListGroup {
+    fn from(value: QueryableListGroup) -> Self {
+        ListGroup {
+            id: value.id_group,
+            name: value.name,
+            parent_id: value.id_parent,
+            position: value.position,
+        }
+    }
+}
+
+impl From<ListGroup> for QueryableListGroup {
+    fn from(value: ListGroup) -> Self {
+        Self {
+            id_group: value.id,
+            name: value.name,
+            id_parent: value.parent_id,
+            position: value.position,
+        }
+    }
+}
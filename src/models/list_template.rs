@@ -0,0 +1,36 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use uuid::Uuid;
+
+use crate::models::QueryableList;
+use crate::schema::list_templates;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = list_templates)]
+pub struct QueryableListTemplate {
+    pub id_list_template: String,
+    pub name: String,
+    /// Name given to lists created from the template.
+    pub list_name: String,
+    pub icon_name: Option<String>,
+    pub provider: String,
+    pub color: Option<String>,
+    pub description: Option<String>,
+    pub created_date_time: NaiveDateTime,
+}
+
+impl QueryableListTemplate {
+    /// Captures how `list` looks, its tasks are saved separately.
+    pub fn from_list(list: &QueryableList, name: String) -> Self {
+        Self {
+            id_list_template: Uuid::new_v4().to_string(),
+            name,
+            list_name: list.name.clone(),
+            icon_name: list.icon_name.clone(),
+            provider: list.provider.clone(),
+            color: list.color.clone(),
+            description: list.description.clone(),
+            created_date_time: Utc::now().naive_utc(),
+        }
+    }
+}
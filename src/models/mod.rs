@@ -3,3 +3,45 @@ pub use task::*;
 
 mod list;
 pub use list::*;
+
+mod recent_item;
+pub use recent_item::*;
+
+mod tombstone;
+pub use tombstone::*;
+
+mod subtask;
+pub use subtask::*;
+
+mod tag;
+pub use tag::*;
+
+mod comment;
+pub use comment::*;
+
+mod attachment;
+pub use attachment::*;
+
+mod time_entry;
+pub use time_entry::*;
+
+mod pomodoro;
+pub use pomodoro::*;
+
+mod priority;
+pub use priority::*;
+
+mod template;
+pub use template::*;
+
+mod status;
+pub use status::*;
+
+mod person;
+pub use person::*;
+
+mod list_group;
+pub use list_group::*;
+
+mod list_template;
+pub use list_template::*;
@@ -0,0 +1,29 @@
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Person;
+
+use crate::schema::people;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = people)]
+pub struct QueryablePerson {
+    pub id_person: String,
+    pub name: String,
+}
+
+impl From<QueryablePerson> for Person {
+    fn from(value: QueryablePerson) -> Self {
+        Person {
+            id: value.id_person,
+            name: value.name,
+        }
+    }
+}
+
+impl From<Person> for QueryablePerson {
+    fn from(value: Person) -> Self {
+        Self {
+            id_person: value.id,
+            name: value.name,
+        }
+    }
+}
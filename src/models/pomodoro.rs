@@ -0,0 +1,42 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Pomodoro;
+use uuid::Uuid;
+
+use crate::schema::pomodoros;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = pomodoros)]
+pub struct QueryablePomodoro {
+    pub id_pomodoro: String,
+    pub id_task: String,
+    /// Planned length in seconds.
+    pub length: i32,
+    pub started_at: NaiveDateTime,
+    pub completed_at: Option<NaiveDateTime>,
+}
+
+impl QueryablePomodoro {
+    /// Starts a session of `length` seconds on `task` now.
+    pub fn start(task: &str, length: i32) -> Self {
+        Self {
+            id_pomodoro: Uuid::new_v4().to_string(),
+            id_task: task.to_string(),
+            length,
+            started_at: Utc::now().naive_utc(),
+            completed_at: None,
+        }
+    }
+}
+
+impl From<QueryablePomodoro> for Pomodoro {
+    fn from(value: QueryablePomodoro) -> Self {
+        Pomodoro {
+            id: value.id_pomodoro,
+            task_id: value.id_task,
+            length: value.length,
+            started_at: value.started_at.timestamp(),
+            completed_at: value.completed_at.map(|d| d.timestamp()),
+        }
+    }
+}
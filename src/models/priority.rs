@@ -0,0 +1,32 @@
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Priority;
+
+use crate::schema::priorities;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = priorities)]
+pub struct QueryablePriority {
+    pub level: i32,
+    pub name: String,
+    pub color: Option<String>,
+}
+
+impl From<QueryablePriority> for Priority {
+    fn from(value: QueryablePriority) -> Self {
+        Priority {
+            level: value.level,
+            name: value.name,
+            color: value.color,
+        }
+    }
+}
+
+impl From<Priority> for QueryablePriority {
+    fn from(value: Priority) -> Self {
+        Self {
+            level: value.level,
+            name: value.name,
+            color: value.color,
+        }
+    }
+}
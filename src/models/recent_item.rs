@@ -0,0 +1,42 @@
+use chrono::NaiveDateTime;
+use diesel::{AsChangeset, Insertable, Queryable};
+
+use crate::schema::recent_items;
+
+/// Frecency halves every three days without visits.
+const HALF_LIFE_HOURS: f64 = 72.0;
+
+#[derive(Debug, Clone, Insertable, Queryable, AsChangeset)]
+#[diesel(table_name = recent_items)]
+pub struct QueryableRecentItem {
+    pub id_item: String,
+    pub kind: String,
+    pub visits: i32,
+    pub score: f64,
+    pub last_visited: NaiveDateTime,
+}
+
+impl QueryableRecentItem {
+    pub fn new(id_item: &str, kind: &str, now: NaiveDateTime) -> Self {
+        Self {
+            id_item: id_item.to_string(),
+            kind: kind.to_string(),
+            visits: 0,
+            score: 0.0,
+            last_visited: now,
+        }
+    }
+
+    /// Score of this item at `now`, decayed since the last visit.
+    pub fn frecency(&self, now: NaiveDateTime) -> f64 {
+        let hours = (now - self.last_visited).num_seconds().max(0) as f64 / 3600.0;
+        self.score * 0.5_f64.powf(hours / HALF_LIFE_HOURS)
+    }
+
+    /// Records a new visit at `now`.
+    pub fn visit(&mut self, now: NaiveDateTime) {
+        self.score = self.frecency(now) + 1.0;
+        self.visits += 1;
+        self.last_visited = now;
+    }
+}
@@ -0,0 +1,41 @@
+use diesel::{AsChangeset, Insertable, Queryable};
+use proto_rust::provider::BoardStatus;
+
+use crate::schema::statuses;
+
+#[derive(Debug, Clone, AsChangeset, Insertable, Queryable)]
+#[diesel(table_name = statuses)]
+pub struct QueryableStatus {
+    pub id_status: String,
+    pub parent_list: String,
+    pub name: String,
+    pub position: i64,
+    /// Tasks moved here are completed.
+    pub done: bool,
+    pub color: Option<String>,
+}
+
+impl From<QueryableStatus> for BoardStatus {
+    fn from(value: QueryableStatus) -> Self {
+        BoardStatus {
+            id: value.id_status,
+            list_id: value.parent_list,
+            name: value.name,
+            done: value.done,
+            color: value.color,
+        }
+    }
+}
+
+impl From<BoardStatus> for QueryableStatus {
+    fn from(value: BoardStatus) -> Self {
+        Self {
+            id_status: value.id,
+            parent_list: value.list_id,
+            name: value.name,
+            position: 0,
+            done: value.done,
+            color: value.color,
+        }
+    }
+}
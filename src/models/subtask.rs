@@ -0,0 +1,65 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Subtask;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::schema::subtasks;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = subtasks)]
+pub struct QueryableSubtask {
+    pub id_subtask: String,
+    pub id_task: String,
+    pub title: String,
+    pub completed: bool,
+    pub position: i64,
+    pub created_date_time: NaiveDateTime,
+    pub last_modified_date_time: NaiveDateTime,
+}
+
+impl QueryableSubtask {
+    /// Returns a copy of this subtask that belongs to another task.
+    pub fn copy_to_task(&self, task: &str) -> Self {
+        Self {
+            id_subtask: Uuid::new_v4().to_string(),
+            id_task: task.to_string(),
+            created_date_time: Utc::now().naive_utc(),
+            last_modified_date_time: Utc::now().naive_utc(),
+            ..self.clone()
+        }
+    }
+}
+
+impl From<QueryableSubtask> for Subtask {
+    fn from(value: QueryableSubtask) -> Self {
+        Subtask {
+            id: value.id_subtask,
+            task_id: value.id_task,
+            title: value.title,
+            completed: value.completed,
+            position: value.position,
+            created_date_time: value.created_date_time.timestamp(),
+            last_modified_date_time: value.last_modified_date_time.timestamp(),
+        }
+    }
+}
+
+impl From<Subtask> for QueryableSubtask {
+    fn from(value: Subtask) -> Self {
+        Self {
+            id_subtask: value.id,
+            id_task: value.task_id,
+            title: value.title,
+            completed: value.completed,
+            position: value.position,
+            created_date_time: NaiveDateTime::from_timestamp_opt(value.created_date_time, 0)
+                .unwrap(),
+            last_modified_date_time: NaiveDateTime::from_timestamp_opt(
+                value.last_modified_date_time,
+                0,
+            )
+            .unwrap(),
+        }
+    }
+}
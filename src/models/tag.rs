@@ -0,0 +1,29 @@
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Tag;
+
+use crate::schema::tags;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = tags)]
+pub struct QueryableTag {
+    pub id_tag: String,
+    pub name: String,
+}
+
+impl From<QueryableTag> for Tag {
+    fn from(value: QueryableTag) -> Self {
+        Tag {
+            id: value.id_tag,
+            name: value.name,
+        }
+    }
+}
+
+impl From<Tag> for QueryableTag {
+    fn from(value: Tag) -> Self {
+        Self {
+            id_tag: value.id,
+            name: value.name,
+        }
+    }
+}
@@ -1,13 +1,18 @@
 use chrono::{NaiveDateTime, Utc};
-use diesel::{Insertable, Queryable};
+use chrono_tz::Tz;
+use diesel::{AsChangeset, Insertable, Queryable, QueryableByName};
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
-use proto_rust::provider::{Task, TaskImportance, TaskStatus};
+use proto_rust::provider::{NotesFormat, Task, TaskImportance, TaskStatus};
 
 use crate::schema::tasks;
+use crate::version;
 
-#[derive(Debug, Clone, Insertable, Queryable)]
-#[diesel(table_name = tasks)]
+#[derive(
+    Serialize, Deserialize, Debug, Clone, Insertable, Queryable, QueryableByName, AsChangeset,
+)]
+#[diesel(table_name = tasks, treat_none_as_null = true)]
 pub struct QueryableTask {
     pub id_task: String,
     pub parent_list: String,
@@ -22,6 +27,107 @@ pub struct QueryableTask {
     pub reminder_date: Option<NaiveDateTime>,
     pub created_date_time: NaiveDateTime,
     pub last_modified_date_time: NaiveDateTime,
+    pub position: i64,
+    pub notes_format: i32,
+    pub recurrence_rule: Option<String>,
+    pub estimate_minutes: Option<i32>,
+    pub color: Option<String>,
+    pub emoji: Option<String>,
+    pub url: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub place_name: Option<String>,
+    /// Board column within the list, see `statuses`.
+    pub id_status: Option<String>,
+    /// Kept above the other tasks of the list.
+    pub pinned: bool,
+    /// Before this the task can't be worked on yet.
+    pub start_date: Option<NaiveDateTime>,
+    /// How long the task blocks out from its reminder.
+    pub duration_minutes: Option<i32>,
+    /// IANA timezone the dates were set in, which decides what day they
+    /// fall on.
+    pub timezone: Option<String>,
+    /// Member of the household the task was given to, see `people`.
+    pub assigned_to: Option<String>,
+    /// Hidden from every list and view but kept, unlike deleted tasks.
+    pub archived: bool,
+}
+
+/// Columns of a stored task to overwrite, where `None` keeps the stored
+/// value. Hosts that predate a feature send its defaults, so its columns are
+/// only written for hosts that know about it.
+#[derive(Debug, Clone, AsChangeset)]
+#[diesel(table_name = tasks)]
+pub struct TaskChanges {
+    pub parent_list: Option<String>,
+    pub title: String,
+    pub body: Option<Option<String>>,
+    pub importance: i32,
+    pub favorite: bool,
+    pub is_reminder_on: bool,
+    pub status: i32,
+    pub completed_on: Option<Option<NaiveDateTime>>,
+    pub due_date: Option<Option<NaiveDateTime>>,
+    pub reminder_date: Option<Option<NaiveDateTime>>,
+    pub created_date_time: NaiveDateTime,
+    pub last_modified_date_time: NaiveDateTime,
+    pub position: Option<i64>,
+    pub notes_format: Option<i32>,
+    pub recurrence_rule: Option<Option<String>>,
+    pub estimate_minutes: Option<Option<i32>>,
+    pub color: Option<Option<String>>,
+    pub emoji: Option<Option<String>>,
+    pub url: Option<Option<String>>,
+    pub latitude: Option<Option<f64>>,
+    pub longitude: Option<Option<f64>>,
+    pub place_name: Option<Option<String>>,
+    pub id_status: Option<Option<String>>,
+    pub pinned: Option<bool>,
+    pub start_date: Option<Option<NaiveDateTime>>,
+    pub duration_minutes: Option<Option<i32>>,
+    pub timezone: Option<Option<String>>,
+    pub assigned_to: Option<Option<String>>,
+    pub archived: Option<bool>,
+}
+
+impl TaskChanges {
+    /// Every column of `task` a host speaking `api_version` knows about.
+    pub fn new(task: &QueryableTask, api_version: u32) -> Self {
+        let supports = |feature| version::supports(api_version, feature);
+        let task = task.clone();
+        Self {
+            parent_list: Some(task.parent_list),
+            title: task.title,
+            body: Some(task.body),
+            importance: task.importance,
+            favorite: task.favorite,
+            is_reminder_on: task.is_reminder_on,
+            status: task.status,
+            completed_on: Some(task.completed_on),
+            due_date: Some(task.due_date),
+            reminder_date: Some(task.reminder_date),
+            created_date_time: task.created_date_time,
+            last_modified_date_time: task.last_modified_date_time,
+            position: supports("task-ordering").then_some(task.position),
+            notes_format: supports("notes-format").then_some(task.notes_format),
+            recurrence_rule: supports("recurrence").then_some(task.recurrence_rule),
+            estimate_minutes: supports("estimates").then_some(task.estimate_minutes),
+            color: supports("task-appearance").then_some(task.color),
+            emoji: supports("task-appearance").then_some(task.emoji),
+            url: supports("links").then_some(task.url),
+            latitude: supports("locations").then_some(task.latitude),
+            longitude: supports("locations").then_some(task.longitude),
+            place_name: supports("locations").then_some(task.place_name),
+            id_status: supports("boards").then_some(task.id_status),
+            pinned: supports("task-pinning").then_some(task.pinned),
+            start_date: supports("start-dates").then_some(task.start_date),
+            duration_minutes: supports("agenda").then_some(task.duration_minutes),
+            timezone: supports("task-timezones").then_some(task.timezone),
+            assigned_to: supports("assignees").then_some(task.assigned_to),
+            archived: supports("task-archiving").then_some(task.archived),
+        }
+    }
 }
 
 impl QueryableTask {
@@ -40,8 +146,94 @@ impl QueryableTask {
             status: TaskStatus::NotStarted as i32,
             created_date_time: Utc::now().naive_utc(),
             last_modified_date_time: Utc::now().naive_utc(),
+            position: 0,
+            notes_format: NotesFormat::Plain as i32,
+            recurrence_rule: None,
+            estimate_minutes: None,
+            color: None,
+            emoji: None,
+            url: None,
+            latitude: None,
+            longitude: None,
+            place_name: None,
+            id_status: None,
+            pinned: false,
+            start_date: None,
+            duration_minutes: None,
+            timezone: None,
+            assigned_to: None,
+            archived: false,
         }
     }
+
+    /// The timezone the dates were set in, when it's known.
+    pub fn zone(&self) -> Option<Tz> {
+        self.timezone
+            .as_ref()
+            .and_then(|timezone| timezone.parse().ok())
+    }
+
+    /// Returns a fresh, not yet completed copy of this task.
+    pub fn duplicate(&self) -> Self {
+        Self {
+            id_task: Uuid::new_v4().to_string(),
+            status: TaskStatus::NotStarted as i32,
+            completed_on: None,
+            archived: false,
+            created_date_time: Utc::now().naive_utc(),
+            last_modified_date_time: Utc::now().naive_utc(),
+            ..self.clone()
+        }
+    }
+
+    /// Returns an identical copy of this task that belongs to another list.
+    pub fn copy_to_list(&self, list: &str) -> Self {
+        Self {
+            id_task: Uuid::new_v4().to_string(),
+            parent_list: list.to_string(),
+            id_status: None,
+            ..self.clone()
+        }
+    }
+
+    /// Folds a duplicate into this task, keeping every piece of information
+    /// that is missing here and appending the duplicate's notes.
+    pub fn merge(&mut self, duplicate: &QueryableTask) {
+        if let Some(notes) = duplicate.body.as_ref().filter(|b| !b.trim().is_empty()) {
+            self.body = match self.body.take().filter(|b| !b.trim().is_empty()) {
+                Some(body) => Some(format!("{body}\n\n{notes}")),
+                None => {
+                    self.notes_format = duplicate.notes_format;
+                    Some(notes.clone())
+                }
+            };
+        }
+        self.importance = self.importance.max(duplicate.importance);
+        self.favorite |= duplicate.favorite;
+        self.pinned |= duplicate.pinned;
+        self.due_date = self.due_date.or(duplicate.due_date);
+        self.start_date = self.start_date.or(duplicate.start_date);
+        self.estimate_minutes = self.estimate_minutes.or(duplicate.estimate_minutes);
+        self.duration_minutes = self.duration_minutes.or(duplicate.duration_minutes);
+        self.color = self.color.take().or_else(|| duplicate.color.clone());
+        self.emoji = self.emoji.take().or_else(|| duplicate.emoji.clone());
+        self.url = self.url.take().or_else(|| duplicate.url.clone());
+        self.assigned_to = self
+            .assigned_to
+            .take()
+            .or_else(|| duplicate.assigned_to.clone());
+        if self.latitude.is_none() {
+            self.latitude = duplicate.latitude;
+            self.longitude = duplicate.longitude;
+            self.place_name = duplicate.place_name.clone();
+        }
+        if self.reminder_date.is_none() {
+            self.reminder_date = duplicate.reminder_date;
+            self.is_reminder_on = duplicate.is_reminder_on;
+        }
+        self.created_date_time = self.created_date_time.min(duplicate.created_date_time);
+        self.last_modified_date_time = Utc::now().naive_utc();
+    }
 }
 
 impl From<QueryableTask> for Task {
@@ -60,6 +252,24 @@ impl From<QueryableTask> for Task {
             reminder_date: value.reminder_date.map(|d| d.timestamp()),
             created_date_time: value.created_date_time.timestamp(),
             last_modified_date_time: value.last_modified_date_time.timestamp(),
+            position: value.position,
+            notes_format: value.notes_format,
+            recurrence_rule: value.recurrence_rule,
+            estimate_minutes: value.estimate_minutes,
+            color: value.color,
+            emoji: value.emoji,
+            url: value.url,
+            latitude: value.latitude,
+            longitude: value.longitude,
+            place_name: value.place_name,
+            status_id: value.id_status,
+            pinned: value.pinned,
+            start_date: value.start_date.map(|d| d.timestamp()),
+            duration_minutes: value.duration_minutes,
+            timezone: value.timezone,
+            progress: None,
+            assigned_to: value.assigned_to,
+            archived: value.archived,
         }
     }
 }
@@ -91,6 +301,25 @@ impl From<Task> for QueryableTask {
                 0,
             )
             .unwrap(),
+            position: task.position,
+            notes_format: task.notes_format,
+            recurrence_rule: task.recurrence_rule,
+            estimate_minutes: task.estimate_minutes,
+            color: task.color,
+            emoji: task.emoji,
+            url: task.url,
+            latitude: task.latitude,
+            longitude: task.longitude,
+            place_name: task.place_name,
+            id_status: task.status_id,
+            pinned: task.pinned,
+            start_date: task
+                .start_date
+                .map(|d| NaiveDateTime::from_timestamp_opt(d, 0).unwrap()),
+            duration_minutes: task.duration_minutes,
+            timezone: task.timezone,
+            assigned_to: task.assigned_to,
+            archived: task.archived,
         }
     }
 }
@@ -0,0 +1,48 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use uuid::Uuid;
+
+use crate::models::QueryableTask;
+use crate::schema::templates;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = templates)]
+pub struct QueryableTemplate {
+    pub id_template: String,
+    pub name: String,
+    pub title: String,
+    pub body: Option<String>,
+    pub notes_format: i32,
+    pub importance: i32,
+    /// Seconds from instantiation until the task is due.
+    pub due_offset: Option<i64>,
+    /// Seconds from the due date, or from instantiation when there is none,
+    /// until the reminder.
+    pub reminder_offset: Option<i64>,
+    pub created_date_time: NaiveDateTime,
+}
+
+impl QueryableTemplate {
+    /// Captures `task` as a template. Without an explicit `due_offset` the
+    /// task is due as long after instantiation as it was after its creation.
+    pub fn from_task(task: &QueryableTask, name: String, due_offset: Option<i64>) -> Self {
+        let due_offset = due_offset.or_else(|| {
+            task.due_date
+                .map(|due| (due - task.created_date_time).num_seconds().max(0))
+        });
+        let reminder_offset = task.reminder_date.map(|reminder| {
+            (reminder - task.due_date.unwrap_or(task.created_date_time)).num_seconds()
+        });
+        Self {
+            id_template: Uuid::new_v4().to_string(),
+            name,
+            title: task.title.clone(),
+            body: task.body.clone(),
+            notes_format: task.notes_format,
+            importance: task.importance,
+            due_offset,
+            reminder_offset,
+            created_date_time: Utc::now().naive_utc(),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+use chrono::{Duration, NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::TimeEntry;
+use uuid::Uuid;
+
+use crate::schema::time_entries;
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = time_entries)]
+pub struct QueryableTimeEntry {
+    pub id_entry: String,
+    pub id_task: String,
+    pub started_at: NaiveDateTime,
+    pub stopped_at: Option<NaiveDateTime>,
+}
+
+impl QueryableTimeEntry {
+    /// Starts a timer on `task` now.
+    pub fn start(task: &str) -> Self {
+        Self {
+            id_entry: Uuid::new_v4().to_string(),
+            id_task: task.to_string(),
+            started_at: Utc::now().naive_utc(),
+            stopped_at: None,
+        }
+    }
+
+    /// Time tracked by this entry, up to now while it's still running.
+    pub fn duration(&self) -> Duration {
+        self.stopped_at.unwrap_or_else(|| Utc::now().naive_utc()) - self.started_at
+    }
+}
+
+impl From<QueryableTimeEntry> for TimeEntry {
+    fn from(value: QueryableTimeEntry) -> Self {
+        TimeEntry {
+            duration: value.duration().num_seconds(),
+            id: value.id_entry,
+            task_id: value.id_task,
+            started_at: value.started_at.timestamp(),
+            stopped_at: value.stopped_at.map(|d| d.timestamp()),
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Insertable, Queryable};
+use proto_rust::provider::Tombstone;
+
+use crate::schema::tombstones;
+
+pub const TASK_ENTITY: &str = "task";
+pub const LIST_ENTITY: &str = "list";
+
+#[derive(Debug, Clone, Insertable, Queryable)]
+#[diesel(table_name = tombstones)]
+pub struct QueryableTombstone {
+    pub id_entity: String,
+    pub entity: String,
+    pub deleted_on: NaiveDateTime,
+}
+
+impl QueryableTombstone {
+    pub fn new(id_entity: String, entity: &str) -> Self {
+        Self {
+            id_entity,
+            entity: entity.to_string(),
+            deleted_on: Utc::now().naive_utc(),
+        }
+    }
+}
+
+impl From<QueryableTombstone> for Tombstone {
+    fn from(value: QueryableTombstone) -> Self {
+        Tombstone {
+            id: value.id_entity,
+            entity: value.entity,
+            deleted_on: value.deleted_on.timestamp(),
+        }
+    }
+}
+
+impl From<Tombstone> for QueryableTombstone {
+    fn from(value: Tombstone) -> Self {
+        Self {
+            id_entity: value.id,
+            entity: value.entity,
+            deleted_on: NaiveDateTime::from_timestamp_opt(value.deleted_on, 0).unwrap(),
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::Result;
+use chrono::Duration;
+use notify_rust::{Notification, Timeout};
+use proto_rust::provider::ChangeKind;
+
+use crate::database::{establish_connection, retry_busy};
+use crate::events::Changes;
+use crate::models::QueryableTask;
+use crate::reminders::{complete, snooze};
+
+const COMPLETE_ACTION: &str = "complete";
+const SNOOZE_ACTION: &str = "snooze";
+
+/// How long the snooze action puts a reminder off.
+const SNOOZE_MINUTES: i64 = 10;
+
+/// Notifications whose actions are waited for at once. Each holds a thread
+/// until it's closed, later ones are shown without actions instead.
+const MAX_WAITING: usize = 16;
+
+static WAITING: AtomicUsize = AtomicUsize::new(0);
+
+/// Shows a desktop notification for the reminder of `task` and applies the
+/// action picked from it, publishing the changes it makes. Talking to the
+/// notification server blocks, so it's done off the runtime.
+pub async fn show(task: QueryableTask, changes: Changes) {
+    let shown = tokio::task::spawn_blocking(move || notify(&task, changes));
+    if let Err(err) = shown.await {
+        tracing::error!("Failed to show the reminder notification: {err}");
+    }
+}
+
+fn notify(task: &QueryableTask, changes: Changes) {
+    let actions = WAITING.fetch_add(1, Ordering::SeqCst) < MAX_WAITING;
+    if !actions {
+        WAITING.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    let mut notification = Notification::new();
+    notification
+        .appname("Done")
+        .summary(&task.title)
+        .body("Reminder")
+        .icon("alarm-symbolic")
+        .timeout(Timeout::Never);
+    if actions {
+        notification
+            .action(COMPLETE_ACTION, "Complete")
+            .action(SNOOZE_ACTION, "Snooze");
+    }
+    let handle = match notification.show() {
+        Ok(handle) => handle,
+        Err(err) => {
+            tracing::error!("Failed to show the reminder notification: {err}");
+            if actions {
+                WAITING.fetch_sub(1, Ordering::SeqCst);
+            }
+            return;
+        }
+    };
+    if !actions {
+        return;
+    }
+
+    // Waiting for the action blocks until the notification is closed, which
+    // may be never, so it gets a thread of its own.
+    let id = task.id_task.clone();
+    std::thread::spawn(move || {
+        handle.wait_for_action(|action| {
+            if let Err(err) = apply(action, &id, &changes) {
+                tracing::error!("Failed to apply the '{action}' reminder action: {err}");
+            }
+        });
+        WAITING.fetch_sub(1, Ordering::SeqCst);
+    });
+}
+
+fn apply(action: &str, id: &str, changes: &Changes) -> Result<()> {
+    match action {
+        COMPLETE_ACTION => {
+            let (completed, next) = retry_busy(|| complete(&mut establish_connection()?, id))?;
+            changes.task(ChangeKind::TaskUpdated, completed.into());
+            if let Some(next) = next {
+                changes.task(ChangeKind::TaskCreated, next.into());
+            }
+        }
+        SNOOZE_ACTION => {
+            let snoozed = retry_busy(|| {
+                snooze(
+                    &mut establish_connection()?,
+                    id,
+                    Duration::minutes(SNOOZE_MINUTES),
+                )
+            })?;
+            changes.task(ChangeKind::TaskUpdated, snoozed.into());
+        }
+        // Dismissed or closed.
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,224 @@
+use anyhow::{Context, Result};
+use chrono::Utc;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::models::{QueryableList, QueryableListGroup, QueryableSubtask, QueryableTask};
+use crate::schema::{list_groups, lists, subtasks, tasks};
+
+/// Position right after the last task of `list`.
+pub fn next_task_position(connection: &mut SqliteConnection, list: &str) -> Result<i64> {
+    let last: Option<i64> = tasks::table
+        .filter(tasks::parent_list.eq(list))
+        .select(tasks::position)
+        .order(tasks::position.desc())
+        .first(connection)
+        .optional()?;
+    Ok(last.map_or(0, |last| last + 1))
+}
+
+/// Moves `task` right after `after` in its list, or to the top when `after`
+/// is `None`, and renumbers the list so positions stay contiguous.
+///
+/// Returns every task whose position changed, the moved one first.
+pub fn reorder_task(
+    connection: &mut SqliteConnection,
+    task: &str,
+    after: Option<&str>,
+) -> Result<Vec<QueryableTask>> {
+    let moved: QueryableTask = tasks::table
+        .find(task)
+        .first(connection)
+        .context("The task doesn't exist.")?;
+    let mut siblings: Vec<QueryableTask> = tasks::table
+        .filter(tasks::parent_list.eq(&moved.parent_list))
+        .filter(tasks::id_task.ne(task))
+        .order((tasks::position.asc(), tasks::created_date_time.asc()))
+        .load(connection)?;
+
+    let index = match after {
+        Some(after) => {
+            siblings
+                .iter()
+                .position(|sibling| sibling.id_task == after)
+                .context("The task to place it after isn't in the same list.")?
+                + 1
+        }
+        None => 0,
+    };
+    siblings.insert(index, moved);
+
+    let now = Utc::now().naive_utc();
+    let mut changed = vec![];
+    for (position, mut sibling) in siblings.into_iter().enumerate() {
+        let position = position as i64;
+        if sibling.id_task != task && sibling.position == position {
+            continue;
+        }
+        diesel::update(tasks::table.find(&sibling.id_task))
+            .set((
+                tasks::position.eq(position),
+                tasks::last_modified_date_time.eq(now),
+            ))
+            .execute(connection)?;
+        sibling.position = position;
+        sibling.last_modified_date_time = now;
+        if sibling.id_task == task {
+            changed.insert(0, sibling);
+        } else {
+            changed.push(sibling);
+        }
+    }
+    Ok(changed)
+}
+
+/// Position right after the last list.
+pub fn next_list_position(connection: &mut SqliteConnection) -> Result<i64> {
+    let last: Option<i64> = lists::table
+        .select(lists::position)
+        .order(lists::position.desc())
+        .first(connection)
+        .optional()?;
+    Ok(last.map_or(0, |last| last + 1))
+}
+
+/// Numbers lists in the order of `ordered`. Lists missing from it keep
+/// their relative order after the ones that were given.
+///
+/// Returns every list, in their new order.
+pub fn reorder_lists(
+    connection: &mut SqliteConnection,
+    ordered: &[String],
+) -> Result<Vec<QueryableList>> {
+    let mut all: Vec<QueryableList> = lists::table.order(lists::position.asc()).load(connection)?;
+    if let Some(unknown) = ordered
+        .iter()
+        .find(|id| !all.iter().any(|list| &list.id_list == *id))
+    {
+        anyhow::bail!("The list {unknown} doesn't exist.");
+    }
+    all.sort_by_key(|list| {
+        ordered
+            .iter()
+            .position(|id| *id == list.id_list)
+            .unwrap_or(ordered.len())
+    });
+
+    for (position, list) in all.iter_mut().enumerate() {
+        let position = position as i64;
+        if list.position == position {
+            continue;
+        }
+        diesel::update(lists::table.find(&list.id_list))
+            .set(lists::position.eq(position))
+            .execute(connection)?;
+        list.position = position;
+    }
+    Ok(all)
+}
+
+/// Position right after the last list group.
+pub fn next_group_position(connection: &mut SqliteConnection) -> Result<i64> {
+    let last: Option<i64> = list_groups::table
+        .select(list_groups::position)
+        .order(list_groups::position.desc())
+        .first(connection)
+        .optional()?;
+    Ok(last.map_or(0, |last| last + 1))
+}
+
+/// Numbers list groups in the order of `ordered`, the way `reorder_lists`
+/// does for lists.
+///
+/// Returns every group, in their new order.
+pub fn reorder_groups(
+    connection: &mut SqliteConnection,
+    ordered: &[String],
+) -> Result<Vec<QueryableListGroup>> {
+    let mut all: Vec<QueryableListGroup> = list_groups::table
+        .order(list_groups::position.asc())
+        .load(connection)?;
+    if let Some(unknown) = ordered
+        .iter()
+        .find(|id| !all.iter().any(|group| &group.id_group == *id))
+    {
+        anyhow::bail!("The group {unknown} doesn't exist.");
+    }
+    all.sort_by_key(|group| {
+        ordered
+            .iter()
+            .position(|id| *id == group.id_group)
+            .unwrap_or(ordered.len())
+    });
+
+    for (position, group) in all.iter_mut().enumerate() {
+        let position = position as i64;
+        if group.position == position {
+            continue;
+        }
+        diesel::update(list_groups::table.find(&group.id_group))
+            .set(list_groups::position.eq(position))
+            .execute(connection)?;
+        group.position = position;
+    }
+    Ok(all)
+}
+
+/// Position right after the last subtask of `task`.
+pub fn next_subtask_position(connection: &mut SqliteConnection, task: &str) -> Result<i64> {
+    let last: Option<i64> = subtasks::table
+        .filter(subtasks::id_task.eq(task))
+        .select(subtasks::position)
+        .order(subtasks::position.desc())
+        .first(connection)
+        .optional()?;
+    Ok(last.map_or(0, |last| last + 1))
+}
+
+/// Moves `subtask` right after `after` in its checklist, or to the top when
+/// `after` is `None`, and renumbers the checklist.
+///
+/// Returns the whole checklist in its new order.
+pub fn reorder_subtask(
+    connection: &mut SqliteConnection,
+    subtask: &str,
+    after: Option<&str>,
+) -> Result<Vec<QueryableSubtask>> {
+    let moved: QueryableSubtask = subtasks::table
+        .find(subtask)
+        .first(connection)
+        .context("The subtask doesn't exist.")?;
+    let mut checklist: Vec<QueryableSubtask> = subtasks::table
+        .filter(subtasks::id_task.eq(&moved.id_task))
+        .filter(subtasks::id_subtask.ne(subtask))
+        .order((subtasks::position.asc(), subtasks::created_date_time.asc()))
+        .load(connection)?;
+
+    let index = match after {
+        Some(after) => {
+            checklist
+                .iter()
+                .position(|item| item.id_subtask == after)
+                .context("The subtask to place it after isn't in the same task.")?
+                + 1
+        }
+        None => 0,
+    };
+    checklist.insert(index, moved);
+
+    let now = Utc::now().naive_utc();
+    for (position, item) in checklist.iter_mut().enumerate() {
+        let position = position as i64;
+        if item.id_subtask != subtask && item.position == position {
+            continue;
+        }
+        diesel::update(subtasks::table.find(&item.id_subtask))
+            .set((
+                subtasks::position.eq(position),
+                subtasks::last_modified_date_time.eq(now),
+            ))
+            .execute(connection)?;
+        item.position = position;
+        item.last_modified_date_time = now;
+    }
+    Ok(checklist)
+}
@@ -0,0 +1,54 @@
+use anyhow::{bail, Result};
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection,
+};
+
+use crate::models::QueryablePriority;
+use crate::schema::{priorities, tasks};
+
+/// Levels tasks can use as their `importance`, lowest first.
+pub fn read(connection: &mut SqliteConnection) -> Result<Vec<QueryablePriority>> {
+    let levels = priorities::table
+        .order(priorities::level.asc())
+        .load(connection)?;
+    Ok(levels)
+}
+
+/// Whether `level` is part of the configured scheme.
+pub fn exists(connection: &mut SqliteConnection, level: i32) -> Result<bool> {
+    let found: Option<i32> = priorities::table
+        .find(level)
+        .select(priorities::level)
+        .first(connection)
+        .optional()?;
+    Ok(found.is_some())
+}
+
+/// Fails unless `level` is part of the configured scheme.
+pub fn check(connection: &mut SqliteConnection, level: i32) -> Result<()> {
+    if !exists(connection, level)? {
+        bail!("`importance` {level} isn't a configured priority");
+    }
+    Ok(())
+}
+
+/// Replaces the whole scheme with `levels`. Levels that tasks still use
+/// can't be removed.
+pub fn replace(connection: &mut SqliteConnection, levels: &[QueryablePriority]) -> Result<()> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let kept: Vec<i32> = levels.iter().map(|priority| priority.level).collect();
+        let in_use: Option<i32> = tasks::table
+            .filter(tasks::importance.ne_all(&kept))
+            .select(tasks::importance)
+            .first(connection)
+            .optional()?;
+        if let Some(level) = in_use {
+            bail!("Tasks still use the priority level {level}.");
+        }
+        diesel::delete(priorities::table).execute(connection)?;
+        diesel::insert_into(priorities::table)
+            .values(levels)
+            .execute(connection)?;
+        Ok(())
+    })
+}
@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use diesel::dsl::{count_star, sql};
+use diesel::sql_types::BigInt;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::Task;
+
+use crate::database::establish_connection;
+use crate::schema::subtasks;
+
+/// Tasks counted per query, well below the variables SQLite allows in one
+/// statement.
+const BATCH_SIZE: usize = 500;
+
+/// Fills in the share of completed subtasks of every task in `tasks` that
+/// has any, counted a batch of tasks per query.
+pub fn attach(connection: &mut SqliteConnection, tasks: &mut [Task]) -> Result<()> {
+    let mut counts: HashMap<String, (i64, i64)> = HashMap::new();
+    for batch in tasks.chunks(BATCH_SIZE) {
+        let ids: Vec<&str> = batch.iter().map(|task| task.id.as_str()).collect();
+        let rows = subtasks::table
+            .filter(subtasks::id_task.eq_any(&ids))
+            .group_by(subtasks::id_task)
+            .select((
+                subtasks::id_task,
+                count_star(),
+                sql::<BigInt>("SUM(subtasks.completed)"),
+            ))
+            .load::<(String, i64, i64)>(connection)?;
+        counts.extend(
+            rows.into_iter()
+                .map(|(task, total, completed)| (task, (total, completed))),
+        );
+    }
+
+    for task in tasks {
+        task.progress = counts
+            .get(&task.id)
+            .map(|(total, completed)| *completed as f64 / *total as f64);
+    }
+    Ok(())
+}
+
+/// `task` with its progress filled in, counted on a blocking thread.
+/// Responses still go out when the subtasks can't be counted, just without
+/// progress.
+pub async fn with_progress(task: Task) -> Task {
+    let fallback = task.clone();
+    let counted = tokio::task::spawn_blocking(move || {
+        let mut tasks = [task];
+        let result =
+            establish_connection().and_then(|mut connection| attach(&mut connection, &mut tasks));
+        if let Err(err) = result {
+            tracing::warn!("Failed to compute task progress: {err}");
+        }
+        let [task] = tasks;
+        task
+    });
+    counted.await.unwrap_or(fallback)
+}
@@ -0,0 +1,406 @@
+//! A small query language for power users and the host's search bar.
+//!
+//! ```text
+//! list:groceries due<2024-06-01 is:favorite -is:done @errands milk
+//! ```
+//!
+//! `start` compares start dates the way `due` does, `is:scheduled` matches
+//! tasks that start today and `is:startable` the ones that can be started
+//! now, so `-is:startable` lists what's deferred.
+//!
+//! Archived tasks are left out unless the query mentions `is:archived`.
+//!
+//! Terms are separated by whitespace and all have to match. A leading `-`
+//! negates a term, values with spaces can be quoted, `@name` is short for
+//! `tag:name` and anything that isn't a filter is matched against the search
+//! index.
+
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, NaiveDate, Utc};
+use diesel::dsl::{not, sql};
+use diesel::expression::{is_aggregate, ValidGrouping};
+use diesel::sql_types::{Bool, Nullable, Text, Timestamp};
+use diesel::sqlite::Sqlite;
+use diesel::{
+    BoolExpressionMethods, BoxableExpression, ExpressionMethods, NullableExpressionMethods,
+    QueryDsl, RunQueryDsl, SqliteConnection, TextExpressionMethods,
+};
+use proto_rust::provider::TaskStatus;
+
+use crate::metadata::RequestContext;
+use crate::models::QueryableTask;
+use crate::schema::{lists, priorities, tags, task_tags, tasks};
+use crate::search::match_expression;
+
+type Filter = Box<dyn BoxableExpression<tasks::table, Sqlite, SqlType = Bool>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    Equal,
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+struct Term {
+    negated: bool,
+    key: Option<String>,
+    comparison: Comparison,
+    value: String,
+}
+
+/// Parses `query` and returns the matching tasks. Relative dates are
+/// resolved in the timezone of the request.
+pub fn query_tasks(
+    connection: &mut SqliteConnection,
+    query: &str,
+    context: &RequestContext,
+) -> Result<Vec<QueryableTask>> {
+    let terms = parse(query)?;
+    let mut statement = tasks::table.into_boxed();
+    let mut text = vec![];
+
+    let archived = terms
+        .iter()
+        .any(|term| term.key.as_deref() == Some("is") && term.value == "archived");
+    if !archived {
+        statement = statement.filter(tasks::archived.eq(false));
+    }
+
+    for term in terms {
+        if term.key.is_none() {
+            if term.negated {
+                bail!("Free text can't be negated: -{}", term.value);
+            }
+            text.push(term.value);
+            continue;
+        }
+        let filter = filter(&term, context)?;
+        statement = if term.negated {
+            statement.filter(not(filter))
+        } else {
+            statement.filter(filter)
+        };
+    }
+
+    let expression = match_expression(&text.join(" "));
+    if !expression.is_empty() {
+        statement = statement.filter(
+            sql::<Bool>("tasks.id_task IN (SELECT id_task FROM tasks_fts WHERE tasks_fts MATCH ")
+                .bind::<Text, _>(expression)
+                .sql(")"),
+        );
+    }
+
+    let results = statement
+        .load::<QueryableTask>(connection)
+        .context("Failed to query tasks.")?;
+    Ok(results)
+}
+
+fn filter(term: &Term, context: &RequestContext) -> Result<Filter> {
+    let key = term.key.as_deref().unwrap_or_default();
+    let value = term.value.as_str();
+    if !["due", "start"].contains(&key) && term.comparison != Comparison::Equal {
+        bail!("Only due and start dates can be compared: {key}");
+    }
+
+    let filter: Filter = match (key, value) {
+        ("list", name) => Box::new(
+            tasks::parent_list.eq_any(
+                lists::table.select(lists::id_list).filter(
+                    lists::name
+                        .like(name.to_string())
+                        .or(lists::id_list.eq(name.to_string())),
+                ),
+            ),
+        ),
+        // Tag names are matched exactly, `%` and `_` are common in them.
+        ("tag", name) => Box::new(
+            tasks::id_task.eq_any(
+                task_tags::table.select(task_tags::id_task).filter(
+                    task_tags::id_tag.eq_any(
+                        tags::table.select(tags::id_tag).filter(
+                            tags::name
+                                .eq(name.to_string())
+                                .or(tags::id_tag.eq(name.to_string())),
+                        ),
+                    ),
+                ),
+            ),
+        ),
+        ("is", "done" | "completed") => Box::new(tasks::status.eq(TaskStatus::Completed as i32)),
+        ("is", "favorite" | "starred") => Box::new(tasks::favorite.eq(true)),
+        // The highest of the configured priorities.
+        ("is", "important") => Box::new(
+            tasks::importance.eq_any(
+                priorities::table
+                    .select(priorities::level)
+                    .order(priorities::level.desc())
+                    .limit(1),
+            ),
+        ),
+        ("is", "reminder") => Box::new(tasks::is_reminder_on.eq(true)),
+        ("is", "archived") => Box::new(tasks::archived.eq(true)),
+        ("is", "overdue") => Box::new(
+            tasks::due_date
+                .is_not_null()
+                .and(tasks::due_date.assume_not_null().lt(Utc::now().naive_utc()))
+                .and(tasks::status.ne(TaskStatus::Completed as i32)),
+        ),
+        ("is", "scheduled") => Box::new(tasks::start_date.is_not_null().and(compare_date(
+            tasks::start_date,
+            context.today(),
+            Comparison::Equal,
+            context,
+        ))),
+        ("is", "startable") => {
+            let end = context.start_of_day(context.today() + Duration::days(1));
+            Box::new(
+                tasks::start_date
+                    .is_null()
+                    .or(tasks::start_date.assume_not_null().lt(end)),
+            )
+        }
+        ("has", "due") => Box::new(tasks::due_date.is_not_null()),
+        ("has", "start") => Box::new(tasks::start_date.is_not_null()),
+        ("has", "notes") => Box::new(
+            tasks::body
+                .is_not_null()
+                .and(tasks::body.assume_not_null().ne("")),
+        ),
+        ("due", date) => {
+            let date = parse_date(date, context)?;
+            Box::new(tasks::due_date.is_not_null().and(compare_date(
+                tasks::due_date,
+                date,
+                term.comparison,
+                context,
+            )))
+        }
+        ("start", date) => {
+            let date = parse_date(date, context)?;
+            Box::new(tasks::start_date.is_not_null().and(compare_date(
+                tasks::start_date,
+                date,
+                term.comparison,
+                context,
+            )))
+        }
+        (key, value) => bail!("Unknown filter: {key}:{value}"),
+    };
+    Ok(filter)
+}
+
+/// Compares the day a nullable date `column` falls on with `date`.
+fn compare_date<C>(
+    column: C,
+    date: NaiveDate,
+    comparison: Comparison,
+    context: &RequestContext,
+) -> Filter
+where
+    C: BoxableExpression<tasks::table, Sqlite, SqlType = Nullable<Timestamp>>
+        + ValidGrouping<(), IsAggregate = is_aggregate::No>
+        + Copy
+        + 'static,
+{
+    let start = context.start_of_day(date);
+    let end = context.start_of_day(date + Duration::days(1));
+    let value = column.assume_not_null();
+    match comparison {
+        Comparison::Equal => Box::new(value.ge(start).and(value.lt(end))),
+        Comparison::Less => Box::new(value.lt(start)),
+        Comparison::LessOrEqual => Box::new(value.lt(end)),
+        Comparison::Greater => Box::new(value.ge(end)),
+        Comparison::GreaterOrEqual => Box::new(value.ge(start)),
+    }
+}
+
+/// Accepts `YYYY-MM-DD`, `today`, `tomorrow` and `yesterday`.
+fn parse_date(value: &str, context: &RequestContext) -> Result<NaiveDate> {
+    let today = context.today();
+    let date = match value {
+        "today" => today,
+        "tomorrow" => today + Duration::days(1),
+        "yesterday" => today - Duration::days(1),
+        value => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .with_context(|| format!("Invalid date: {value}"))?,
+    };
+    Ok(date)
+}
+
+fn parse(query: &str) -> Result<Vec<Term>> {
+    let mut terms = vec![];
+    let mut chars = query.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        if chars.peek().is_none() {
+            break;
+        }
+
+        let mut token = String::new();
+        let mut quoted = false;
+        while let Some(c) = chars.next_if(|c| quoted || !c.is_whitespace()) {
+            if c == '"' {
+                quoted = !quoted;
+            } else {
+                token.push(c);
+            }
+        }
+        if quoted {
+            bail!("Unterminated quote in query");
+        }
+        terms.push(term(&token));
+    }
+
+    Ok(terms)
+}
+
+fn term(token: &str) -> Term {
+    let (negated, token) = match token.strip_prefix('-') {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+    if let Some(tag) = token.strip_prefix('@').filter(|tag| !tag.is_empty()) {
+        return Term {
+            negated,
+            key: Some("tag".to_string()),
+            comparison: Comparison::Equal,
+            value: tag.to_string(),
+        };
+    }
+
+    let operators = [
+        ("<=", Comparison::LessOrEqual),
+        (">=", Comparison::GreaterOrEqual),
+        (":", Comparison::Equal),
+        ("<", Comparison::Less),
+        (">", Comparison::Greater),
+    ];
+    let split = operators
+        .iter()
+        .filter_map(|(operator, comparison)| {
+            token
+                .find(operator)
+                .map(|index| (index, operator.len(), *comparison))
+        })
+        .min_by_key(|(index, length, _)| (*index, usize::MAX - length));
+
+    match split {
+        Some((index, length, comparison)) if index > 0 => Term {
+            negated,
+            key: Some(token[..index].to_lowercase()),
+            comparison,
+            value: token[index + length..].to_string(),
+        },
+        _ => Term {
+            negated,
+            key: None,
+            comparison: Comparison::Equal,
+            value: token.to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyed(negated: bool, key: &str, comparison: Comparison, value: &str) -> Term {
+        Term {
+            negated,
+            key: Some(key.to_string()),
+            comparison,
+            value: value.to_string(),
+        }
+    }
+
+    fn text(value: &str) -> Term {
+        Term {
+            negated: false,
+            key: None,
+            comparison: Comparison::Equal,
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn queries_are_split_into_terms() {
+        assert_eq!(
+            parse("list:groceries due<2024-06-01 is:favorite -is:done @errands milk").unwrap(),
+            vec![
+                keyed(false, "list", Comparison::Equal, "groceries"),
+                keyed(false, "due", Comparison::Less, "2024-06-01"),
+                keyed(false, "is", Comparison::Equal, "favorite"),
+                keyed(true, "is", Comparison::Equal, "done"),
+                keyed(false, "tag", Comparison::Equal, "errands"),
+                text("milk"),
+            ]
+        );
+        assert!(parse("  ").unwrap().is_empty());
+    }
+
+    #[test]
+    fn quoted_values_keep_their_spaces() {
+        assert_eq!(
+            parse(r#"list:"Home office" "paint the wall""#).unwrap(),
+            vec![
+                keyed(false, "list", Comparison::Equal, "Home office"),
+                text("paint the wall"),
+            ]
+        );
+        assert!(parse(r#"list:"Home office"#).is_err());
+    }
+
+    #[test]
+    fn the_longest_operator_wins() {
+        assert_eq!(
+            term("due<=2024-06-01"),
+            keyed(false, "due", Comparison::LessOrEqual, "2024-06-01")
+        );
+        assert_eq!(
+            term("start>=today"),
+            keyed(false, "start", Comparison::GreaterOrEqual, "today")
+        );
+        assert_eq!(
+            term("due>tomorrow"),
+            keyed(false, "due", Comparison::Greater, "tomorrow")
+        );
+        // The first operator splits, the rest belongs to the value.
+        assert_eq!(
+            term("tag:a<b"),
+            keyed(false, "tag", Comparison::Equal, "a<b")
+        );
+    }
+
+    #[test]
+    fn keys_are_case_insensitive() {
+        assert_eq!(
+            term("IS:Done"),
+            keyed(false, "is", Comparison::Equal, "Done")
+        );
+    }
+
+    #[test]
+    fn terms_without_a_key_are_free_text() {
+        assert_eq!(term(":done"), text(":done"));
+        assert_eq!(term("<3"), text("<3"));
+        assert_eq!(term("@"), text("@"));
+        assert_eq!(term("-"), text("-"));
+        assert_eq!(
+            term("-milk"),
+            Term {
+                negated: true,
+                ..text("milk")
+            }
+        );
+        assert_eq!(
+            term("-@home"),
+            keyed(true, "tag", Comparison::Equal, "home")
+        );
+    }
+}
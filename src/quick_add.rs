@@ -0,0 +1,346 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, Weekday};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use uuid::Uuid;
+
+use crate::inbox::{is_inbox, INBOX_ID};
+use crate::list_settings;
+use crate::metadata::RequestContext;
+use crate::models::{QueryableTag, QueryableTask};
+use crate::ordering::next_task_position;
+use crate::priorities;
+use crate::schema::{lists, tags, task_tags, tasks};
+use crate::settings;
+
+/// Words dropped when they introduce a date or a time, as in "at 9am".
+const PREPOSITIONS: &[&str] = &["at", "on", "by", "due"];
+
+/// Relative dates further out are past the last date chrono represents.
+const MAX_DAYS: i64 = 100_000_000;
+
+/// What a quick-add line asks for, before anything is looked up.
+#[derive(Debug, Default, PartialEq)]
+pub struct Parsed {
+    pub title: String,
+    pub date: Option<NaiveDate>,
+    pub time: Option<NaiveTime>,
+    pub tags: Vec<String>,
+    pub priority: Option<String>,
+}
+
+/// Splits a line like "Pay rent tomorrow 9am #finance !high" into its
+/// title, date, time, `#tags` and `!priority`. Dates and times are wall
+/// clock values relative to `now`, in the user's timezone.
+pub fn parse(text: &str, now: NaiveDateTime) -> Parsed {
+    let today = now.date();
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut parsed = Parsed::default();
+    let mut title: Vec<&str> = vec![];
+
+    let mut index = 0;
+    while index < words.len() {
+        let word = words[index];
+        let lower = word.to_lowercase();
+        index += 1;
+
+        if let Some(tag) = word.strip_prefix('#').filter(|tag| !tag.is_empty()) {
+            if !parsed
+                .tags
+                .iter()
+                .any(|known| known.eq_ignore_ascii_case(tag))
+            {
+                parsed.tags.push(tag.to_string());
+            }
+            continue;
+        }
+        if let Some(priority) = word.strip_prefix('!').filter(|name| !name.is_empty()) {
+            parsed.priority = Some(priority.to_string());
+            continue;
+        }
+
+        let relative = (lower == "in")
+            .then(|| words.get(index..index + 2))
+            .flatten()
+            .and_then(|rest| in_days(rest[0], rest[1], today));
+        if let Some(date) = relative {
+            parsed.date = Some(date);
+            index += 2;
+            continue;
+        }
+        if let Some(date) = date(&lower, today) {
+            parsed.date = Some(date);
+        } else if let Some(time) = time(&lower) {
+            parsed.time = Some(time);
+        } else {
+            title.push(word);
+            continue;
+        }
+        if title
+            .last()
+            .is_some_and(|last| PREPOSITIONS.contains(&last.to_lowercase().as_str()))
+        {
+            title.pop();
+        }
+    }
+
+    // A time on its own means its next occurrence.
+    if let (None, Some(time)) = (parsed.date, parsed.time) {
+        parsed.date = if time > now.time() {
+            Some(today)
+        } else {
+            today.succ_opt()
+        };
+    }
+    parsed.title = title.join(" ");
+    parsed
+}
+
+/// Creates the task `text` describes in `list`, or in the default list
+/// when none is given and the inbox when there's no list at all. Tags that
+/// don't exist yet are created.
+///
+/// Returns the task and its tags.
+pub fn create(
+    connection: &mut SqliteConnection,
+    context: &RequestContext,
+    text: &str,
+    list: Option<&str>,
+) -> Result<(QueryableTask, Vec<QueryableTag>)> {
+    let parsed = parse(text, context.now());
+    if parsed.title.is_empty() {
+        bail!("The task needs a title.");
+    }
+
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let list = match list {
+            Some(list) if is_inbox(list) => INBOX_ID.to_string(),
+            Some(list) => lists::table
+                .find(list)
+                .select(lists::id_list)
+                .first::<String>(connection)
+                .context("The list doesn't exist.")?,
+            // Without any list, the task waits in the inbox.
+            None => settings::default_list(connection)?
+                .map_or_else(|| INBOX_ID.to_string(), |list| list.id_list),
+        };
+
+        let mut task = QueryableTask::new(parsed.title.clone(), list);
+        task.timezone = Some(context.timezone.name().to_string());
+        if let Some(name) = &parsed.priority {
+            task.importance = priority(connection, name)?;
+        }
+        priorities::check(connection, task.importance)?;
+        if let Some(date) = parsed.date {
+            task.due_date = Some(context.start_of_day(date));
+        }
+        if let (Some(date), Some(time)) = (parsed.date, parsed.time) {
+            task.reminder_date = Some(context.to_utc(date.and_time(time)));
+            task.is_reminder_on = true;
+        }
+        list_settings::apply_defaults(connection, &mut task)?;
+        task.position = next_task_position(connection, &task.parent_list)?;
+        diesel::insert_into(tasks::table)
+            .values(&task)
+            .execute(connection)?;
+
+        let known: Vec<QueryableTag> = tags::table.load(connection)?;
+        let mut tagged = vec![];
+        for name in &parsed.tags {
+            let tag = match known.iter().find(|tag| tag.name.eq_ignore_ascii_case(name)) {
+                Some(tag) => tag.clone(),
+                None => {
+                    let tag = QueryableTag {
+                        id_tag: Uuid::new_v4().to_string(),
+                        name: name.clone(),
+                    };
+                    diesel::insert_into(tags::table)
+                        .values(&tag)
+                        .execute(connection)?;
+                    tag
+                }
+            };
+            diesel::insert_into(task_tags::table)
+                .values((
+                    task_tags::id_task.eq(&task.id_task),
+                    task_tags::id_tag.eq(&tag.id_tag),
+                ))
+                .execute(connection)?;
+            tagged.push(tag);
+        }
+        Ok((task, tagged))
+    })
+}
+
+/// The level of the priority called `name`, or given as a number.
+fn priority(connection: &mut SqliteConnection, name: &str) -> Result<i32> {
+    if let Ok(level) = name.parse::<i32>() {
+        if priorities::exists(connection, level)? {
+            return Ok(level);
+        }
+    }
+    priorities::read(connection)?
+        .into_iter()
+        .find(|priority| priority.name.eq_ignore_ascii_case(name))
+        .map(|priority| priority.level)
+        .with_context(|| format!("There is no priority called '{name}'."))
+}
+
+fn date(word: &str, today: NaiveDate) -> Option<NaiveDate> {
+    match word {
+        "today" | "tonight" => return Some(today),
+        "tomorrow" | "tmr" => return today.succ_opt(),
+        _ => {}
+    }
+    if let Ok(date) = NaiveDate::parse_from_str(word, "%Y-%m-%d") {
+        return Some(date);
+    }
+    // The coming weekday, today included. Abbreviations like "sun" are too
+    // easily part of the title.
+    if word.len() <= 3 {
+        return None;
+    }
+    let weekday = word.parse::<Weekday>().ok()?;
+    let ahead = (weekday.num_days_from_monday() + 7 - today.weekday().num_days_from_monday()) % 7;
+    today.checked_add_signed(Duration::days(ahead as i64))
+}
+
+/// The date "in 3 days" or "in 2 weeks" from `today`, `None` when it's out
+/// of range so the phrase stays in the title.
+fn in_days(count: &str, unit: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let count: i64 = count.parse().ok()?;
+    let days = match unit.to_lowercase().as_str() {
+        "day" | "days" => count,
+        "week" | "weeks" => count.checked_mul(7)?,
+        _ => return None,
+    };
+    if days.abs() > MAX_DAYS {
+        return None;
+    }
+    today.checked_add_signed(Duration::days(days))
+}
+
+/// "9am", "9:30pm", "21:00" or "noon". A bare number isn't a time, it's
+/// more likely part of the title.
+fn time(word: &str) -> Option<NaiveTime> {
+    if word == "noon" {
+        return NaiveTime::from_hms_opt(12, 0, 0);
+    }
+    let (clock, offset) = if let Some(clock) = word.strip_suffix("am") {
+        (clock, Some(0))
+    } else if let Some(clock) = word.strip_suffix("pm") {
+        (clock, Some(12))
+    } else {
+        (word, None)
+    };
+    let (hour, minute) = match clock.split_once(':') {
+        Some((hour, minute)) if minute.len() == 2 => (hour, minute.parse().ok()?),
+        None if offset.is_some() => (clock, 0),
+        _ => return None,
+    };
+    let mut hour: u32 = hour.parse().ok()?;
+    if let Some(offset) = offset {
+        if !(1..=12).contains(&hour) {
+            return None;
+        }
+        hour = hour % 12 + offset;
+    }
+    NaiveTime::from_hms_opt(hour, minute, 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A Wednesday morning.
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2023, 1, 4)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+    }
+
+    fn day(day: u32) -> Option<NaiveDate> {
+        NaiveDate::from_ymd_opt(2023, 1, day)
+    }
+
+    fn hour(hour: u32) -> Option<NaiveTime> {
+        NaiveTime::from_hms_opt(hour, 0, 0)
+    }
+
+    #[test]
+    fn lines_are_split_into_their_parts() {
+        assert_eq!(
+            parse("Pay rent tomorrow 9am #finance !high", now()),
+            Parsed {
+                title: "Pay rent".to_string(),
+                date: day(5),
+                time: hour(9),
+                tags: vec!["finance".to_string()],
+                priority: Some("high".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn repeated_tags_are_kept_once() {
+        let parsed = parse("Groceries #Errands #errands #home", now());
+        assert_eq!(parsed.title, "Groceries");
+        assert_eq!(parsed.tags, vec!["Errands", "home"]);
+    }
+
+    #[test]
+    fn bare_times_mean_their_next_occurrence() {
+        let parsed = parse("Call the bank at 9am", now());
+        assert_eq!(parsed.title, "Call the bank");
+        assert_eq!((parsed.date, parsed.time), (day(5), hour(9)));
+
+        let parsed = parse("Call the bank at 11am", now());
+        assert_eq!((parsed.date, parsed.time), (day(4), hour(11)));
+    }
+
+    #[test]
+    fn relative_dates_count_days_and_weeks() {
+        let parsed = parse("Review the draft in 2 weeks", now());
+        assert_eq!(parsed.title, "Review the draft");
+        assert_eq!(parsed.date, day(18));
+        assert_eq!(parse("Water plants in 3 days", now()).date, day(7));
+    }
+
+    #[test]
+    fn out_of_range_counts_stay_in_the_title() {
+        for text in [
+            "Read in 99999999999 days",
+            "Read in 20000000 weeks",
+            "Read in 9999999999999999999 weeks",
+            "Read in 2 fortnights",
+        ] {
+            let parsed = parse(text, now());
+            assert_eq!(parsed.title, text);
+            assert_eq!(parsed.date, None);
+        }
+    }
+
+    #[test]
+    fn short_weekday_words_stay_in_the_title() {
+        let parsed = parse("Put on sun screen", now());
+        assert_eq!(parsed.title, "Put on sun screen");
+        assert_eq!(parsed.date, None);
+
+        let parsed = parse("Brunch on sunday", now());
+        assert_eq!(parsed.title, "Brunch");
+        assert_eq!(parsed.date, day(8));
+        assert_eq!(parse("Standup wednesday", now()).date, day(4));
+    }
+
+    #[test]
+    fn times_need_a_clock_format() {
+        assert_eq!(time("12am"), hour(0));
+        assert_eq!(time("12pm"), hour(12));
+        assert_eq!(time("9:30pm"), NaiveTime::from_hms_opt(21, 30, 0));
+        assert_eq!(time("21:00"), hour(21));
+        assert_eq!(time("noon"), hour(12));
+        for word in ["9", "13pm", "0am", "9:3", "25:00"] {
+            assert_eq!(time(word), None, "{word}");
+        }
+    }
+}
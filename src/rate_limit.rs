@@ -0,0 +1,172 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use tonic::body::BoxBody;
+use tonic::codegen::http::{Request, Response};
+use tonic::codegen::BoxFuture;
+use tonic::transport::server::{TcpConnectInfo, TlsConnectInfo};
+use tonic::transport::Body;
+use tonic::Status;
+use tower::{Layer, Service};
+
+use crate::config::{Rate, RateLimitConfig};
+
+/// Methods that are never limited, so the host can always tell whether the
+/// plugin is alive.
+const EXEMPT_PREFIX: &str = "/grpc.health.v1.Health/";
+
+/// How often buckets that went idle are dropped.
+const EVICT_PERIOD: Duration = Duration::from_secs(60);
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl Bucket {
+    fn new(rate: &Rate) -> Self {
+        Self {
+            tokens: rate.burst as f64,
+            refilled_at: Instant::now(),
+        }
+    }
+
+    fn take(&mut self, rate: &Rate) -> bool {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate.requests_per_second).min(rate.burst as f64);
+        self.refilled_at = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Whether the bucket refilled completely by `now`, in which case it's
+    /// no different from a new one.
+    fn is_idle(&self, rate: &Rate, now: Instant) -> bool {
+        let elapsed = now.duration_since(self.refilled_at).as_secs_f64();
+        self.tokens + elapsed * rate.requests_per_second >= rate.burst as f64
+    }
+}
+
+type Buckets = HashMap<(Option<IpAddr>, String), Bucket>;
+
+/// Token buckets per peer and method, and when idle ones were last dropped.
+#[derive(Debug)]
+struct Limiter {
+    config: RateLimitConfig,
+    buckets: Mutex<(Buckets, Instant)>,
+}
+
+impl Limiter {
+    fn rate(&self, method: &str) -> &Rate {
+        self.config
+            .methods
+            .get(method)
+            .unwrap_or(&self.config.default)
+    }
+
+    fn allow(&self, peer: Option<IpAddr>, method: &str) -> bool {
+        if method.starts_with(EXEMPT_PREFIX) {
+            return true;
+        }
+        let rate = self.rate(method);
+        let mut guard = self.buckets.lock().unwrap();
+        let (buckets, evicted_at) = &mut *guard;
+        // Peers come and go, and any path gets a bucket, so full buckets
+        // are dropped to keep the map from growing without bound.
+        let now = Instant::now();
+        if now.duration_since(*evicted_at) >= EVICT_PERIOD {
+            buckets.retain(|(_, method), bucket| !bucket.is_idle(self.rate(method), now));
+            *evicted_at = now;
+        }
+        buckets
+            .entry((peer, method.to_string()))
+            .or_insert_with(|| Bucket::new(rate))
+            .take(rate)
+    }
+}
+
+/// Rejects requests with `RESOURCE_EXHAUSTED` once a peer goes over the
+/// configured rate for a method. Peers on a Unix socket share one bucket.
+#[derive(Debug, Clone)]
+pub struct RateLimitLayer {
+    limiter: Option<Arc<Limiter>>,
+}
+
+impl RateLimitLayer {
+    pub fn new(config: Option<RateLimitConfig>) -> Self {
+        Self {
+            limiter: config.map(|config| {
+                Arc::new(Limiter {
+                    config,
+                    buckets: Mutex::new((HashMap::new(), Instant::now())),
+                })
+            }),
+        }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: Option<Arc<Limiter>>,
+}
+
+impl<S> Service<Request<Body>> for RateLimit<S>
+where
+    S: Service<Request<Body>, Response = Response<BoxBody>> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        if let Some(limiter) = &self.limiter {
+            let extensions = request.extensions();
+            let peer = extensions
+                .get::<TcpConnectInfo>()
+                .or_else(|| {
+                    extensions
+                        .get::<TlsConnectInfo<TcpConnectInfo>>()
+                        .map(|info| info.get_ref())
+                })
+                .and_then(|info| info.remote_addr())
+                .map(|addr| addr.ip());
+            let method = request.uri().path();
+            if !limiter.allow(peer, method) {
+                tracing::warn!("Rate limit exceeded for {method}");
+                let response = Status::resource_exhausted("Too many requests").to_http();
+                return Box::pin(async move { Ok(response) });
+            }
+        }
+
+        // The clone might not be ready, keep the instance that was polled.
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        Box::pin(inner.call(request))
+    }
+}
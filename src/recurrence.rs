@@ -0,0 +1,631 @@
+use std::fmt;
+use std::str::FromStr;
+
+use anyhow::{bail, Context, Result};
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, TimeZone, Utc, Weekday};
+use chrono_tz::Tz;
+use diesel::{ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::models::QueryableTask;
+use crate::ordering::next_task_position;
+use crate::schema::tasks;
+
+/// Most periods looked at before giving up on a rule that never matches,
+/// such as the 30th of every February.
+const MAX_PERIODS: i64 = 10_000;
+
+/// Largest `INTERVAL` accepted, which keeps every period of the series
+/// within the dates chrono can represent.
+pub const MAX_INTERVAL: u32 = 1_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frequency {
+    Daily,
+    Weekly,
+    Monthly,
+    Yearly,
+}
+
+/// A recurrence rule as defined by RFC 5545, limited to the parts task apps
+/// use: `FREQ`, `INTERVAL`, `COUNT`, `UNTIL`, `BYDAY` without ordinals and
+/// `BYMONTHDAY`.
+///
+/// The series starts at the task's due date, which always counts as its
+/// first occurrence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub frequency: Frequency,
+    pub interval: u32,
+    pub count: Option<u32>,
+    pub until: Option<NaiveDateTime>,
+    pub by_day: Vec<Weekday>,
+    /// Days of the month, negative ones count from the end.
+    pub by_month_day: Vec<i32>,
+}
+
+impl Rule {
+    /// Up to `limit` occurrences of the series starting at `start` that come
+    /// after `after`, in order.
+    pub fn occurrences_after(
+        &self,
+        start: NaiveDateTime,
+        after: NaiveDateTime,
+        limit: usize,
+    ) -> Vec<NaiveDateTime> {
+        let mut found = vec![];
+        if limit == 0 {
+            return found;
+        }
+        let mut emitted = 1;
+        if start > after {
+            found.push(start);
+            if found.len() >= limit {
+                return found;
+            }
+        }
+        for period in 0..MAX_PERIODS {
+            // Series that run past the last representable date end there.
+            let Some(candidates) = self.candidates(start, period) else {
+                return found;
+            };
+            for candidate in candidates {
+                if candidate <= start {
+                    continue;
+                }
+                if self.until.is_some_and(|until| candidate > until)
+                    || self.count.is_some_and(|count| emitted >= count)
+                {
+                    return found;
+                }
+                emitted += 1;
+                if candidate > after {
+                    found.push(candidate);
+                }
+                if found.len() >= limit {
+                    return found;
+                }
+            }
+        }
+        found
+    }
+
+    /// Same as `occurrences_after` for UTC instants, with the series laid out
+    /// on the wall clock of `zone` so weekdays and days of the month are the
+    /// user's and occurrences keep their time across daylight saving changes.
+    pub fn occurrences_after_in(
+        &self,
+        zone: Tz,
+        start: NaiveDateTime,
+        after: NaiveDateTime,
+        limit: usize,
+    ) -> Vec<NaiveDateTime> {
+        self.occurrences_after(local(zone, start), local(zone, after), limit)
+            .into_iter()
+            .map(|occurrence| utc(zone, occurrence))
+            .collect()
+    }
+
+    /// The occurrence right after the UTC instant `start` in `zone` and the
+    /// rule of the series that continues from it, or `None` when the series
+    /// ends at `start`.
+    pub fn following(&self, zone: Tz, start: NaiveDateTime) -> Option<(NaiveDateTime, Rule)> {
+        let next = *self.occurrences_after_in(zone, start, start, 1).first()?;
+        let rule = Rule {
+            count: self.count.map(|count| count - 1),
+            ..self.clone()
+        };
+        Some((next, rule))
+    }
+
+    /// Occurrences the rule allows in the `period`th period after `start`,
+    /// in order, or `None` when the period is out of the representable range.
+    fn candidates(&self, start: NaiveDateTime, period: i64) -> Option<Vec<NaiveDateTime>> {
+        let step = period.checked_mul(self.interval as i64)?;
+        let date = start.date();
+        let mut days: Vec<NaiveDate> = match self.frequency {
+            Frequency::Daily => vec![date.checked_add_signed(Duration::days(step))?],
+            Frequency::Weekly => {
+                let monday = date
+                    .checked_sub_signed(Duration::days(
+                        date.weekday().num_days_from_monday() as i64
+                    ))?
+                    .checked_add_signed(Duration::weeks(step))?;
+                let mut weekdays = if self.by_day.is_empty() {
+                    vec![date.weekday()]
+                } else {
+                    self.by_day.clone()
+                };
+                weekdays.sort_by_key(|weekday| weekday.num_days_from_monday());
+                weekdays
+                    .into_iter()
+                    .map(|weekday| {
+                        monday.checked_add_signed(Duration::days(
+                            weekday.num_days_from_monday() as i64
+                        ))
+                    })
+                    .collect::<Option<_>>()?
+            }
+            Frequency::Monthly => {
+                let month = (date.year() as i64 * 12 + date.month0() as i64).checked_add(step)?;
+                let (year, month) = (i32::try_from(month / 12).ok()?, (month % 12) as u32 + 1);
+                if !self.by_month_day.is_empty() {
+                    self.by_month_day
+                        .iter()
+                        .filter_map(|day| month_day(year, month, *day))
+                        .collect()
+                } else if !self.by_day.is_empty() {
+                    (1..=days_in_month(year, month))
+                        .filter_map(|day| NaiveDate::from_ymd_opt(year, month, day))
+                        .collect()
+                } else {
+                    NaiveDate::from_ymd_opt(year, month, date.day())
+                        .into_iter()
+                        .collect()
+                }
+            }
+            Frequency::Yearly => {
+                let year = date.year().checked_add(i32::try_from(step).ok()?)?;
+                NaiveDate::from_ymd_opt(year, date.month(), date.day())
+                    .into_iter()
+                    .collect()
+            }
+        };
+        days.retain(|day| {
+            (self.by_day.is_empty() || self.by_day.contains(&day.weekday()))
+                && (self.by_month_day.is_empty()
+                    || self
+                        .by_month_day
+                        .iter()
+                        .any(|by| month_day(day.year(), day.month(), *by) == Some(*day)))
+        });
+        days.sort();
+        days.dedup();
+        Some(
+            days.into_iter()
+                .map(|day| day.and_time(start.time()))
+                .collect(),
+        )
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let (next_year, next_month) = if month == 12 {
+        (year + 1, 1)
+    } else {
+        (year, month + 1)
+    };
+    NaiveDate::from_ymd_opt(next_year, next_month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+/// Resolves a `BYMONTHDAY` value, `None` when the month is too short.
+fn month_day(year: i32, month: u32, day: i32) -> Option<NaiveDate> {
+    let length = days_in_month(year, month) as i32;
+    let day = if day < 0 { length + day + 1 } else { day };
+    if day < 1 || day > length {
+        return None;
+    }
+    NaiveDate::from_ymd_opt(year, month, day as u32)
+}
+
+fn parse_weekday(value: &str) -> Result<Weekday> {
+    Ok(match value {
+        "MO" => Weekday::Mon,
+        "TU" => Weekday::Tue,
+        "WE" => Weekday::Wed,
+        "TH" => Weekday::Thu,
+        "FR" => Weekday::Fri,
+        "SA" => Weekday::Sat,
+        "SU" => Weekday::Sun,
+        _ if value.len() > 2 => bail!("BYDAY ordinals like '{value}' aren't supported"),
+        _ => bail!("'{value}' isn't a weekday"),
+    })
+}
+
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+/// Dates without a time end at the last second of that day.
+fn parse_until(value: &str) -> Result<NaiveDateTime> {
+    let value = value.trim_end_matches('Z');
+    if let Ok(until) = NaiveDateTime::parse_from_str(value, "%Y%m%dT%H%M%S") {
+        return Ok(until);
+    }
+    NaiveDate::parse_from_str(value, "%Y%m%d")
+        .ok()
+        .and_then(|date| date.and_hms_opt(23, 59, 59))
+        .with_context(|| format!("UNTIL '{value}' isn't a date"))
+}
+
+impl FromStr for Rule {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let value = value.trim();
+        let value = value.strip_prefix("RRULE:").unwrap_or(value);
+        let mut frequency = None;
+        let mut rule = Rule {
+            frequency: Frequency::Daily,
+            interval: 1,
+            count: None,
+            until: None,
+            by_day: vec![],
+            by_month_day: vec![],
+        };
+
+        for part in value.split(';').filter(|part| !part.is_empty()) {
+            let (key, value) = part
+                .split_once('=')
+                .with_context(|| format!("'{part}' isn't a KEY=VALUE pair"))?;
+            match key.to_uppercase().as_str() {
+                "FREQ" => {
+                    frequency = Some(match value.to_uppercase().as_str() {
+                        "DAILY" => Frequency::Daily,
+                        "WEEKLY" => Frequency::Weekly,
+                        "MONTHLY" => Frequency::Monthly,
+                        "YEARLY" => Frequency::Yearly,
+                        _ => bail!("FREQ={value} isn't supported"),
+                    })
+                }
+                "INTERVAL" => {
+                    rule.interval = value.parse().context("INTERVAL must be a number")?;
+                    if !(1..=MAX_INTERVAL).contains(&rule.interval) {
+                        bail!("INTERVAL must be between 1 and {MAX_INTERVAL}");
+                    }
+                }
+                "COUNT" => {
+                    let count: u32 = value.parse().context("COUNT must be a number")?;
+                    if count == 0 {
+                        bail!("COUNT must be at least 1");
+                    }
+                    rule.count = Some(count);
+                }
+                "UNTIL" => rule.until = Some(parse_until(value)?),
+                "BYDAY" => {
+                    rule.by_day = value
+                        .split(',')
+                        .map(|day| parse_weekday(&day.to_uppercase()))
+                        .collect::<Result<_>>()?
+                }
+                "BYMONTHDAY" => {
+                    rule.by_month_day = value
+                        .split(',')
+                        .map(|day| match day.parse::<i32>() {
+                            Ok(day) if day != 0 && (-31..=31).contains(&day) => Ok(day),
+                            _ => Err(anyhow::anyhow!("BYMONTHDAY '{day}' isn't a day")),
+                        })
+                        .collect::<Result<_>>()?
+                }
+                // Weeks always start on Monday here.
+                "WKST" if value.eq_ignore_ascii_case("MO") => {}
+                other => bail!("{other} isn't supported"),
+            }
+        }
+
+        rule.frequency = frequency.context("FREQ is required")?;
+        if rule.count.is_some() && rule.until.is_some() {
+            bail!("COUNT and UNTIL can't be used together");
+        }
+        if rule.frequency == Frequency::Weekly && !rule.by_month_day.is_empty() {
+            bail!("BYMONTHDAY can't be used with FREQ=WEEKLY");
+        }
+        if rule.frequency == Frequency::Yearly
+            && !(rule.by_day.is_empty() && rule.by_month_day.is_empty())
+        {
+            bail!("BYDAY and BYMONTHDAY aren't supported with FREQ=YEARLY");
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let frequency = match self.frequency {
+            Frequency::Daily => "DAILY",
+            Frequency::Weekly => "WEEKLY",
+            Frequency::Monthly => "MONTHLY",
+            Frequency::Yearly => "YEARLY",
+        };
+        write!(f, "FREQ={frequency}")?;
+        if self.interval != 1 {
+            write!(f, ";INTERVAL={}", self.interval)?;
+        }
+        if let Some(count) = self.count {
+            write!(f, ";COUNT={count}")?;
+        }
+        if let Some(until) = self.until {
+            write!(f, ";UNTIL={}", until.format("%Y%m%dT%H%M%SZ"))?;
+        }
+        if !self.by_day.is_empty() {
+            let days: Vec<&str> = self.by_day.iter().map(|day| weekday_code(*day)).collect();
+            write!(f, ";BYDAY={}", days.join(","))?;
+        }
+        if !self.by_month_day.is_empty() {
+            let days: Vec<String> = self.by_month_day.iter().map(i32::to_string).collect();
+            write!(f, ";BYMONTHDAY={}", days.join(","))?;
+        }
+        Ok(())
+    }
+}
+
+fn local(zone: Tz, instant: NaiveDateTime) -> NaiveDateTime {
+    Utc.from_utc_datetime(&instant)
+        .with_timezone(&zone)
+        .naive_local()
+}
+
+/// The UTC instant of `local` in `zone`. Times skipped when the clocks go
+/// forward move on to the first local time that exists after the gap.
+fn utc(zone: Tz, local: NaiveDateTime) -> NaiveDateTime {
+    // Gaps fall on whole minutes and never last a day.
+    (0..=24 * 60)
+        .filter_map(|minutes| local.checked_add_signed(Duration::minutes(minutes)))
+        .find_map(|candidate| zone.from_local_datetime(&candidate).earliest())
+        .map(|instant| instant.naive_utc())
+        .unwrap_or(local)
+}
+
+/// The zone the series of `task` is laid out in. Tasks from before the
+/// timezone was recorded repeat in UTC, as they always did.
+pub fn zone(task: &QueryableTask) -> Tz {
+    task.zone().unwrap_or(Tz::UTC)
+}
+
+/// Where the series of `task` currently stands: its due date, or when it
+/// was completed for tasks without one.
+pub fn anchor(task: &QueryableTask) -> NaiveDateTime {
+    task.due_date
+        .or(task.completed_on)
+        .unwrap_or_else(|| Utc::now().naive_utc())
+}
+
+/// Moves `date` by `by`, `None` when that leaves the representable range.
+fn shift(date: Option<NaiveDateTime>, by: Duration) -> Option<Option<NaiveDateTime>> {
+    match date {
+        Some(date) => date.checked_add_signed(by).map(Some),
+        None => Some(None),
+    }
+}
+
+/// Creates the instance that follows a completed `task` in its series and
+/// returns it, or `None` when the task doesn't repeat or its series ended.
+/// The reminder and start date keep their distance to the due date, and
+/// series whose dates would leave the representable range end.
+///
+/// The rule is handed over to the new instance, so completing `task` again
+/// doesn't spawn another one.
+pub fn spawn_next(
+    connection: &mut SqliteConnection,
+    task: &QueryableTask,
+) -> Result<Option<QueryableTask>> {
+    let rule: Rule = match &task.recurrence_rule {
+        Some(rule) => rule.parse()?,
+        None => return Ok(None),
+    };
+    let start = anchor(task);
+    let (due, rule) = match rule.following(zone(task), start) {
+        Some(following) => following,
+        None => return Ok(None),
+    };
+
+    let (Some(reminder_date), Some(start_date)) = (
+        shift(task.reminder_date, due - start),
+        shift(task.start_date, due - start),
+    ) else {
+        return Ok(None);
+    };
+
+    let mut next = task.duplicate();
+    next.due_date = Some(due);
+    next.reminder_date = reminder_date;
+    next.start_date = start_date;
+    next.recurrence_rule = Some(rule.to_string());
+    next.position = next_task_position(connection, &task.parent_list)?;
+    diesel::insert_into(tasks::table)
+        .values(&next)
+        .execute(connection)
+        .context("Failed to create the next occurrence.")?;
+    diesel::update(tasks::table.find(&task.id_task))
+        .set(tasks::recurrence_rule.eq(None::<String>))
+        .execute(connection)?;
+    Ok(Some(next))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(value: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M").unwrap()
+    }
+
+    fn rule(value: &str) -> Rule {
+        value.parse().unwrap()
+    }
+
+    /// Every occurrence of the series starting at `start`, the first included.
+    fn series(value: &str, start: &str, limit: usize) -> Vec<NaiveDateTime> {
+        let start = at(start);
+        rule(value).occurrences_after(start, start - Duration::seconds(1), limit)
+    }
+
+    #[test]
+    fn count_includes_the_first_occurrence() {
+        let occurrences = series("FREQ=DAILY;COUNT=3", "2023-01-01 09:00", 10);
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2023-01-01 09:00"),
+                at("2023-01-02 09:00"),
+                at("2023-01-03 09:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn until_a_date_covers_that_whole_day() {
+        let occurrences = series(
+            "FREQ=DAILY;INTERVAL=2;UNTIL=20230105",
+            "2023-01-01 09:00",
+            10,
+        );
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2023-01-01 09:00"),
+                at("2023-01-03 09:00"),
+                at("2023-01-05 09:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn negative_month_days_count_from_the_end() {
+        let occurrences = series("FREQ=MONTHLY;BYMONTHDAY=-1", "2023-01-31 09:00", 3);
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2023-01-31 09:00"),
+                at("2023-02-28 09:00"),
+                at("2023-03-31 09:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn months_without_the_day_are_skipped() {
+        let occurrences = series("FREQ=MONTHLY;BYMONTHDAY=30", "2023-01-30 09:00", 3);
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2023-01-30 09:00"),
+                at("2023-03-30 09:00"),
+                at("2023-04-30 09:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn rules_that_never_match_end() {
+        let occurrences = series(
+            "FREQ=MONTHLY;INTERVAL=12;BYMONTHDAY=30",
+            "2023-02-28 09:00",
+            3,
+        );
+        assert_eq!(occurrences, vec![at("2023-02-28 09:00")]);
+    }
+
+    #[test]
+    fn leap_days_repeat_in_leap_years() {
+        let occurrences = series("FREQ=YEARLY", "2024-02-29 09:00", 2);
+        assert_eq!(
+            occurrences,
+            vec![at("2024-02-29 09:00"), at("2028-02-29 09:00")]
+        );
+    }
+
+    #[test]
+    fn weekly_days_follow_in_order() {
+        let occurrences = series("FREQ=WEEKLY;BYDAY=FR,MO", "2023-01-02 09:00", 4);
+        assert_eq!(
+            occurrences,
+            vec![
+                at("2023-01-02 09:00"),
+                at("2023-01-06 09:00"),
+                at("2023-01-09 09:00"),
+                at("2023-01-13 09:00"),
+            ]
+        );
+    }
+
+    #[test]
+    fn series_end_at_the_last_representable_date() {
+        let start = NaiveDate::MAX.and_hms_opt(9, 0, 0).unwrap() - Duration::days(400);
+        for value in [
+            "FREQ=DAILY;INTERVAL=1000",
+            "FREQ=WEEKLY;INTERVAL=1000",
+            "FREQ=MONTHLY;INTERVAL=1000",
+            "FREQ=YEARLY;INTERVAL=1000",
+        ] {
+            assert!(rule(value).occurrences_after(start, start, 5).is_empty());
+        }
+    }
+
+    #[test]
+    fn following_hands_over_the_remaining_count() {
+        let (next, rest) = rule("FREQ=DAILY;COUNT=2")
+            .following(Tz::UTC, at("2023-01-01 09:00"))
+            .unwrap();
+        assert_eq!(next, at("2023-01-02 09:00"));
+        assert_eq!(rest.count, Some(1));
+        assert!(rest.following(Tz::UTC, next).is_none());
+    }
+
+    #[test]
+    fn local_times_are_kept_across_daylight_saving_changes() {
+        let zone: Tz = "Europe/Berlin".parse().unwrap();
+        // 9:00 in Berlin, the day before the clocks go forward.
+        let occurrences = rule("FREQ=DAILY").occurrences_after_in(
+            zone,
+            at("2023-03-25 08:00"),
+            at("2023-03-25 08:00"),
+            1,
+        );
+        assert_eq!(occurrences, vec![at("2023-03-26 07:00")]);
+    }
+
+    #[test]
+    fn skipped_local_times_move_past_the_gap() {
+        let zone: Tz = "Europe/Berlin".parse().unwrap();
+        assert_eq!(utc(zone, at("2023-03-26 02:30")), at("2023-03-26 01:00"));
+        // Repeated times resolve to the earlier instant.
+        assert_eq!(utc(zone, at("2023-10-29 02:30")), at("2023-10-29 00:30"));
+    }
+
+    #[test]
+    fn rules_survive_a_round_trip() {
+        for value in [
+            "FREQ=DAILY",
+            "FREQ=WEEKLY;INTERVAL=2;COUNT=5;BYDAY=MO,WE",
+            "FREQ=MONTHLY;UNTIL=20230105T235959Z;BYMONTHDAY=1,-1",
+            "FREQ=YEARLY;INTERVAL=1000",
+        ] {
+            assert_eq!(rule(value).to_string(), value);
+            assert_eq!(rule(&rule(value).to_string()), rule(value));
+        }
+        assert_eq!(
+            rule("RRULE:freq=weekly;byday=mo;wkst=MO").to_string(),
+            "FREQ=WEEKLY;BYDAY=MO"
+        );
+    }
+
+    #[test]
+    fn unsupported_rules_are_refused() {
+        for value in [
+            "",
+            "INTERVAL=2",
+            "FREQ=HOURLY",
+            "FREQ=DAILY;INTERVAL=0",
+            "FREQ=DAILY;INTERVAL=1001",
+            "FREQ=DAILY;COUNT=0",
+            "FREQ=DAILY;COUNT=2;UNTIL=20230105",
+            "FREQ=MONTHLY;BYDAY=1MO",
+            "FREQ=MONTHLY;BYMONTHDAY=0",
+            "FREQ=MONTHLY;BYMONTHDAY=32",
+            "FREQ=WEEKLY;BYMONTHDAY=1",
+            "FREQ=YEARLY;BYDAY=MO",
+        ] {
+            assert!(value.parse::<Rule>().is_err(), "{value}");
+        }
+    }
+}
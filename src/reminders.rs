@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::{ChangeKind, TaskStatus};
+
+use crate::audit;
+use crate::completion;
+use crate::database::{establish_connection, retry_busy};
+use crate::events::Changes;
+use crate::lifecycle::Lifecycle;
+use crate::models::{QueryableTask, TASK_ENTITY};
+use crate::schema::tasks;
+use crate::settings;
+
+const REMINDER_PERIOD: Duration = Duration::from_secs(30);
+
+/// When reminders were last looked at, so the ones that came due while the
+/// server wasn't running still fire once it starts.
+const CHECKED_UNTIL_KEY: &str = "reminders_checked_until";
+
+/// Tasks still open whose reminder came due after `after` and at or before
+/// `until`.
+pub fn due_reminders(
+    connection: &mut SqliteConnection,
+    after: NaiveDateTime,
+    until: NaiveDateTime,
+) -> Result<Vec<QueryableTask>> {
+    let due = tasks::table
+        .filter(tasks::is_reminder_on.eq(true))
+        .filter(tasks::status.ne(TaskStatus::Completed as i32))
+        .filter(tasks::archived.eq(false))
+        .filter(tasks::reminder_date.gt(after))
+        .filter(tasks::reminder_date.le(until))
+        .order(tasks::reminder_date.asc())
+        .load(connection)?;
+    Ok(due)
+}
+
+/// Reminds about `task` again `duration` from now and records the snooze in
+/// the audit trail.
+pub fn snooze(
+    connection: &mut SqliteConnection,
+    task: &str,
+    duration: chrono::Duration,
+) -> Result<QueryableTask> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let now = Utc::now().naive_utc();
+        let updated = diesel::update(tasks::table.find(task))
+            .set((
+                tasks::is_reminder_on.eq(true),
+                tasks::reminder_date.eq(now + duration),
+                tasks::last_modified_date_time.eq(now),
+            ))
+            .execute(connection)?;
+        if updated == 0 {
+            bail!("The task doesn't exist.");
+        }
+        audit::record(
+            connection,
+            TASK_ENTITY,
+            task,
+            audit::REMINDER_SNOOZED,
+            Some(format!("{} seconds", duration.num_seconds())),
+        )?;
+        let snoozed = tasks::table.find(task).first(connection)?;
+        Ok(snoozed)
+    })
+}
+
+/// Completes `task` from its reminder and returns it along with the next
+/// occurrence when it repeats.
+#[cfg_attr(not(feature = "notify"), allow(dead_code))]
+pub fn complete(
+    connection: &mut SqliteConnection,
+    task: &str,
+) -> Result<(QueryableTask, Option<QueryableTask>)> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let now = Utc::now().naive_utc();
+        diesel::update(tasks::table.find(task))
+            .set((
+                tasks::status.eq(TaskStatus::Completed as i32),
+                tasks::completed_on.eq(now),
+                tasks::last_modified_date_time.eq(now),
+            ))
+            .execute(connection)?;
+        let completed: QueryableTask = tasks::table
+            .find(task)
+            .first(connection)
+            .context("The task doesn't exist.")?;
+        let next = completion::completed(connection, &completed)?;
+        Ok((completed, next))
+    })
+}
+
+/// Returns the reminders that came due since the last check and moves the
+/// checkpoint to now.
+fn fire(connection: &mut SqliteConnection) -> Result<Vec<QueryableTask>> {
+    let now = Utc::now().naive_utc();
+    let checked_until = settings::get(connection, CHECKED_UNTIL_KEY)?
+        .and_then(|value| value.parse().ok())
+        .and_then(|value| NaiveDateTime::from_timestamp_opt(value, 0))
+        .unwrap_or(now);
+    let due = due_reminders(connection, checked_until, now)?;
+    settings::set(connection, CHECKED_UNTIL_KEY, &now.timestamp().to_string())?;
+    Ok(due)
+}
+
+/// Publishes a `ReminderFired` event for every reminder as it comes due,
+/// skipping checks while the provider is paused. Reminders that come due
+/// during a pause fire once it's resumed.
+pub async fn schedule(changes: Changes, lifecycle: Lifecycle) -> Result<()> {
+    let mut interval = tokio::time::interval(REMINDER_PERIOD);
+    loop {
+        interval.tick().await;
+        if lifecycle.is_paused() {
+            continue;
+        }
+        let due = tokio::task::spawn_blocking(|| retry_busy(|| fire(&mut establish_connection()?)));
+        match due.await {
+            Ok(Ok(due)) => {
+                for task in due {
+                    tracing::info!("Reminder fired for task {}.", task.id_task);
+                    #[cfg(feature = "notify")]
+                    crate::notifications::show(task.clone(), changes.clone()).await;
+                    changes.task(ChangeKind::ReminderFired, task.into());
+                }
+            }
+            Ok(Err(err)) => tracing::error!("Failed to check reminders: {err}"),
+            Err(err) => tracing::error!("The reminder check stopped unexpectedly: {err}"),
+        }
+    }
+}
@@ -0,0 +1,172 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use diesel::connection::SimpleConnection;
+use diesel::RunQueryDsl;
+use object_store::aws::AmazonS3Builder;
+use object_store::local::LocalFileSystem;
+use object_store::path::Path;
+use object_store::ObjectStore;
+
+use crate::config::ReplicationConfig;
+use crate::database::{database_url, establish_connection, retry_busy, DATABASE_NAME};
+use crate::lifecycle::Lifecycle;
+
+pub type SharedReplicationState = Arc<Mutex<ReplicationState>>;
+
+#[derive(Debug, Default, Clone)]
+pub struct ReplicationState {
+    pub enabled: bool,
+    pub target: String,
+    pub last_replicated_at: Option<NaiveDateTime>,
+    pub replicated_bytes: u64,
+    pub last_error: Option<String>,
+}
+
+/// Keeps a standby copy of the database up to date on a second location.
+///
+/// The database runs in WAL mode and, whenever the main file or its WAL
+/// changed since the last pass, a consistent snapshot is taken with
+/// `VACUUM INTO` and shipped to the target.
+pub struct Replicator {
+    store: Arc<dyn ObjectStore>,
+    location: Path,
+    interval: Duration,
+    state: SharedReplicationState,
+    lifecycle: Lifecycle,
+}
+
+impl Replicator {
+    pub fn new(
+        config: &ReplicationConfig,
+        state: SharedReplicationState,
+        lifecycle: Lifecycle,
+    ) -> Result<Self> {
+        let (store, target, location): (Arc<dyn ObjectStore>, String, Path) =
+            match (&config.path, &config.s3) {
+                (Some(path), _) => {
+                    std::fs::create_dir_all(path)?;
+                    let store = LocalFileSystem::new_with_prefix(path)?;
+                    (
+                        Arc::new(store),
+                        path.display().to_string(),
+                        Path::from(DATABASE_NAME),
+                    )
+                }
+                (None, Some(s3)) => {
+                    let store = AmazonS3Builder::new()
+                        .with_endpoint(&s3.endpoint)
+                        .with_bucket_name(&s3.bucket)
+                        .with_region(&s3.region)
+                        .with_access_key_id(&s3.access_key_id)
+                        .with_secret_access_key(&s3.secret_access_key)
+                        .with_allow_http(true)
+                        .build()?;
+                    let location = match &s3.prefix {
+                        Some(prefix) => Path::from(format!("{prefix}/{DATABASE_NAME}")),
+                        None => Path::from(DATABASE_NAME),
+                    };
+                    let target = format!("{}/{}/{location}", s3.endpoint, s3.bucket);
+                    (Arc::new(store), target, location)
+                }
+                (None, None) => anyhow::bail!("Replication needs either a path or an S3 target"),
+            };
+
+        establish_connection()?.batch_execute("PRAGMA journal_mode = WAL;")?;
+
+        {
+            let mut state = state.lock().unwrap();
+            state.enabled = true;
+            state.target = target;
+        }
+
+        Ok(Self {
+            store,
+            location,
+            interval: Duration::from_secs(config.interval_secs.max(1)),
+            state,
+            lifecycle,
+        })
+    }
+
+    pub async fn run(self) -> Result<()> {
+        let mut interval = tokio::time::interval(self.interval);
+        let mut last_seen = None;
+        loop {
+            interval.tick().await;
+            if self.lifecycle.is_paused() {
+                continue;
+            }
+            let modified = match last_modified() {
+                Ok(modified) => modified,
+                Err(err) => {
+                    self.record_error(err);
+                    continue;
+                }
+            };
+            if last_seen == Some(modified) {
+                continue;
+            }
+            match self.replicate().await {
+                Ok(bytes) => {
+                    last_seen = Some(modified);
+                    let mut state = self.state.lock().unwrap();
+                    state.last_replicated_at = Some(Utc::now().naive_utc());
+                    state.replicated_bytes += bytes;
+                    state.last_error = None;
+                }
+                Err(err) => self.record_error(err),
+            }
+        }
+    }
+
+    async fn replicate(&self) -> Result<u64> {
+        let bytes = tokio::task::spawn_blocking(|| retry_busy(snapshot)).await??;
+        let size = bytes.len() as u64;
+        self.store
+            .put(&self.location, bytes.into())
+            .await
+            .context("Failed to upload the database snapshot")?;
+        Ok(size)
+    }
+
+    fn record_error(&self, err: anyhow::Error) {
+        tracing::error!("Replication failed: {err}");
+        self.state.lock().unwrap().last_error = Some(err.to_string());
+    }
+}
+
+/// Copies the database into a consistent snapshot and returns its contents.
+fn snapshot() -> Result<Vec<u8>> {
+    let snapshot = snapshot_path()?;
+    if snapshot.exists() {
+        std::fs::remove_file(&snapshot)?;
+    }
+    let target = snapshot
+        .to_str()
+        .context("Failed to convert path to string")?
+        .replace('\'', "''");
+    diesel::sql_query(format!("VACUUM INTO '{target}'"))
+        .execute(&mut establish_connection()?)
+        .context("Failed to snapshot the database")?;
+
+    let bytes = std::fs::read(&snapshot)?;
+    std::fs::remove_file(&snapshot)?;
+    Ok(bytes)
+}
+
+fn snapshot_path() -> Result<PathBuf> {
+    Ok(PathBuf::from(format!("{}.snapshot", database_url()?)))
+}
+
+fn last_modified() -> Result<(SystemTime, Option<SystemTime>)> {
+    let database = database_url()?;
+    let main = std::fs::metadata(&database)?.modified()?;
+    let wal = std::fs::metadata(format!("{database}-wal"))
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    Ok((main, wal))
+}
@@ -1,5 +1,78 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    attachments (id_attachment) {
+        id_attachment -> Text,
+        id_task -> Text,
+        file_name -> Text,
+        mime_type -> Nullable<Text>,
+        size -> BigInt,
+        hash -> Text,
+        created_date_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    audit_log (id_entry) {
+        id_entry -> Text,
+        entity -> Text,
+        id_entity -> Text,
+        action -> Text,
+        detail -> Nullable<Text>,
+        created_date_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    comments (id_comment) {
+        id_comment -> Text,
+        id_task -> Text,
+        author -> Text,
+        body -> Text,
+        created_date_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    idempotency_keys (key, method) {
+        key -> Text,
+        method -> Text,
+        payload_hash -> Nullable<Text>,
+        id_entity -> Text,
+        created -> Timestamp,
+    }
+}
+
+diesel::table! {
+    list_groups (id_group) {
+        id_group -> Text,
+        name -> Text,
+        id_parent -> Nullable<Text>,
+        position -> BigInt,
+    }
+}
+
+diesel::table! {
+    list_template_tasks (id_list_template, position) {
+        id_list_template -> Text,
+        position -> BigInt,
+        id_template -> Text,
+    }
+}
+
+diesel::table! {
+    list_templates (id_list_template) {
+        id_list_template -> Text,
+        name -> Text,
+        list_name -> Text,
+        icon_name -> Nullable<Text>,
+        provider -> Text,
+        color -> Nullable<Text>,
+        description -> Nullable<Text>,
+        created_date_time -> Timestamp,
+    }
+}
+
 diesel::table! {
     lists (id_list) {
         id_list -> Text,
@@ -7,6 +80,99 @@ diesel::table! {
         is_owner -> Bool,
         icon_name -> Nullable<Text>,
         provider -> Text,
+        position -> BigInt,
+        archived -> Bool,
+        pinned -> Bool,
+        id_group -> Nullable<Text>,
+        color -> Nullable<Text>,
+        description -> Nullable<Text>,
+        protected -> Bool,
+    }
+}
+
+diesel::table! {
+    people (id_person) {
+        id_person -> Text,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    pomodoros (id_pomodoro) {
+        id_pomodoro -> Text,
+        id_task -> Text,
+        length -> Integer,
+        started_at -> Timestamp,
+        completed_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    priorities (level) {
+        level -> Integer,
+        name -> Text,
+        color -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    recent_items (id_item) {
+        id_item -> Text,
+        kind -> Text,
+        visits -> Integer,
+        score -> Double,
+        last_visited -> Timestamp,
+    }
+}
+
+diesel::table! {
+    settings (key) {
+        key -> Text,
+        value -> Text,
+    }
+}
+
+diesel::table! {
+    statuses (id_status) {
+        id_status -> Text,
+        parent_list -> Text,
+        name -> Text,
+        position -> BigInt,
+        done -> Bool,
+        color -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    subtasks (id_subtask) {
+        id_subtask -> Text,
+        id_task -> Text,
+        title -> Text,
+        completed -> Bool,
+        position -> BigInt,
+        created_date_time -> Timestamp,
+        last_modified_date_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    tags (id_tag) {
+        id_tag -> Text,
+        name -> Text,
+    }
+}
+
+diesel::table! {
+    task_dependencies (id_task, id_blocker) {
+        id_task -> Text,
+        id_blocker -> Text,
+    }
+}
+
+diesel::table! {
+    task_tags (id_task, id_tag) {
+        id_task -> Text,
+        id_tag -> Text,
     }
 }
 
@@ -25,7 +191,95 @@ diesel::table! {
         reminder_date -> Nullable<Timestamp>,
         created_date_time -> Timestamp,
         last_modified_date_time -> Timestamp,
+        position -> BigInt,
+        notes_format -> Integer,
+        recurrence_rule -> Nullable<Text>,
+        estimate_minutes -> Nullable<Integer>,
+        color -> Nullable<Text>,
+        emoji -> Nullable<Text>,
+        url -> Nullable<Text>,
+        latitude -> Nullable<Double>,
+        longitude -> Nullable<Double>,
+        place_name -> Nullable<Text>,
+        id_status -> Nullable<Text>,
+        pinned -> Bool,
+        start_date -> Nullable<Timestamp>,
+        duration_minutes -> Nullable<Integer>,
+        timezone -> Nullable<Text>,
+        assigned_to -> Nullable<Text>,
+        archived -> Bool,
+    }
+}
+
+diesel::table! {
+    template_subtasks (id_template, position) {
+        id_template -> Text,
+        position -> BigInt,
+        title -> Text,
+    }
+}
+
+diesel::table! {
+    template_tags (id_template, id_tag) {
+        id_template -> Text,
+        id_tag -> Text,
+    }
+}
+
+diesel::table! {
+    templates (id_template) {
+        id_template -> Text,
+        name -> Text,
+        title -> Text,
+        body -> Nullable<Text>,
+        notes_format -> Integer,
+        importance -> Integer,
+        due_offset -> Nullable<BigInt>,
+        reminder_offset -> Nullable<BigInt>,
+        created_date_time -> Timestamp,
+    }
+}
+
+diesel::table! {
+    time_entries (id_entry) {
+        id_entry -> Text,
+        id_task -> Text,
+        started_at -> Timestamp,
+        stopped_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    tombstones (id_entity) {
+        id_entity -> Text,
+        entity -> Text,
+        deleted_on -> Timestamp,
     }
 }
 
-diesel::allow_tables_to_appear_in_same_query!(lists, tasks,);
+diesel::allow_tables_to_appear_in_same_query!(
+    attachments,
+    audit_log,
+    comments,
+    idempotency_keys,
+    list_groups,
+    list_template_tasks,
+    list_templates,
+    lists,
+    people,
+    pomodoros,
+    priorities,
+    recent_items,
+    settings,
+    statuses,
+    subtasks,
+    tags,
+    task_dependencies,
+    task_tags,
+    tasks,
+    template_subtasks,
+    template_tags,
+    templates,
+    time_entries,
+    tombstones,
+);
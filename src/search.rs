@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+
+use anyhow::{Context, Result};
+use chrono::NaiveDateTime;
+use diesel::connection::SimpleConnection;
+use diesel::dsl::sql;
+use diesel::sql_types::{Bool, Double, Text};
+use diesel::{
+    Connection, ExpressionMethods, QueryDsl, QueryableByName, RunQueryDsl, SqliteConnection,
+};
+
+use crate::models::QueryableTask;
+use crate::schema::tasks;
+
+/// Structured filters applied on top of the text query.
+#[derive(Debug, Default)]
+pub struct SearchFilters {
+    pub list: Option<String>,
+    pub status: Option<i32>,
+    pub due_after: Option<NaiveDateTime>,
+    pub due_before: Option<NaiveDateTime>,
+    pub start_after: Option<NaiveDateTime>,
+    pub start_before: Option<NaiveDateTime>,
+}
+
+/// Number of results per list and per status.
+#[derive(Debug, Default)]
+pub struct Facets {
+    pub lists: HashMap<String, i64>,
+    pub statuses: HashMap<i32, i64>,
+}
+
+impl Facets {
+    pub fn count(results: &[QueryableTask]) -> Self {
+        let mut facets = Facets::default();
+        for task in results {
+            *facets.lists.entry(task.parent_list.clone()).or_default() += 1;
+            *facets.statuses.entry(task.status).or_default() += 1;
+        }
+        facets
+    }
+}
+
+#[derive(QueryableByName)]
+struct TableDefinition {
+    #[diesel(sql_type = Text)]
+    sql: String,
+}
+
+/// Picks the FTS5 tokenizer that works best for the given language.
+///
+/// SQLite only ships a stemmer for English, CJK scripts have no word
+/// separators so they are indexed as trigrams, and every other language
+/// falls back to plain unicode tokenization.
+pub fn tokenizer(language: &str) -> &'static str {
+    let language = language.split(['-', '_']).next().unwrap_or_default();
+    match language.to_lowercase().as_str() {
+        "en" => "porter unicode61 remove_diacritics 2",
+        "zh" | "ja" | "ko" => "trigram",
+        _ => "unicode61 remove_diacritics 2",
+    }
+}
+
+/// Recreates the search index using the tokenizer for `language`.
+pub fn rebuild_index(connection: &mut SqliteConnection, language: &str) -> Result<()> {
+    let tokenizer = tokenizer(language);
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        connection.batch_execute(&format!(
+            "DROP TABLE IF EXISTS tasks_fts;
+             CREATE VIRTUAL TABLE tasks_fts USING fts5
+             (
+                 id_task UNINDEXED,
+                 title,
+                 body,
+                 tokenize = '{tokenizer}'
+             );
+             INSERT INTO tasks_fts (id_task, title, body)
+             SELECT id_task, title, body FROM tasks;"
+        ))?;
+        Ok(())
+    })?;
+    tracing::info!("Search index rebuilt with the '{tokenizer}' tokenizer.");
+    Ok(())
+}
+
+/// Rebuilds the search index only if it was built for another language.
+pub fn ensure_language(connection: &mut SqliteConnection, language: &str) -> Result<()> {
+    let definition: TableDefinition =
+        diesel::sql_query("SELECT sql FROM sqlite_master WHERE name = 'tasks_fts'")
+            .get_result(connection)
+            .context("The search index doesn't exist")?;
+    if !definition
+        .sql
+        .contains(&format!("'{}'", tokenizer(language)))
+    {
+        rebuild_index(connection, language)?;
+    }
+    Ok(())
+}
+
+/// Runs a full-text search over task titles and bodies narrowed down by
+/// `filters`, best matches first. An empty query only applies the filters.
+pub fn search(
+    connection: &mut SqliteConnection,
+    query: &str,
+    filters: &SearchFilters,
+) -> Result<Vec<QueryableTask>> {
+    let expression = match_expression(query);
+    let mut statement = tasks::table.into_boxed();
+
+    if !expression.is_empty() {
+        statement = statement
+            .filter(
+                sql::<Bool>("tasks.id_task IN (SELECT id_task FROM tasks_fts WHERE tasks_fts MATCH ")
+                    .bind::<Text, _>(expression.clone())
+                    .sql(")"),
+            )
+            .order(
+                sql::<Double>(
+                    "(SELECT rank FROM tasks_fts WHERE tasks_fts.id_task = tasks.id_task AND tasks_fts MATCH ",
+                )
+                .bind::<Text, _>(expression)
+                .sql(")"),
+            );
+    }
+    if let Some(list) = &filters.list {
+        statement = statement.filter(tasks::parent_list.eq(list.clone()));
+    }
+    if let Some(status) = filters.status {
+        statement = statement.filter(tasks::status.eq(status));
+    }
+    if let Some(after) = filters.due_after {
+        statement = statement.filter(tasks::due_date.ge(after));
+    }
+    if let Some(before) = filters.due_before {
+        statement = statement.filter(tasks::due_date.lt(before));
+    }
+    if let Some(after) = filters.start_after {
+        statement = statement.filter(tasks::start_date.ge(after));
+    }
+    if let Some(before) = filters.start_before {
+        statement = statement.filter(tasks::start_date.lt(before));
+    }
+
+    let results = statement
+        .load::<QueryableTask>(connection)
+        .context("Failed to search tasks.")?;
+    Ok(results)
+}
+
+/// Quotes every term so user input can't be interpreted as FTS5 syntax.
+pub fn match_expression(query: &str) -> String {
+    query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
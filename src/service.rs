@@ -1,14 +1,109 @@
-use crate::database::establish_connection;
-use crate::diesel::{ExpressionMethods, QueryDsl, RunQueryDsl};
-use crate::models::{QueryableList, QueryableTask};
+use crate::agenda;
+use crate::assignees;
+use crate::attachments::{blob_path, BlobWriter, CHUNK_SIZE, MAX_SIZE};
+use crate::bundle;
+use crate::cleanup::remove_orphans;
+use crate::completion;
+use crate::config::StreamingConfig;
+use crate::counters::{list_counters, lists_with_counters, ListCounters};
+use crate::database::{establish_connection, retry_busy};
+use crate::dependencies;
+use crate::diesel::{
+    BoolExpressionMethods, Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl,
+    SqliteConnection,
+};
+use crate::duplicates;
+use crate::events::{self, Changes};
+use crate::frecency::{is_user_action, recent_items, record_access};
+use crate::groups;
+use crate::ical;
+use crate::icons;
+use crate::idempotency::{idempotency_key, payload_hash, remember, replayed};
+use crate::inbox;
+#[cfg(feature = "link-preview")]
+use crate::link_preview;
+use crate::list_settings;
+use crate::list_templates;
+use crate::location;
+use crate::metadata::{chunk_size, deadline, detect_duplicates, RequestContext};
+use crate::metrics;
+use crate::models::{
+    ListChanges, QueryableAttachment, QueryableComment, QueryableList, QueryablePerson,
+    QueryablePomodoro, QueryablePriority, QueryableStatus, QueryableSubtask, QueryableTag,
+    QueryableTask, QueryableTimeEntry, QueryableTombstone, TaskChanges, LIST_ENTITY, TASK_ENTITY,
+};
+use crate::ordering::{
+    next_list_position, next_subtask_position, next_task_position, reorder_groups, reorder_lists,
+    reorder_subtask, reorder_task,
+};
+use crate::priorities;
+use crate::progress::{self, with_progress};
+use crate::query::query_tasks;
+use crate::quick_add;
+use crate::recurrence::{self, anchor, Rule};
+use crate::reminders::snooze;
+use crate::schema::lists::archived as list_archived;
 use crate::schema::lists::dsl::*;
+use crate::schema::lists::pinned as list_pinned;
+use crate::schema::lists::position as list_position;
+use crate::schema::tasks::archived as task_archived;
+use crate::schema::tasks::color as task_color;
 use crate::schema::tasks::dsl::*;
+use crate::schema::tasks::pinned as task_pinned;
+use crate::schema::tasks::position as task_position;
+use crate::schema::{
+    attachments, comments, people, pomodoros, subtasks, tags, task_tags, time_entries, tombstones,
+};
+use crate::search::{search, Facets, SearchFilters};
+use crate::settings;
+use crate::statistics;
+use crate::statuses;
+use crate::supervisor::Supervisor;
+use crate::sync::sync;
+use crate::templates;
+use crate::validation::{installed_icon, upcoming_days, Validate};
+use crate::version;
 use anyhow::Context;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
 use proto_rust::provider::provider_server::Provider;
-use proto_rust::provider::{CountResponse, Empty, List, ListResponse, Task, TaskResponse};
+use proto_rust::provider::{
+    AgendaBlock, AgendaResponse, Attachment, AttachmentChunk, AttachmentResponse,
+    AttachmentsResponse, BoardStatus, BundleResponse, ChangeEvent, ChangeKind, ChangeSet,
+    ClientVersion, Comment, CommentResponse, CommentsResponse, CompletionSettings,
+    CompletionSettingsResponse, CountResponse, CreateListFromTemplateRequest, DateRangeRequest,
+    DependencyRequest, DuplicateListRequest, DuplicateListResponse, Empty, IcalResponse,
+    IconsResponse, InstantiateTemplateRequest, LinkPreviewResponse, List, ListCounter,
+    ListCountersResponse, ListGroup, ListGroupResponse, ListGroupsResponse, ListResponse,
+    ListSettings, ListSettingsResponse, ListStatistics, ListStatisticsResponse, ListTemplate,
+    ListTemplateResponse, ListTemplatesResponse, ListWithCount, ListWithTasksResponse,
+    MergeListsRequest, MergeListsResponse, MergeTasksRequest, MoveTaskRequest, MoveToStatusRequest,
+    NearbyTasksRequest, NegotiatedVersion, NextOccurrencesRequest, OccurrencesResponse,
+    OrphanCleanupResponse, OverdueTasksRequest, PeopleResponse, Person, PersonResponse,
+    PomodoroDay, PomodoroResponse, PomodoroStatsResponse, PrioritiesResponse, Priority,
+    QuickAddRequest, QuickAddResponse, ReadAllListsRequest, ReadTasksFromListRequest,
+    RecentItemResponse, ReorderListGroupsRequest, ReorderListsRequest, ReorderListsResponse,
+    ReorderSubtaskRequest, ReorderTaskRequest, ReorderTaskResponse, SaveListTemplateRequest,
+    SaveTemplateRequest, SearchRequest, SearchResponse, ServerInfoResponse,
+    SetAllTasksCompletedRequest, SetListPinnedRequest, SetListProtectedRequest,
+    SetTaskPinnedRequest, SnoozeReminderRequest, StartPomodoroRequest, StatusesResponse, Subtask,
+    SubtaskResponse, SubtasksResponse, Tag, TagResponse, TagTasksRequest, TagsResponse, Task,
+    TaskResponse, TaskStatus, TaskTagRequest, Template, TemplateResponse, TemplatesResponse,
+    TimeEntriesResponse, TimeEntry, TimeEntryResponse, UpdatePrioritiesRequest,
+    UpdateStatusesRequest, WorkloadDay, WorkloadResponse,
+};
 use proto_rust::{ListIdResponse, TaskIdResponse};
+use std::collections::{BTreeMap, HashMap};
+use tokio::io::AsyncReadExt;
+use tokio::sync::broadcast::error::RecvError;
+use tokio::sync::mpsc::{Receiver, Sender};
+use tokio::time::Instant;
 use tokio_stream::wrappers::ReceiverStream;
-use tonic::{Request, Response, Status};
+use tonic::metadata::MetadataMap;
+use tonic::{Request, Response, Status, Streaming};
+
+/// Most occurrences returned by `get_next_occurrences`.
+const MAX_OCCURRENCES: usize = 1000;
 
 #[derive(Debug, Default)]
 pub struct LocalService {
@@ -16,423 +111,4325 @@ pub struct LocalService {
     pub name: String,
     pub description: String,
     pub icon: String,
+    /// Timezone used when a request doesn't carry one.
+    pub timezone: Tz,
+    pub supervisor: Supervisor,
+    pub changes: Changes,
+    pub streaming: StreamingConfig,
+}
+
+/// How a stream should be delivered, read from the request metadata.
+#[derive(Debug, Clone, Copy)]
+struct StreamOptions {
+    deadline: Option<Instant>,
+    /// Tasks per message, one means the legacy `task` field is used.
+    chunk_size: usize,
+}
+
+/// Outcome of `create_task`.
+enum Created {
+    New(Task),
+    /// Created earlier by a request with the same idempotency key.
+    Replayed(Task),
+    /// Nothing was created because these tasks look like the same one.
+    Duplicates(Vec<Task>),
+}
+
+impl LocalService {
+    fn channel<T>(&self) -> (Sender<T>, Receiver<T>) {
+        tokio::sync::mpsc::channel(self.streaming.channel_capacity.max(1))
+    }
+
+    fn stream_options(&self, metadata: &MetadataMap) -> StreamOptions {
+        StreamOptions {
+            deadline: deadline(metadata),
+            chunk_size: chunk_size(metadata)
+                .unwrap_or(1)
+                .clamp(1, self.streaming.max_chunk_size.max(1)),
+        }
+    }
+
+    /// Archives or restores a list, leaving its tasks untouched.
+    fn set_archived(&self, id: String, value: bool) -> ListResponse {
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<List> {
+            let mut connection = establish_connection()?;
+            let updated = diesel::update(lists.filter(id_list.eq(&id)))
+                .set(list_archived.eq(value))
+                .execute(&mut connection)
+                .context("Failed to update list.")?;
+            if updated == 0 {
+                anyhow::bail!("The list doesn't exist.");
+            }
+            let list: QueryableList = lists.find(&id).first(&mut connection)?;
+            Ok(list.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(list) => {
+                self.changes.list(ChangeKind::ListUpdated, list.clone());
+                response.list = Some(list);
+                response.successful = true;
+                response.message = if value {
+                    "List archived successfully.".to_string()
+                } else {
+                    "List unarchived successfully.".to_string()
+                }
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        response
+    }
+
+    /// Archives or restores a task, which keeps its completion state.
+    fn set_task_archived(&self, id: String, value: bool) -> TaskResponse {
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let mut connection = establish_connection()?;
+            let updated = diesel::update(tasks.filter(id_task.eq(&id)))
+                .set((
+                    task_archived.eq(value),
+                    last_modified_date_time.eq(Utc::now().naive_utc()),
+                ))
+                .execute(&mut connection)
+                .context("Failed to update task.")?;
+            if updated == 0 {
+                anyhow::bail!("The task doesn't exist.");
+            }
+            let task: QueryableTask = tasks.find(&id).first(&mut connection)?;
+            let mut task = [Task::from(task)];
+            progress::attach(&mut connection, &mut task)?;
+            let [task] = task;
+            Ok(task)
+        };
+
+        match retry_busy(send_request) {
+            Ok(task) => {
+                self.changes.task(ChangeKind::TaskUpdated, task.clone());
+                response.task = Some(task);
+                response.successful = true;
+                response.message = if value {
+                    "Task archived successfully.".to_string()
+                } else {
+                    "Task unarchived successfully.".to_string()
+                }
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        response
+    }
+
+    /// Runs `send_request` on the supervisor and streams the tasks it returns,
+    /// in chunks when the client asked for them.
+    fn stream_tasks<F>(
+        &self,
+        method: &'static str,
+        options: StreamOptions,
+        send_request: F,
+    ) -> ReceiverStream<Result<TaskResponse, Status>>
+    where
+        F: FnMut() -> anyhow::Result<Vec<Task>> + Send + 'static,
+    {
+        let mut send_request = send_request;
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let mut value = send_request()?;
+            progress::attach(&mut establish_connection()?, &mut value)?;
+            Ok(value)
+        };
+        let (tx, rx) = self.channel();
+        let StreamOptions {
+            deadline,
+            chunk_size,
+        } = options;
+
+        self.supervisor.spawn_stream(method, async move {
+            let responses = match fetch(&tx, deadline, send_request).await? {
+                Some(Ok(value)) if chunk_size > 1 => value
+                    .chunks(chunk_size)
+                    .map(|chunk| TaskResponse {
+                        successful: true,
+                        message: "Tasks fetched successfully.".to_string(),
+                        task: None,
+                        tasks: chunk.to_vec(),
+                    })
+                    .collect(),
+                Some(Ok(value)) => value
+                    .into_iter()
+                    .map(|task| TaskResponse {
+                        successful: true,
+                        message: "Task fetched successfully.".to_string(),
+                        task: Some(task),
+                        tasks: vec![],
+                    })
+                    .collect(),
+                Some(Err(err)) => vec![TaskResponse {
+                    successful: false,
+                    message: err.to_string(),
+                    ..Default::default()
+                }],
+                None => return Ok(()),
+            };
+            send_all(method, &tx, deadline, responses).await;
+            Ok(())
+        });
+
+        ReceiverStream::new(rx)
+    }
+
+    /// Runs `send_request` on the supervisor and streams the lists it returns.
+    fn stream_lists<F>(
+        &self,
+        method: &'static str,
+        options: StreamOptions,
+        send_request: F,
+    ) -> ReceiverStream<Result<ListResponse, Status>>
+    where
+        F: FnMut() -> anyhow::Result<Vec<List>> + Send + 'static,
+    {
+        let (tx, rx) = self.channel();
+        let deadline = options.deadline;
+
+        self.supervisor.spawn_stream(method, async move {
+            let responses = match fetch(&tx, deadline, send_request).await? {
+                Some(Ok(value)) => value
+                    .into_iter()
+                    .map(|list| ListResponse {
+                        successful: true,
+                        message: "List fetched successfully.".to_string(),
+                        list: Some(list),
+                    })
+                    .collect(),
+                Some(Err(err)) => vec![ListResponse {
+                    successful: false,
+                    message: err.to_string(),
+                    list: None,
+                }],
+                None => return Ok(()),
+            };
+            send_all(method, &tx, deadline, responses).await;
+            Ok(())
+        });
+
+        ReceiverStream::new(rx)
+    }
+}
+
+/// Copies the checklist, tags and attachments of every `(original, copy)` pair of tasks.
+fn copy_task_details(
+    connection: &mut SqliteConnection,
+    pairs: &[(String, String)],
+) -> anyhow::Result<()> {
+    for (original, copy) in pairs {
+        let tagged: Vec<String> = task_tags::table
+            .filter(task_tags::id_task.eq(original))
+            .select(task_tags::id_tag)
+            .load(connection)?;
+        let tagged: Vec<_> = tagged
+            .iter()
+            .map(|tag| (task_tags::id_task.eq(copy), task_tags::id_tag.eq(tag)))
+            .collect();
+        diesel::insert_into(task_tags::table)
+            .values(&tagged)
+            .execute(connection)?;
+
+        let checklist: Vec<QueryableSubtask> = subtasks::table
+            .filter(subtasks::id_task.eq(original))
+            .load(connection)?
+            .iter()
+            .map(|subtask: &QueryableSubtask| subtask.copy_to_task(copy))
+            .collect();
+        diesel::insert_into(subtasks::table)
+            .values(&checklist)
+            .execute(connection)?;
+
+        let files: Vec<QueryableAttachment> = attachments::table
+            .filter(attachments::id_task.eq(original))
+            .load(connection)?
+            .iter()
+            .map(|attachment: &QueryableAttachment| attachment.copy_to_task(copy))
+            .collect();
+        diesel::insert_into(attachments::table)
+            .values(&files)
+            .execute(connection)?;
+    }
+    Ok(())
+}
+
+/// The error of a change refused because its list is protected, answered
+/// with `FAILED_PRECONDITION` instead of an unsuccessful response.
+#[derive(Debug)]
+struct ProtectedList;
+
+impl std::fmt::Display for ProtectedList {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("The list is protected, unprotect it first.")
+    }
+}
+
+impl std::error::Error for ProtectedList {}
+
+/// Refuses to go on when `list` is protected. Lists that don't exist are
+/// left for the caller to report. Run it in the transaction that changes
+/// the list, so it can't be protected in between.
+fn check_unprotected(connection: &mut SqliteConnection, list: &str) -> anyhow::Result<()> {
+    let protected_list: Option<bool> = lists
+        .find(list)
+        .select(protected)
+        .first(connection)
+        .optional()?;
+    if protected_list == Some(true) {
+        return Err(ProtectedList.into());
+    }
+    Ok(())
+}
+
+/// Runs the blocking `send_request` off the runtime and waits for it unless
+/// the client hangs up or its deadline passes first, in which case `None` is
+/// returned and the result is discarded.
+///
+/// Blocking work can't be interrupted, so a query that already started keeps
+/// running to the end and its writes are committed even though nobody reads
+/// the response.
+async fn fetch<T, R, F>(
+    tx: &Sender<Result<R, Status>>,
+    deadline: Option<Instant>,
+    send_request: F,
+) -> anyhow::Result<Option<anyhow::Result<T>>>
+where
+    T: Send + 'static,
+    F: FnMut() -> anyhow::Result<T> + Send + 'static,
+{
+    let span = tracing::info_span!("db_query");
+    let work = tokio::task::spawn_blocking(move || span.in_scope(|| retry_busy(send_request)));
+    tokio::select! {
+        result = work => Ok(Some(result?)),
+        _ = tx.closed() => Ok(None),
+        _ = expired(deadline) => {
+            tx.send(Err(Status::deadline_exceeded("Deadline exceeded"))).await.ok();
+            Ok(None)
+        }
+    }
+}
+
+/// Sends every response, stopping early when the client hangs up or its
+/// deadline passes.
+async fn send_all<R>(
+    method: &str,
+    tx: &Sender<Result<R, Status>>,
+    deadline: Option<Instant>,
+    responses: Vec<R>,
+) {
+    metrics::record_stream(method, responses.len());
+    for response in responses {
+        tokio::select! {
+            sent = tx.send(Ok(response)) => {
+                if sent.is_err() {
+                    return;
+                }
+            }
+            _ = expired(deadline) => {
+                tx.send(Err(Status::deadline_exceeded("Deadline exceeded"))).await.ok();
+                return;
+            }
+        }
+    }
+}
+
+/// Resolves once `deadline` has passed, never when there is none.
+async fn expired(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
 }
 
 #[tonic::async_trait]
 impl Provider for LocalService {
-    async fn get_id(&self, request: Request<Empty>) -> Result<Response<String>, Status> {
-        tracing::info!("Request received: {request:?}");
+    async fn get_id(&self, _request: Request<Empty>) -> Result<Response<String>, Status> {
         Ok(Response::new(self.id.clone()))
     }
 
-    async fn get_name(&self, request: Request<Empty>) -> Result<Response<String>, Status> {
-        tracing::info!("Request received: {request:?}");
+    async fn get_name(&self, _request: Request<Empty>) -> Result<Response<String>, Status> {
         Ok(Response::new(self.name.clone()))
     }
 
-    async fn get_description(&self, request: Request<Empty>) -> Result<Response<String>, Status> {
-        tracing::info!("Request received: {request:?}");
+    async fn get_description(&self, _request: Request<Empty>) -> Result<Response<String>, Status> {
         Ok(Response::new(self.description.clone()))
     }
 
-    async fn get_icon_name(&self, request: Request<Empty>) -> Result<Response<String>, Status> {
-        tracing::info!("Request received: {request:?}");
+    async fn get_icon_name(&self, _request: Request<Empty>) -> Result<Response<String>, Status> {
         Ok(Response::new(self.icon.clone()))
     }
 
+    async fn negotiate(
+        &self,
+        request: Request<ClientVersion>,
+    ) -> Result<Response<NegotiatedVersion>, Status> {
+        let ClientVersion { api_version, .. } = request.into_inner();
+        let negotiated = version::negotiate(api_version);
+
+        Ok(Response::new(NegotiatedVersion {
+            supported: negotiated.is_some(),
+            api_version: negotiated.unwrap_or_default(),
+            min_api_version: version::MIN_API_VERSION,
+            max_api_version: version::API_VERSION,
+            features: negotiated.map(version::features).unwrap_or_default(),
+        }))
+    }
+
+    async fn get_server_info(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ServerInfoResponse>, Status> {
+        Ok(Response::new(ServerInfoResponse {
+            version: version::VERSION.to_string(),
+            proto_revision: version::PROTO_REVISION.to_string(),
+            features: version::features(version::API_VERSION),
+            limits: HashMap::from([
+                ("max_message_size".to_string(), version::MAX_MESSAGE_SIZE),
+                ("watch_buffer".to_string(), events::CAPACITY as i64),
+                ("max_attachment_size".to_string(), MAX_SIZE as i64),
+            ]),
+        }))
+    }
+
     type ReadAllTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
 
     async fn read_all_tasks(
         &self,
         request: Request<Empty>,
     ) -> Result<Response<Self::ReadAllTasksStream>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        let options = self.stream_options(request.metadata());
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.iter().map(|t| t.clone().into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_all_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadTasksFromListStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_tasks_from_list(
+        &self,
+        request: Request<ReadTasksFromListRequest>,
+    ) -> Result<Response<Self::ReadTasksFromListStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        request.get_ref().validate()?;
+        let ReadTasksFromListRequest {
+            list_id: id,
+            include_completed,
+        } = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let mut connection = establish_connection()?;
+            let include_completed = match include_completed {
+                Some(value) => value,
+                None => !list_settings::hides_completed(&mut connection, &id)?,
+            };
+            let mut query = tasks
+                .filter(task_archived.eq(false))
+                .filter(parent_list.eq(&id))
+                .into_boxed();
+            if !include_completed {
+                query = query.filter(status.ne(TaskStatus::Completed as i32));
+            }
+            let result: Vec<QueryableTask> = query
+                .order((
+                    task_pinned.desc(),
+                    task_position.asc(),
+                    created_date_time.asc(),
+                ))
+                .load::<QueryableTask>(&mut connection)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.iter().map(|t| t.clone().into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_tasks_from_list",
+            options,
+            send_request,
+        )))
+    }
+
+    async fn search_tasks(
+        &self,
+        request: Request<SearchRequest>,
+    ) -> Result<Response<SearchResponse>, Status> {
+        let request = request.into_inner();
+        let mut response = SearchResponse::default();
+
+        let send_request = || -> anyhow::Result<(Vec<Task>, Facets)> {
+            let filters = SearchFilters {
+                list: request.list_id.clone(),
+                status: request.status,
+                due_after: request
+                    .due_after
+                    .map(|d| NaiveDateTime::from_timestamp_opt(d, 0).context("Invalid date."))
+                    .transpose()?,
+                due_before: request
+                    .due_before
+                    .map(|d| NaiveDateTime::from_timestamp_opt(d, 0).context("Invalid date."))
+                    .transpose()?,
+                start_after: request
+                    .start_after
+                    .map(|d| NaiveDateTime::from_timestamp_opt(d, 0).context("Invalid date."))
+                    .transpose()?,
+                start_before: request
+                    .start_before
+                    .map(|d| NaiveDateTime::from_timestamp_opt(d, 0).context("Invalid date."))
+                    .transpose()?,
+            };
+            let mut connection = establish_connection()?;
+            let results = search(&mut connection, &request.query, &filters)?;
+            let facets = Facets::count(&results);
+            let mut results: Vec<Task> = results.into_iter().map(Task::from).collect();
+            progress::attach(&mut connection, &mut results)?;
+            Ok((results, facets))
+        };
+
+        match retry_busy(send_request) {
+            Ok((value, facets)) => {
+                response.list_counts = facets.lists;
+                response.status_counts = facets.statuses;
+                response.tasks = value;
+                response.successful = true;
+                response.message = "Tasks fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type QueryTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn query_tasks(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<Self::QueryTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let query = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let result = query_tasks(&mut establish_connection()?, &query, &context)?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "query_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadTasksByTagStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_tasks_by_tag(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<Self::ReadTasksByTagStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let id = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let tagged = task_tags::table
+                .filter(task_tags::id_tag.eq(&id))
+                .select(task_tags::id_task);
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(id_task.eq_any(tagged))
+                .order((parent_list.asc(), task_pinned.desc(), task_position.asc()))
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            Ok(result.into_iter().map(Task::from).collect())
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_tasks_by_tag",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadTasksAssignedToStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_tasks_assigned_to(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<Self::ReadTasksAssignedToStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let person = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(assigned_to.eq(&person))
+                .order((parent_list.asc(), task_pinned.desc(), task_position.asc()))
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            Ok(result.into_iter().map(Task::from).collect())
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_tasks_assigned_to",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadArchivedTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_archived_tasks(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::ReadArchivedTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
 
         let send_request = || -> anyhow::Result<Vec<Task>> {
             let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(true))
+                .order(last_modified_date_time.desc())
                 .load::<QueryableTask>(&mut establish_connection()?)
                 .context("Failed to fetch list of tasks.")?;
-            let results: Vec<Task> = result.iter().map(|t| t.clone().into()).collect();
+            Ok(result.into_iter().map(Task::from).collect())
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_archived_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadTasksInRangeStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_tasks_in_range(
+        &self,
+        request: Request<DateRangeRequest>,
+    ) -> Result<Response<Self::ReadTasksInRangeStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let DateRangeRequest { start, end } = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let start =
+                NaiveDateTime::from_timestamp_opt(start, 0).context("Invalid start date.")?;
+            let end = NaiveDateTime::from_timestamp_opt(end, 0).context("Invalid end date.")?;
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(
+                    due_date
+                        .ge(start)
+                        .and(due_date.lt(end))
+                        .or(reminder_date.ge(start).and(reminder_date.lt(end))),
+                )
+                .order((due_date.asc(), reminder_date.asc()))
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
             Ok(results)
         };
 
-        let mut response = TaskResponse::default();
+        Ok(Response::new(self.stream_tasks(
+            "read_tasks_in_range",
+            options,
+            send_request,
+        )))
+    }
 
-        tokio::spawn(async move {
-            match send_request() {
-                Ok(value) => {
-                    response.successful = true;
-                    for task in &value[..] {
-                        let response = TaskResponse {
-                            successful: true,
-                            message: "Task fetched succesfully.".to_string(),
-                            task: Some(task.clone()),
-                        };
-                        tx.send(Ok(response)).await.unwrap();
-                    }
-                }
-                Err(err) => response.message = err.to_string(),
+    async fn read_agenda(
+        &self,
+        request: Request<DateRangeRequest>,
+    ) -> Result<Response<AgendaResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let DateRangeRequest { start, end } = request.into_inner();
+        let mut response = AgendaResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<AgendaBlock>> {
+            let start =
+                NaiveDateTime::from_timestamp_opt(start, 0).context("Invalid start date.")?;
+            let end = NaiveDateTime::from_timestamp_opt(end, 0).context("Invalid end date.")?;
+            agenda::blocks(&mut establish_connection()?, &context, start, end)
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.blocks = value;
+                response.successful = true;
+                response.message = "Agenda fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type ReadTodayTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_today_tasks(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::ReadTodayTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let today = context.today();
+            let start = context.start_of_day(today);
+            let end = context.start_of_day(today + Duration::days(1));
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(
+                    due_date.ge(start).and(due_date.lt(end)).or(due_date
+                        .lt(start)
+                        .and(status.ne(TaskStatus::Completed as i32))),
+                )
+                .order(due_date.asc())
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_today_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadUpcomingTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_upcoming_tasks(
+        &self,
+        request: Request<u32>,
+    ) -> Result<Response<Self::ReadUpcomingTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let days = request.into_inner();
+        upcoming_days(days)?;
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let today = context.today();
+            let start = context.start_of_day(today);
+            let end = context.start_of_day(today + Duration::days(days.into()));
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(due_date.ge(start).and(due_date.lt(end)))
+                .filter(status.ne(TaskStatus::Completed as i32))
+                .order(due_date.asc())
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_upcoming_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadCompletedTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_completed_tasks(
+        &self,
+        request: Request<i64>,
+    ) -> Result<Response<Self::ReadCompletedTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let since = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let since =
+                NaiveDateTime::from_timestamp_opt(since, 0).context("Invalid timestamp.")?;
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(status.eq(TaskStatus::Completed as i32))
+                .filter(completed_on.gt(since))
+                .order(completed_on.asc())
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_completed_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadFavoriteTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_favorite_tasks(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::ReadFavoriteTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+
+        let send_request = || -> anyhow::Result<Vec<Task>> {
+            let result: Vec<QueryableTask> = tasks
+                .filter(task_archived.eq(false))
+                .filter(favorite.eq(true))
+                .order(created_date_time.asc())
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_favorite_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    type ReadOverdueTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_overdue_tasks(
+        &self,
+        request: Request<OverdueTasksRequest>,
+    ) -> Result<Response<Self::ReadOverdueTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let group_by_list = request.into_inner().group_by_list;
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let start = context.start_of_day(context.today());
+            let mut query = tasks
+                .filter(task_archived.eq(false))
+                .filter(due_date.lt(start))
+                .filter(status.ne(TaskStatus::Completed as i32))
+                .into_boxed();
+            if group_by_list {
+                query = query.order(parent_list.asc());
+            }
+            let result: Vec<QueryableTask> = query
+                .then_order_by(due_date.asc())
+                .load::<QueryableTask>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_overdue_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    async fn read_task_ids_from_list(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TaskIdResponse>, Status> {
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let result: Vec<String> = tasks
+                .filter(task_archived.eq(false))
+                .select(id_task)
+                .filter(parent_list.eq(request.get_ref()))
+                .load::<String>(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            Ok(result)
+        };
+
+        let mut response = TaskIdResponse {
+            successful: true,
+            message: String::new(),
+            tasks: vec![],
+        };
+
+        match retry_busy(send_request) {
+            Ok(result) => {
+                response.successful = true;
+                response.tasks = result;
+            }
+            Err(_) => response.message = "Failed to fetch list of tasks".to_string(),
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn read_task_count_from_list(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<CountResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let id = request.into_inner();
+        let mut response = CountResponse::default();
+
+        let send_request = || -> anyhow::Result<Option<ListCounters>> {
+            let counters = list_counters(&mut establish_connection()?, &context, Some(&id))?;
+            Ok(counters.into_iter().next())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                if let Some(counters) = value {
+                    response.count = counters.total;
+                    response.completed = counters.completed;
+                    response.incomplete = counters.incomplete;
+                    response.overdue = counters.overdue;
+                }
+                response.successful = true;
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn create_task(&self, request: Request<Task>) -> Result<Response<TaskResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let key = idempotency_key(request.metadata());
+        let detect = detect_duplicates(request.metadata());
+        let task = request.into_inner();
+        task.validate()?;
+        let hash = payload_hash(&task);
+        let mut response = TaskResponse::default();
+
+        // Returns whether the task was created by this request rather than
+        // by an earlier one with the same idempotency key, or the tasks it
+        // may duplicate when nothing was created.
+        let send_request = || -> anyhow::Result<Created> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                if let Some(key) = &key {
+                    if let Some(id) = replayed(connection, key, "create_task", &hash)? {
+                        let original: QueryableTask = tasks
+                            .find(id)
+                            .first(connection)
+                            .context("The task created by this request no longer exists.")?;
+                        return Ok(Created::Replayed(original.into()));
+                    }
+                }
+
+                priorities::check(connection, task.importance)?;
+                assignees::check(connection, task.assigned_to.as_deref())?;
+                let mut queryable_task: QueryableTask = task.clone().into();
+                if detect {
+                    let candidates = duplicates::candidates(connection, &queryable_task)?;
+                    if !candidates.is_empty() {
+                        let mut candidates: Vec<Task> =
+                            candidates.into_iter().map(Task::from).collect();
+                        progress::attach(connection, &mut candidates)?;
+                        return Ok(Created::Duplicates(candidates));
+                    }
+                }
+                queryable_task.position =
+                    next_task_position(connection, &queryable_task.parent_list)?;
+                list_settings::apply_defaults(connection, &mut queryable_task)?;
+                if queryable_task.timezone.is_none() {
+                    queryable_task.timezone = Some(context.timezone.name().to_string());
+                }
+
+                diesel::insert_into(tasks)
+                    .values(&queryable_task)
+                    .execute(connection)?;
+                if let Some(key) = &key {
+                    remember(
+                        connection,
+                        key,
+                        "create_task",
+                        &hash,
+                        &queryable_task.id_task,
+                    )?;
+                }
+
+                Ok(Created::New(queryable_task.into()))
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(Created::Duplicates(candidates)) => {
+                response.tasks = candidates;
+                response.message = "Similar tasks already exist.".to_string()
+            }
+            Ok(Created::New(task)) => {
+                self.changes.task(ChangeKind::TaskCreated, task.clone());
+                response.task = Some(with_progress(task).await);
+                response.successful = true;
+                response.message = "Task added successfully.".to_string()
+            }
+            Ok(Created::Replayed(task)) => {
+                response.task = Some(with_progress(task).await);
+                response.successful = true;
+                response.message = "Task added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn upsert_task(&self, request: Request<Task>) -> Result<Response<TaskResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let task = request.into_inner();
+        task.validate()?;
+        let mut response = TaskResponse::default();
+
+        // Returns the stored task and the next occurrence when this completed
+        // a repeating task.
+        let send_request = || -> anyhow::Result<(QueryableTask, Option<QueryableTask>)> {
+            let queryable_task: QueryableTask = task.clone().into();
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                priorities::check(connection, queryable_task.importance)?;
+                assignees::check(connection, queryable_task.assigned_to.as_deref())?;
+                let previous: Option<i32> = tasks
+                    .find(&queryable_task.id_task)
+                    .select(status)
+                    .first(connection)
+                    .optional()?;
+
+                diesel::insert_into(tasks)
+                    .values(&queryable_task)
+                    .on_conflict(id_task)
+                    .do_update()
+                    .set(&TaskChanges::new(&queryable_task, context.api_version))
+                    .execute(connection)
+                    .context("Failed to upsert task.")?;
+                let stored: QueryableTask =
+                    tasks.find(&queryable_task.id_task).first(connection)?;
+
+                let completed = TaskStatus::Completed as i32;
+                if previous.is_none_or(|previous| previous == completed)
+                    || stored.status != completed
+                {
+                    return Ok((stored, None));
+                }
+                let next = completion::completed(connection, &stored)?;
+                Ok((stored, next))
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok((stored, next)) => {
+                let stored: Task = stored.into();
+                self.changes.task(ChangeKind::TaskUpdated, stored.clone());
+                if let Some(next) = next {
+                    self.changes.task(ChangeKind::TaskCreated, next.into());
+                }
+                response.task = Some(with_progress(stored).await);
+                response.successful = true;
+                response.message = "Task upserted successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_task(&self, request: Request<String>) -> Result<Response<TaskResponse>, Status> {
+        let user_action = is_user_action(request.metadata());
+        let id = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let mut connection = establish_connection()?;
+            let result: QueryableTask = tasks
+                .find(&id)
+                .first(&mut connection)
+                .context("Failed to fetch list of tasks.")?;
+            if user_action {
+                if let Err(err) = record_access(&mut connection, &id, TASK_ENTITY) {
+                    tracing::warn!("Failed to record task access: {err}");
+                }
+            }
+            Ok(result.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Task fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn update_task(&self, request: Request<Task>) -> Result<Response<TaskResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let task = request.into_inner();
+        task.validate()?;
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Option<QueryableTask>> {
+            let task: QueryableTask = task.clone().into();
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                priorities::check(connection, task.importance)?;
+                assignees::check(connection, task.assigned_to.as_deref())?;
+                let previous: Option<i32> = tasks
+                    .find(&task.id_task)
+                    .select(status)
+                    .first(connection)
+                    .optional()?;
+
+                // Moving, board columns and archiving have calls of their own.
+                let changes = TaskChanges {
+                    parent_list: None,
+                    id_status: None,
+                    archived: None,
+                    ..TaskChanges::new(&task, context.api_version)
+                };
+                diesel::update(tasks.find(&task.id_task))
+                    .set(&changes)
+                    .execute(connection)
+                    .context("Failed to update task.")?;
+
+                let completed = TaskStatus::Completed as i32;
+                if previous.is_none_or(|previous| previous == completed) || task.status != completed
+                {
+                    return Ok(None);
+                }
+                let stored: QueryableTask = tasks.find(&task.id_task).first(connection)?;
+                completion::completed(connection, &stored)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(next) => {
+                self.changes.task(ChangeKind::TaskUpdated, task);
+                if let Some(next) = next {
+                    self.changes.task(ChangeKind::TaskCreated, next.into());
+                }
+                response.task = None;
+                response.successful = true;
+                response.message = "Task updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_workload(
+        &self,
+        request: Request<DateRangeRequest>,
+    ) -> Result<Response<WorkloadResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let DateRangeRequest { start, end } = request.into_inner();
+        let mut response = WorkloadResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<WorkloadDay>> {
+            let start =
+                NaiveDateTime::from_timestamp_opt(start, 0).context("Invalid start date.")?;
+            let end = NaiveDateTime::from_timestamp_opt(end, 0).context("Invalid end date.")?;
+            let scheduled: Vec<(Option<NaiveDateTime>, Option<i32>, Option<String>)> = tasks
+                .filter(task_archived.eq(false))
+                .filter(due_date.ge(start))
+                .filter(due_date.lt(end))
+                .filter(status.ne(TaskStatus::Completed as i32))
+                .select((due_date, estimate_minutes, timezone))
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch scheduled tasks.")?;
+
+            let mut days: BTreeMap<NaiveDate, (i64, i64)> = BTreeMap::new();
+            for (due, estimate, zone) in scheduled {
+                if let Some(due) = due {
+                    let zone = context.in_zone(zone.and_then(|zone| zone.parse().ok()));
+                    let day = days.entry(zone.local_date(due)).or_default();
+                    day.0 += estimate.unwrap_or_default() as i64;
+                    day.1 += 1;
+                }
+            }
+            Ok(days
+                .into_iter()
+                .map(|(day, (minutes, count))| WorkloadDay {
+                    date: context.start_of_day(day).timestamp(),
+                    minutes,
+                    tasks: count,
+                })
+                .collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.days = value;
+                response.successful = true;
+                response.message = "Workload fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_priorities(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<PrioritiesResponse>, Status> {
+        let mut response = PrioritiesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Priority>> {
+            let levels = priorities::read(&mut establish_connection()?)
+                .context("Failed to fetch priorities.")?;
+            Ok(levels.into_iter().map(Priority::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.priorities = value;
+                response.successful = true;
+                response.message = "Priorities fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn update_priorities(
+        &self,
+        request: Request<UpdatePrioritiesRequest>,
+    ) -> Result<Response<PrioritiesResponse>, Status> {
+        request.get_ref().validate()?;
+        let levels: Vec<QueryablePriority> = request
+            .into_inner()
+            .priorities
+            .into_iter()
+            .map(QueryablePriority::from)
+            .collect();
+        let mut response = PrioritiesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Priority>> {
+            let mut connection = establish_connection()?;
+            priorities::replace(&mut connection, &levels)?;
+            let levels = priorities::read(&mut connection)?;
+            Ok(levels.into_iter().map(Priority::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.priorities = value;
+                response.successful = true;
+                response.message = "Priorities updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_statuses(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<StatusesResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = StatusesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<BoardStatus>> {
+            let columns = statuses::read(&mut establish_connection()?, &id)
+                .context("Failed to fetch statuses.")?;
+            Ok(columns.into_iter().map(BoardStatus::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.statuses = value;
+                response.successful = true;
+                response.message = "Statuses fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn update_statuses(
+        &self,
+        request: Request<UpdateStatusesRequest>,
+    ) -> Result<Response<StatusesResponse>, Status> {
+        request.get_ref().validate()?;
+        let UpdateStatusesRequest {
+            list_id,
+            statuses: columns,
+        } = request.into_inner();
+        let mut response = StatusesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<BoardStatus>> {
+            let mut connection = establish_connection()?;
+            let columns = columns.iter().cloned().map(QueryableStatus::from).collect();
+            statuses::replace(&mut connection, &list_id, columns)?;
+            let columns = statuses::read(&mut connection, &list_id)?;
+            Ok(columns.into_iter().map(BoardStatus::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.statuses = value;
+                response.successful = true;
+                response.message = "Statuses updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn move_task_to_status(
+        &self,
+        request: Request<MoveToStatusRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let MoveToStatusRequest { task_id, status_id } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<(Task, Option<QueryableTask>)> {
+            let (moved, next) =
+                statuses::move_task(&mut establish_connection()?, &task_id, &status_id)?;
+            Ok((moved.into(), next))
+        };
+
+        match retry_busy(send_request) {
+            Ok((value, next)) => {
+                self.changes.task(ChangeKind::TaskUpdated, value.clone());
+                if let Some(next) = next {
+                    self.changes.task(ChangeKind::TaskCreated, next.into());
+                }
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Task moved successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn add_dependency(
+        &self,
+        request: Request<DependencyRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let DependencyRequest {
+            task_id,
+            blocker_id,
+        } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                dependencies::add(connection, &task_id, &blocker_id)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes.id(ChangeKind::TaskUpdated, task_id);
+                response.successful = true;
+                response.message = "Dependency added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn remove_dependency(
+        &self,
+        request: Request<DependencyRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let DependencyRequest {
+            task_id,
+            blocker_id,
+        } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            dependencies::remove(&mut establish_connection()?, &task_id, &blocker_id)
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes.id(ChangeKind::TaskUpdated, task_id);
+                response.successful = true;
+                response.message = "Dependency removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type ReadBlockedTasksStream = ReceiverStream<Result<TaskResponse, Status>>;
+    type ReadTasksNearStream = ReceiverStream<Result<TaskResponse, Status>>;
+
+    async fn read_blocked_tasks(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::ReadBlockedTasksStream>, Status> {
+        let options = self.stream_options(request.metadata());
+
+        let send_request = || -> anyhow::Result<Vec<Task>> {
+            let result = dependencies::blocked_tasks(&mut establish_connection()?)
+                .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_blocked_tasks",
+            options,
+            send_request,
+        )))
+    }
+
+    async fn read_tasks_near(
+        &self,
+        request: Request<NearbyTasksRequest>,
+    ) -> Result<Response<Self::ReadTasksNearStream>, Status> {
+        request.get_ref().validate()?;
+        let options = self.stream_options(request.metadata());
+        let NearbyTasksRequest {
+            latitude: near_latitude,
+            longitude: near_longitude,
+            radius,
+        } = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<Vec<Task>> {
+            let result = location::tasks_near(
+                &mut establish_connection()?,
+                near_latitude,
+                near_longitude,
+                radius,
+            )
+            .context("Failed to fetch list of tasks.")?;
+            let results: Vec<Task> = result.into_iter().map(|t| t.into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_tasks(
+            "read_tasks_near",
+            options,
+            send_request,
+        )))
+    }
+
+    async fn fetch_link_preview(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<LinkPreviewResponse>, Status> {
+        #[cfg(not(feature = "link-preview"))]
+        {
+            let _ = request;
+            Err(Status::unimplemented(
+                "Link previews aren't enabled in this build.",
+            ))
+        }
+        #[cfg(feature = "link-preview")]
+        {
+            let id = request.into_inner();
+            let mut response = LinkPreviewResponse::default();
+
+            let send_request = || -> anyhow::Result<String> {
+                let link: Option<String> = tasks
+                    .find(&id)
+                    .select(url)
+                    .first(&mut establish_connection()?)
+                    .context("The task doesn't exist.")?;
+                link.context("The task has no link.")
+            };
+
+            let preview = match retry_busy(send_request) {
+                Ok(link) => link_preview::fetch(&link).await,
+                Err(err) => Err(err),
+            };
+            match preview {
+                Ok(value) => {
+                    response.preview = Some(value);
+                    response.successful = true;
+                    response.message = "Link preview fetched successfully.".to_string()
+                }
+                Err(err) => response.message = err.to_string(),
+            }
+            Ok(Response::new(response))
+        }
+    }
+
+    async fn snooze_reminder(
+        &self,
+        request: Request<SnoozeReminderRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let SnoozeReminderRequest { task_id, duration } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let snoozed = snooze(
+                &mut establish_connection()?,
+                &task_id,
+                Duration::seconds(duration),
+            )?;
+            Ok(snoozed.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.task(ChangeKind::TaskUpdated, value.clone());
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Reminder snoozed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn get_next_occurrences(
+        &self,
+        request: Request<NextOccurrencesRequest>,
+    ) -> Result<Response<OccurrencesResponse>, Status> {
+        request.get_ref().validate()?;
+        let NextOccurrencesRequest { task_id, count } = request.into_inner();
+        let mut response = OccurrencesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<i64>> {
+            let task: QueryableTask = tasks
+                .find(&task_id)
+                .first(&mut establish_connection()?)
+                .context("The task doesn't exist.")?;
+            let rule: Rule = match &task.recurrence_rule {
+                Some(rule) => rule.parse()?,
+                None => return Ok(vec![]),
+            };
+            let start = anchor(&task);
+            let limit = (count as usize).min(MAX_OCCURRENCES);
+            Ok(rule
+                .occurrences_after_in(recurrence::zone(&task), start, start, limit)
+                .into_iter()
+                .map(|occurrence| occurrence.timestamp())
+                .collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.occurrences = value;
+                response.successful = true;
+                response.message = "Occurrences fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_task(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                diesel::delete(tasks.filter(id_task.eq(&id))).execute(connection)?;
+                diesel::replace_into(tombstones::table)
+                    .values(&QueryableTombstone::new(id.clone(), TASK_ENTITY))
+                    .execute(connection)?;
+                Ok(())
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes.id(ChangeKind::TaskDeleted, id);
+                response.task = None;
+                response.successful = true;
+                response.message = "Task removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn create_subtask(
+        &self,
+        request: Request<Subtask>,
+    ) -> Result<Response<SubtaskResponse>, Status> {
+        let subtask = request.into_inner();
+        subtask.validate()?;
+        let mut response = SubtaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Subtask> {
+            let mut connection = establish_connection()?;
+            let created = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let mut subtask: QueryableSubtask = subtask.clone().into();
+                tasks
+                    .find(&subtask.id_task)
+                    .select(id_task)
+                    .first::<String>(connection)
+                    .context("The task doesn't exist.")?;
+                subtask.position = next_subtask_position(connection, &subtask.id_task)?;
+                subtask.last_modified_date_time = Utc::now().naive_utc();
+                diesel::insert_into(subtasks::table)
+                    .values(&subtask)
+                    .execute(connection)?;
+                Ok(subtask)
+            })?;
+            Ok(created.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes
+                    .id(ChangeKind::TaskUpdated, value.task_id.clone());
+                response.subtask = Some(value);
+                response.successful = true;
+                response.message = "Subtask added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_subtasks(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<SubtasksResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = SubtasksResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Subtask>> {
+            let checklist: Vec<QueryableSubtask> = subtasks::table
+                .filter(subtasks::id_task.eq(&id))
+                .order((subtasks::position.asc(), subtasks::created_date_time.asc()))
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch subtasks.")?;
+            Ok(checklist.into_iter().map(Subtask::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.subtasks = value;
+                response.successful = true;
+                response.message = "Subtasks fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn update_subtask(
+        &self,
+        request: Request<Subtask>,
+    ) -> Result<Response<SubtaskResponse>, Status> {
+        let subtask = request.into_inner();
+        subtask.validate()?;
+        let mut response = SubtaskResponse::default();
+
+        // Also returns the next occurrence of the task when checking off the
+        // last subtask completed a repeating one.
+        let send_request = || -> anyhow::Result<(Subtask, Option<QueryableTask>)> {
+            let subtask: QueryableSubtask = subtask.clone().into();
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                // The client's clock can't be trusted to order changes.
+                let updated = diesel::update(subtasks::table.find(&subtask.id_subtask))
+                    .set((
+                        subtasks::title.eq(&subtask.title),
+                        subtasks::completed.eq(subtask.completed),
+                        subtasks::last_modified_date_time.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(connection)
+                    .context("Failed to update subtask.")?;
+                if updated == 0 {
+                    anyhow::bail!("The subtask doesn't exist.");
+                }
+                let subtask: QueryableSubtask = subtasks::table
+                    .find(&subtask.id_subtask)
+                    .first(connection)?;
+                let next = if subtask.completed {
+                    completion::complete_task(connection, &subtask.id_task)?
+                } else {
+                    None
+                };
+                Ok((subtask.into(), next))
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok((value, next)) => {
+                self.changes
+                    .id(ChangeKind::TaskUpdated, value.task_id.clone());
+                if let Some(next) = next {
+                    self.changes.task(ChangeKind::TaskCreated, next.into());
+                }
+                response.subtask = Some(value);
+                response.successful = true;
+                response.message = "Subtask updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_subtask(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<SubtaskResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = SubtaskResponse::default();
+
+        let send_request = || -> anyhow::Result<String> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let task: String = subtasks::table
+                    .find(&id)
+                    .select(subtasks::id_task)
+                    .first(connection)
+                    .context("The subtask doesn't exist.")?;
+                diesel::delete(subtasks::table.find(&id)).execute(connection)?;
+                Ok(task)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(task) => {
+                self.changes.id(ChangeKind::TaskUpdated, task);
+                response.subtask = None;
+                response.successful = true;
+                response.message = "Subtask removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn reorder_subtask(
+        &self,
+        request: Request<ReorderSubtaskRequest>,
+    ) -> Result<Response<SubtasksResponse>, Status> {
+        request.get_ref().validate()?;
+        let ReorderSubtaskRequest {
+            subtask_id,
+            after_subtask_id,
+        } = request.into_inner();
+        let mut response = SubtasksResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Subtask>> {
+            let mut connection = establish_connection()?;
+            let checklist = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                reorder_subtask(connection, &subtask_id, after_subtask_id.as_deref())
+            })?;
+            Ok(checklist.into_iter().map(Subtask::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                if let Some(subtask) = value.first() {
+                    self.changes
+                        .id(ChangeKind::TaskUpdated, subtask.task_id.clone());
+                }
+                response.subtasks = value;
+                response.successful = true;
+                response.message = "Subtask reordered successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn add_comment(
+        &self,
+        request: Request<Comment>,
+    ) -> Result<Response<CommentResponse>, Status> {
+        let comment = request.into_inner();
+        comment.validate()?;
+        let mut response = CommentResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                tasks
+                    .find(&comment.task_id)
+                    .select(id_task)
+                    .first::<String>(connection)
+                    .context("The task doesn't exist.")?;
+                diesel::insert_into(comments::table)
+                    .values(&QueryableComment::from(comment.clone()))
+                    .execute(connection)?;
+                Ok(())
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes
+                    .id(ChangeKind::TaskUpdated, comment.task_id.clone());
+                response.comment = Some(comment);
+                response.successful = true;
+                response.message = "Comment added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_comments(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<CommentsResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = CommentsResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Comment>> {
+            let results: Vec<QueryableComment> = comments::table
+                .filter(comments::id_task.eq(&id))
+                .order(comments::created_date_time.asc())
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch comments.")?;
+            Ok(results.into_iter().map(Comment::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.comments = value;
+                response.successful = true;
+                response.message = "Comments fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_comment(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<CommentResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = CommentResponse::default();
+
+        let send_request = || -> anyhow::Result<String> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let task: String = comments::table
+                    .find(&id)
+                    .select(comments::id_task)
+                    .first(connection)
+                    .context("The comment doesn't exist.")?;
+                diesel::delete(comments::table.find(&id)).execute(connection)?;
+                Ok(task)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(task) => {
+                self.changes.id(ChangeKind::TaskUpdated, task);
+                response.comment = None;
+                response.successful = true;
+                response.message = "Comment removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn upload_attachment(
+        &self,
+        request: Request<Streaming<AttachmentChunk>>,
+    ) -> Result<Response<AttachmentResponse>, Status> {
+        let mut incoming = request.into_inner();
+        let first = incoming
+            .message()
+            .await?
+            .ok_or_else(|| Status::invalid_argument("The upload is empty"))?;
+        let attachment = first.attachment.ok_or_else(|| {
+            Status::invalid_argument("The first message must describe the attachment")
+        })?;
+        attachment.validate()?;
+        let mut response = AttachmentResponse::default();
+
+        let stored = async {
+            let mut writer = BlobWriter::new().await?;
+            writer.write(&first.data).await?;
+            while let Some(chunk) = incoming.message().await? {
+                writer.write(&chunk.data).await?;
+            }
+            writer.finish().await
+        }
+        .await;
+        let (hash, size) = match stored {
+            Ok(value) => value,
+            Err(err) => {
+                response.message = err.to_string();
+                return Ok(Response::new(response));
+            }
+        };
+        let row = QueryableAttachment {
+            hash,
+            size: size as i64,
+            ..attachment.into()
+        };
+
+        let send_request = || -> anyhow::Result<()> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                tasks
+                    .find(&row.id_task)
+                    .select(id_task)
+                    .first::<String>(connection)
+                    .context("The task doesn't exist.")?;
+                diesel::insert_into(attachments::table)
+                    .values(&row)
+                    .execute(connection)?;
+                Ok(())
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes
+                    .id(ChangeKind::TaskUpdated, row.id_task.clone());
+                response.attachment = Some(row.into());
+                response.successful = true;
+                response.message = "Attachment uploaded successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type DownloadAttachmentStream = ReceiverStream<Result<AttachmentChunk, Status>>;
+
+    async fn download_attachment(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<Self::DownloadAttachmentStream>, Status> {
+        let StreamOptions { deadline, .. } = self.stream_options(request.metadata());
+        let (tx, rx) = self.channel();
+        let id = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<QueryableAttachment> {
+            attachments::table
+                .find(&id)
+                .first(&mut establish_connection()?)
+                .context("The attachment doesn't exist.")
+        };
+
+        self.supervisor
+            .spawn_stream("download_attachment", async move {
+                let row = match fetch(&tx, deadline, send_request).await? {
+                    Some(Ok(value)) => value,
+                    Some(Err(err)) => {
+                        tx.send(Err(Status::not_found(err.to_string()))).await.ok();
+                        return Ok(());
+                    }
+                    None => return Ok(()),
+                };
+                let mut file = match tokio::fs::File::open(blob_path(&row.hash)?).await {
+                    Ok(file) => file,
+                    Err(err) => {
+                        let message = format!("The attachment contents are missing: {err}");
+                        tx.send(Err(Status::data_loss(message))).await.ok();
+                        return Ok(());
+                    }
+                };
+
+                // The first message carries the metadata, so even an empty
+                // file is answered with one message.
+                let mut attachment = Some(Attachment::from(row));
+                loop {
+                    let mut data = vec![0; CHUNK_SIZE];
+                    let read = file.read(&mut data).await?;
+                    if read == 0 && attachment.is_none() {
+                        break;
+                    }
+                    data.truncate(read);
+                    let chunk = AttachmentChunk {
+                        attachment: attachment.take(),
+                        data,
+                    };
+                    tokio::select! {
+                        sent = tx.send(Ok(chunk)) => {
+                            if sent.is_err() {
+                                break;
+                            }
+                        }
+                        _ = expired(deadline) => {
+                            tx.send(Err(Status::deadline_exceeded("Deadline exceeded"))).await.ok();
+                            break;
+                        }
+                    }
+                }
+                Ok(())
+            });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn list_attachments(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<AttachmentsResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = AttachmentsResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Attachment>> {
+            let results: Vec<QueryableAttachment> = attachments::table
+                .filter(attachments::id_task.eq(&id))
+                .order(attachments::created_date_time.asc())
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch attachments.")?;
+            Ok(results.into_iter().map(Attachment::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.attachments = value;
+                response.successful = true;
+                response.message = "Attachments fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_attachment(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<AttachmentResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = AttachmentResponse::default();
+
+        // The stored file is left to the orphan cleanup since other
+        // attachments may share it.
+        let send_request = || -> anyhow::Result<String> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let task: String = attachments::table
+                    .find(&id)
+                    .select(attachments::id_task)
+                    .first(connection)
+                    .context("The attachment doesn't exist.")?;
+                diesel::delete(attachments::table.find(&id)).execute(connection)?;
+                Ok(task)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(task) => {
+                self.changes.id(ChangeKind::TaskUpdated, task);
+                response.attachment = None;
+                response.successful = true;
+                response.message = "Attachment removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn quick_add(
+        &self,
+        request: Request<QuickAddRequest>,
+    ) -> Result<Response<QuickAddResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        request.get_ref().validate()?;
+        let QuickAddRequest { text, default_list } = request.into_inner();
+        let mut response = QuickAddResponse::default();
+
+        let send_request = || -> anyhow::Result<(Task, Vec<Tag>)> {
+            let (task, tagged) = quick_add::create(
+                &mut establish_connection()?,
+                &context,
+                &text,
+                default_list.as_deref(),
+            )?;
+            Ok((task.into(), tagged.into_iter().map(Tag::from).collect()))
+        };
+
+        match retry_busy(send_request) {
+            Ok((task, tagged)) => {
+                self.changes.task(ChangeKind::TaskCreated, task.clone());
+                response.task = Some(with_progress(task).await);
+                response.tags = tagged;
+                response.successful = true;
+                response.message = "Task added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn save_task_as_template(
+        &self,
+        request: Request<SaveTemplateRequest>,
+    ) -> Result<Response<TemplateResponse>, Status> {
+        request.get_ref().validate()?;
+        let SaveTemplateRequest {
+            task_id,
+            name: template_name,
+            due_offset,
+        } = request.into_inner();
+        let mut response = TemplateResponse::default();
+
+        let send_request = || -> anyhow::Result<Template> {
+            templates::save(
+                &mut establish_connection()?,
+                &task_id,
+                template_name.trim().to_string(),
+                due_offset,
+            )
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.template = Some(value);
+                response.successful = true;
+                response.message = "Template saved successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_templates(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<TemplatesResponse>, Status> {
+        let mut response = TemplatesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Template>> {
+            templates::read_all(&mut establish_connection()?).context("Failed to fetch templates.")
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.templates = value;
+                response.successful = true;
+                response.message = "Templates fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_template(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TemplateResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TemplateResponse::default();
+
+        let send_request =
+            || -> anyhow::Result<()> { templates::remove(&mut establish_connection()?, &id) };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.template = None;
+                response.successful = true;
+                response.message = "Template removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn instantiate_template(
+        &self,
+        request: Request<InstantiateTemplateRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        request.get_ref().validate()?;
+        let InstantiateTemplateRequest {
+            template_id,
+            list_id,
+        } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let task = templates::instantiate(
+                &mut establish_connection()?,
+                &template_id,
+                &list_id,
+                context.timezone,
+            )?;
+            Ok(task.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.task(ChangeKind::TaskCreated, value.clone());
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Task created from template successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn save_list_as_template(
+        &self,
+        request: Request<SaveListTemplateRequest>,
+    ) -> Result<Response<ListTemplateResponse>, Status> {
+        request.get_ref().validate()?;
+        let SaveListTemplateRequest {
+            list_id,
+            name: template_name,
+        } = request.into_inner();
+        let mut response = ListTemplateResponse::default();
+
+        let send_request = || -> anyhow::Result<ListTemplate> {
+            list_templates::save(
+                &mut establish_connection()?,
+                &list_id,
+                template_name.trim().to_string(),
+            )
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.template = Some(value);
+                response.successful = true;
+                response.message = "Template saved successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_list_templates(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ListTemplatesResponse>, Status> {
+        let mut response = ListTemplatesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<ListTemplate>> {
+            list_templates::read_all(&mut establish_connection()?)
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.templates = value;
+                response.successful = true;
+                response.message = "Templates fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_list_template(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListTemplateResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = ListTemplateResponse::default();
+
+        let send_request =
+            || -> anyhow::Result<()> { list_templates::remove(&mut establish_connection()?, &id) };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.template = None;
+                response.successful = true;
+                response.message = "Template removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn create_list_from_template(
+        &self,
+        request: Request<CreateListFromTemplateRequest>,
+    ) -> Result<Response<ListWithTasksResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        request.get_ref().validate()?;
+        let CreateListFromTemplateRequest {
+            template_id,
+            name: list_name,
+        } = request.into_inner();
+        let mut response = ListWithTasksResponse::default();
+
+        let send_request = || -> anyhow::Result<(List, Vec<Task>)> {
+            let mut connection = establish_connection()?;
+            let (list, children) = list_templates::instantiate(
+                &mut connection,
+                &template_id,
+                list_name.as_deref().map(str::trim).map(str::to_string),
+                context.timezone,
+            )?;
+            let mut children: Vec<Task> = children.into_iter().map(Task::from).collect();
+            progress::attach(&mut connection, &mut children)?;
+            Ok((list.into(), children))
+        };
+
+        match retry_busy(send_request) {
+            Ok((list, children)) => {
+                self.changes.list(ChangeKind::ListCreated, list.clone());
+                for task in &children {
+                    self.changes.task(ChangeKind::TaskCreated, task.clone());
+                }
+                response.list = Some(list);
+                response.tasks = children;
+                response.successful = true;
+                response.message = "List created from template successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn start_timer(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TimeEntryResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TimeEntryResponse::default();
+
+        let send_request = || -> anyhow::Result<QueryableTimeEntry> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                tasks
+                    .find(&id)
+                    .select(id_task)
+                    .first::<String>(connection)
+                    .context("The task doesn't exist.")?;
+                let running: Option<String> = time_entries::table
+                    .filter(time_entries::id_task.eq(&id))
+                    .filter(time_entries::stopped_at.is_null())
+                    .select(time_entries::id_entry)
+                    .first(connection)
+                    .optional()?;
+                if running.is_some() {
+                    anyhow::bail!("A timer is already running for this task.");
+                }
+                let entry = QueryableTimeEntry::start(&id);
+                diesel::insert_into(time_entries::table)
+                    .values(&entry)
+                    .execute(connection)?;
+                Ok(entry)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.id(ChangeKind::TaskUpdated, id.clone());
+                response.entry = Some(value.into());
+                response.successful = true;
+                response.message = "Timer started successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn stop_timer(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TimeEntryResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TimeEntryResponse::default();
+
+        let send_request = || -> anyhow::Result<QueryableTimeEntry> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let mut entry: QueryableTimeEntry = time_entries::table
+                    .filter(time_entries::id_task.eq(&id))
+                    .filter(time_entries::stopped_at.is_null())
+                    .first(connection)
+                    .optional()?
+                    .context("No timer is running for this task.")?;
+                let now = Utc::now().naive_utc();
+                diesel::update(time_entries::table.find(&entry.id_entry))
+                    .set(time_entries::stopped_at.eq(now))
+                    .execute(connection)?;
+                entry.stopped_at = Some(now);
+                Ok(entry)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.id(ChangeKind::TaskUpdated, id.clone());
+                response.entry = Some(value.into());
+                response.successful = true;
+                response.message = "Timer stopped successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_time_entries(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TimeEntriesResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TimeEntriesResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<QueryableTimeEntry>> {
+            let results = time_entries::table
+                .filter(time_entries::id_task.eq(&id))
+                .order(time_entries::started_at.asc())
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch time entries.")?;
+            Ok(results)
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.total = value
+                    .iter()
+                    .map(|entry| entry.duration().num_seconds())
+                    .sum();
+                response.entries = value.into_iter().map(TimeEntry::from).collect();
+                response.successful = true;
+                response.message = "Time entries fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn start_pomodoro(
+        &self,
+        request: Request<StartPomodoroRequest>,
+    ) -> Result<Response<PomodoroResponse>, Status> {
+        request.get_ref().validate()?;
+        let StartPomodoroRequest { task_id, length } = request.into_inner();
+        let mut response = PomodoroResponse::default();
+
+        let send_request = || -> anyhow::Result<QueryablePomodoro> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                tasks
+                    .find(&task_id)
+                    .select(id_task)
+                    .first::<String>(connection)
+                    .context("The task doesn't exist.")?;
+                let pomodoro = QueryablePomodoro::start(&task_id, length);
+                diesel::insert_into(pomodoros::table)
+                    .values(&pomodoro)
+                    .execute(connection)?;
+                Ok(pomodoro)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.id(ChangeKind::TaskUpdated, task_id.clone());
+                response.pomodoro = Some(value.into());
+                response.successful = true;
+                response.message = "Pomodoro started successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn complete_pomodoro(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<PomodoroResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = PomodoroResponse::default();
+
+        let send_request = || -> anyhow::Result<QueryablePomodoro> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let mut pomodoro: QueryablePomodoro = pomodoros::table
+                    .find(&id)
+                    .first(connection)
+                    .context("The pomodoro doesn't exist.")?;
+                if pomodoro.completed_at.is_some() {
+                    anyhow::bail!("The pomodoro is already completed.");
+                }
+                let now = Utc::now().naive_utc();
+                diesel::update(pomodoros::table.find(&id))
+                    .set(pomodoros::completed_at.eq(now))
+                    .execute(connection)?;
+                pomodoro.completed_at = Some(now);
+                Ok(pomodoro)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes
+                    .id(ChangeKind::TaskUpdated, value.id_task.clone());
+                response.pomodoro = Some(value.into());
+                response.successful = true;
+                response.message = "Pomodoro completed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_pomodoro_stats(
+        &self,
+        request: Request<DateRangeRequest>,
+    ) -> Result<Response<PomodoroStatsResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let DateRangeRequest { start, end } = request.into_inner();
+        let mut response = PomodoroStatsResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<PomodoroDay>> {
+            let start =
+                NaiveDateTime::from_timestamp_opt(start, 0).context("Invalid start date.")?;
+            let end = NaiveDateTime::from_timestamp_opt(end, 0).context("Invalid end date.")?;
+            let completed: Vec<Option<NaiveDateTime>> = pomodoros::table
+                .filter(pomodoros::completed_at.ge(start))
+                .filter(pomodoros::completed_at.lt(end))
+                .select(pomodoros::completed_at)
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch pomodoros.")?;
+
+            let mut days: BTreeMap<NaiveDate, i64> = BTreeMap::new();
+            for completed_at in completed.into_iter().flatten() {
+                *days.entry(context.local_date(completed_at)).or_default() += 1;
+            }
+            Ok(days
+                .into_iter()
+                .map(|(day, count)| PomodoroDay {
+                    date: context.start_of_day(day).timestamp(),
+                    count,
+                })
+                .collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.days = value;
+                response.successful = true;
+                response.message = "Pomodoro statistics fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn create_tag(&self, request: Request<Tag>) -> Result<Response<TagResponse>, Status> {
+        let tag = request.into_inner();
+        tag.validate()?;
+        let mut response = TagResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let tag: QueryableTag = tag.clone().into();
+            diesel::insert_into(tags::table)
+                .values(&tag)
+                .execute(&mut establish_connection()?)
+                .context("Failed to create tag, its name may already be in use.")?;
+            Ok(())
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.tag = Some(tag);
+                response.successful = true;
+                response.message = "Tag added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_all_tags(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<TagsResponse>, Status> {
+        let mut response = TagsResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Tag>> {
+            let results: Vec<QueryableTag> = tags::table
+                .order(tags::name.asc())
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch tags.")?;
+            Ok(results.into_iter().map(Tag::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.tags = value;
+                response.successful = true;
+                response.message = "Tags fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn rename_tag(&self, request: Request<Tag>) -> Result<Response<TagResponse>, Status> {
+        let tag = request.into_inner();
+        tag.validate()?;
+        let mut response = TagResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let updated = diesel::update(tags::table.find(&tag.id))
+                .set(tags::name.eq(&tag.name))
+                .execute(&mut establish_connection()?)
+                .context("Failed to rename tag, its name may already be in use.")?;
+            if updated == 0 {
+                anyhow::bail!("The tag doesn't exist.");
+            }
+            Ok(())
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.tag = Some(tag);
+                response.successful = true;
+                response.message = "Tag renamed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_tag(&self, request: Request<String>) -> Result<Response<TagResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TagResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let tagged: Vec<String> = task_tags::table
+                    .filter(task_tags::id_tag.eq(&id))
+                    .select(task_tags::id_task)
+                    .load(connection)?;
+                let removed = diesel::delete(tags::table.find(&id)).execute(connection)?;
+                if removed == 0 {
+                    anyhow::bail!("The tag doesn't exist.");
+                }
+                Ok(tagged)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(tagged) => {
+                for task in tagged {
+                    self.changes.id(ChangeKind::TaskUpdated, task);
+                }
+                response.tag = None;
+                response.successful = true;
+                response.message = "Tag removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_task_tags(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TagsResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TagsResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Tag>> {
+            let tagged = task_tags::table
+                .filter(task_tags::id_task.eq(&id))
+                .select(task_tags::id_tag);
+            let results: Vec<QueryableTag> = tags::table
+                .filter(tags::id_tag.eq_any(tagged))
+                .order(tags::name.asc())
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch tags.")?;
+            Ok(results.into_iter().map(Tag::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.tags = value;
+                response.successful = true;
+                response.message = "Tags fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn assign_tag(
+        &self,
+        request: Request<TaskTagRequest>,
+    ) -> Result<Response<TagResponse>, Status> {
+        request.get_ref().validate()?;
+        let TaskTagRequest { task_id, tag_id } = request.into_inner();
+        let mut response = TagResponse::default();
+
+        let send_request = || -> anyhow::Result<Tag> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                tasks
+                    .find(&task_id)
+                    .select(id_task)
+                    .first::<String>(connection)
+                    .context("The task doesn't exist.")?;
+                let tag: QueryableTag = tags::table
+                    .find(&tag_id)
+                    .first(connection)
+                    .context("The tag doesn't exist.")?;
+                diesel::insert_or_ignore_into(task_tags::table)
+                    .values((
+                        task_tags::id_task.eq(&task_id),
+                        task_tags::id_tag.eq(&tag_id),
+                    ))
+                    .execute(connection)?;
+                Ok(tag.into())
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.id(ChangeKind::TaskUpdated, task_id);
+                response.tag = Some(value);
+                response.successful = true;
+                response.message = "Tag assigned successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn unassign_tag(
+        &self,
+        request: Request<TaskTagRequest>,
+    ) -> Result<Response<TagResponse>, Status> {
+        request.get_ref().validate()?;
+        let TaskTagRequest { task_id, tag_id } = request.into_inner();
+        let mut response = TagResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            diesel::delete(task_tags::table.find((&task_id, &tag_id)))
+                .execute(&mut establish_connection()?)
+                .context("Failed to unassign tag.")?;
+            Ok(())
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes.id(ChangeKind::TaskUpdated, task_id);
+                response.tag = None;
+                response.successful = true;
+                response.message = "Tag unassigned successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn create_person(
+        &self,
+        request: Request<Person>,
+    ) -> Result<Response<PersonResponse>, Status> {
+        let person = request.into_inner();
+        person.validate()?;
+        let mut response = PersonResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let person: QueryablePerson = person.clone().into();
+            diesel::insert_into(people::table)
+                .values(&person)
+                .execute(&mut establish_connection()?)
+                .context("Failed to add person, their name may already be in use.")?;
+            Ok(())
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.person = Some(person);
+                response.successful = true;
+                response.message = "Person added successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_all_people(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<PeopleResponse>, Status> {
+        let mut response = PeopleResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Person>> {
+            let results: Vec<QueryablePerson> = people::table
+                .order(people::name.asc())
+                .load(&mut establish_connection()?)
+                .context("Failed to fetch people.")?;
+            Ok(results.into_iter().map(Person::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.people = value;
+                response.successful = true;
+                response.message = "People fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn rename_person(
+        &self,
+        request: Request<Person>,
+    ) -> Result<Response<PersonResponse>, Status> {
+        let person = request.into_inner();
+        person.validate()?;
+        let mut response = PersonResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let updated = diesel::update(people::table.find(&person.id))
+                .set(people::name.eq(&person.name))
+                .execute(&mut establish_connection()?)
+                .context("Failed to rename person, their name may already be in use.")?;
+            if updated == 0 {
+                anyhow::bail!("The person doesn't exist.");
+            }
+            Ok(())
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                response.person = Some(person);
+                response.successful = true;
+                response.message = "Person renamed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn delete_person(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<PersonResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = PersonResponse::default();
+
+        // Returns the tasks that were assigned to them.
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let assigned: Vec<String> = tasks
+                    .filter(assigned_to.eq(&id))
+                    .select(id_task)
+                    .load(connection)?;
+                let removed = diesel::delete(people::table.find(&id)).execute(connection)?;
+                if removed == 0 {
+                    anyhow::bail!("The person doesn't exist.");
+                }
+                Ok(assigned)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(assigned) => {
+                for task in assigned {
+                    self.changes.id(ChangeKind::TaskUpdated, task);
+                }
+                response.person = None;
+                response.successful = true;
+                response.message = "Person removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn tag_tasks(
+        &self,
+        request: Request<TagTasksRequest>,
+    ) -> Result<Response<CountResponse>, Status> {
+        request.get_ref().validate()?;
+        let TagTasksRequest { tag_id, task_ids } = request.into_inner();
+        let mut response = CountResponse::default();
+
+        // Returns the tasks that didn't have the tag yet.
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                tags::table
+                    .find(&tag_id)
+                    .select(tags::id_tag)
+                    .first::<String>(connection)
+                    .context("The tag doesn't exist.")?;
+                let tagged = task_tags::table
+                    .filter(task_tags::id_tag.eq(&tag_id))
+                    .select(task_tags::id_task);
+                let untagged: Vec<String> = tasks
+                    .filter(id_task.eq_any(&task_ids))
+                    .filter(id_task.ne_all(tagged))
+                    .select(id_task)
+                    .load(connection)?;
+                let rows: Vec<_> = untagged
+                    .iter()
+                    .map(|task| (task_tags::id_task.eq(task), task_tags::id_tag.eq(&tag_id)))
+                    .collect();
+                diesel::insert_into(task_tags::table)
+                    .values(&rows)
+                    .execute(connection)
+                    .context("Failed to tag tasks.")?;
+                Ok(untagged)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.count = value.len() as i64;
+                for task in value {
+                    self.changes.id(ChangeKind::TaskUpdated, task);
+                }
+                response.successful = true;
+                response.message = "Tasks tagged successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn untag_tasks(
+        &self,
+        request: Request<TagTasksRequest>,
+    ) -> Result<Response<CountResponse>, Status> {
+        request.get_ref().validate()?;
+        let TagTasksRequest { tag_id, task_ids } = request.into_inner();
+        let mut response = CountResponse::default();
+
+        // Returns the tasks that had the tag.
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let tagged = task_tags::table
+                    .filter(task_tags::id_tag.eq(&tag_id))
+                    .filter(task_tags::id_task.eq_any(&task_ids));
+                let untagged: Vec<String> =
+                    tagged.clone().select(task_tags::id_task).load(connection)?;
+                diesel::delete(tagged)
+                    .execute(connection)
+                    .context("Failed to untag tasks.")?;
+                Ok(untagged)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.count = value.len() as i64;
+                for task in value {
+                    self.changes.id(ChangeKind::TaskUpdated, task);
+                }
+                response.successful = true;
+                response.message = "Tasks untagged successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn clear_completed_tasks(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<CountResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = CountResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let completed: Vec<String> = tasks
+                    .filter(task_archived.eq(false))
+                    .filter(parent_list.eq(&id))
+                    .filter(status.eq(TaskStatus::Completed as i32))
+                    .select(id_task)
+                    .load(connection)?;
+                diesel::delete(tasks.filter(id_task.eq_any(&completed)))
+                    .execute(connection)
+                    .context("Failed to remove completed tasks.")?;
+
+                let removed: Vec<QueryableTombstone> = completed
+                    .iter()
+                    .map(|id| QueryableTombstone::new(id.clone(), TASK_ENTITY))
+                    .collect();
+                diesel::replace_into(tombstones::table)
+                    .values(&removed)
+                    .execute(connection)?;
+                Ok(completed)
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(ids) => {
+                response.count = ids.len() as i64;
+                for id in ids {
+                    self.changes.id(ChangeKind::TaskDeleted, id);
+                }
+                response.successful = true;
+                response.message = "Completed tasks removed successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn set_all_tasks_completed(
+        &self,
+        request: Request<SetAllTasksCompletedRequest>,
+    ) -> Result<Response<CountResponse>, Status> {
+        request.get_ref().validate()?;
+        let SetAllTasksCompletedRequest { list_id, completed } = request.into_inner();
+        let mut response = CountResponse::default();
+
+        // Returns the updated tasks and the next occurrences of the repeating
+        // ones that were completed.
+        let send_request = || -> anyhow::Result<(Vec<Task>, Vec<Task>)> {
+            let to = if completed {
+                TaskStatus::Completed
+            } else {
+                TaskStatus::NotStarted
+            };
+            let now = Utc::now().naive_utc();
+            let mut connection = establish_connection()?;
+            let (updated, spawned) =
+                connection.transaction::<_, anyhow::Error, _>(|connection| {
+                    let mut query = tasks
+                        .filter(task_archived.eq(false))
+                        .filter(parent_list.eq(&list_id))
+                        .into_boxed();
+                    // Completing covers every task that isn't completed, with
+                    // statuses added after this version included.
+                    query = if completed {
+                        query.filter(status.ne(TaskStatus::Completed as i32))
+                    } else {
+                        query.filter(status.eq(TaskStatus::Completed as i32))
+                    };
+                    let ids: Vec<String> = query.select(id_task).load(connection)?;
+                    diesel::update(tasks.filter(id_task.eq_any(&ids)))
+                        .set((
+                            status.eq(to as i32),
+                            completed_on.eq(completed.then_some(now)),
+                            last_modified_date_time.eq(now),
+                        ))
+                        .execute(connection)
+                        .context("Failed to update tasks.")?;
+                    let updated: Vec<QueryableTask> =
+                        tasks.filter(id_task.eq_any(&ids)).load(connection)?;
+                    let mut spawned = vec![];
+                    if completed {
+                        for task in &updated {
+                            spawned.extend(completion::completed(connection, task)?);
+                        }
+                    }
+                    Ok((updated, spawned))
+                })?;
+            Ok((
+                updated.into_iter().map(Task::from).collect(),
+                spawned.into_iter().map(Task::from).collect(),
+            ))
+        };
+
+        match retry_busy(send_request) {
+            Ok((value, spawned)) => {
+                response.count = value.len() as i64;
+                for task in value {
+                    self.changes.task(ChangeKind::TaskUpdated, task);
+                }
+                for task in spawned {
+                    self.changes.task(ChangeKind::TaskCreated, task);
+                }
+                response.successful = true;
+                response.message = "Tasks updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn duplicate_task(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let mut connection = establish_connection()?;
+            let original: QueryableTask = tasks
+                .find(&id)
+                .first(&mut connection)
+                .context("Failed to fetch task.")?;
+            let mut copy = original.duplicate();
+            copy.position = next_task_position(&mut connection, &copy.parent_list)?;
+
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                diesel::insert_into(tasks)
+                    .values(&copy)
+                    .execute(connection)?;
+                copy_task_details(
+                    connection,
+                    &[(original.id_task.clone(), copy.id_task.clone())],
+                )
+            })?;
+
+            Ok(copy.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.task(ChangeKind::TaskCreated, value.clone());
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Task duplicated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn move_task(
+        &self,
+        request: Request<MoveTaskRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let MoveTaskRequest {
+            task_id,
+            target_list_id,
+        } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let mut connection = establish_connection()?;
+            let moved = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let exists: i64 = lists
+                    .filter(id_list.eq(&target_list_id))
+                    .count()
+                    .get_result(connection)?;
+                if exists == 0 && !inbox::is_inbox(&target_list_id) {
+                    anyhow::bail!("The target list doesn't exist.");
+                }
+
+                let last = next_task_position(connection, &target_list_id)?;
+                let updated = diesel::update(tasks.filter(id_task.eq(&task_id)))
+                    .set((
+                        parent_list.eq(&target_list_id),
+                        task_position.eq(last),
+                        last_modified_date_time.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(connection)
+                    .context("Failed to move task.")?;
+                if updated == 0 {
+                    anyhow::bail!("The task doesn't exist.");
+                }
+
+                let task: QueryableTask = tasks.find(&task_id).first(connection)?;
+                Ok(task)
+            })?;
+            Ok(moved.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                self.changes.task(ChangeKind::TaskUpdated, value.clone());
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Task moved successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn merge_tasks(
+        &self,
+        request: Request<MergeTasksRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let MergeTasksRequest {
+            primary,
+            duplicates,
+        } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<(Task, Vec<String>)> {
+            let mut connection = establish_connection()?;
+            let (merged, ids) = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let mut merged: QueryableTask = tasks
+                    .find(&primary)
+                    .first(connection)
+                    .context("Failed to fetch primary task.")?;
+                let others: Vec<QueryableTask> = tasks
+                    .filter(id_task.eq_any(&duplicates))
+                    .filter(id_task.ne(&primary))
+                    .load(connection)
+                    .context("Failed to fetch duplicate tasks.")?;
+
+                for duplicate in &others {
+                    merged.merge(duplicate);
+                }
+
+                diesel::update(tasks.filter(id_task.eq(&merged.id_task)))
+                    .set((
+                        body.eq(&merged.body),
+                        notes_format.eq(merged.notes_format),
+                        estimate_minutes.eq(merged.estimate_minutes),
+                        duration_minutes.eq(merged.duration_minutes),
+                        task_color.eq(&merged.color),
+                        emoji.eq(&merged.emoji),
+                        url.eq(&merged.url),
+                        assigned_to.eq(&merged.assigned_to),
+                        latitude.eq(merged.latitude),
+                        longitude.eq(merged.longitude),
+                        place_name.eq(&merged.place_name),
+                        importance.eq(merged.importance),
+                        favorite.eq(merged.favorite),
+                        task_pinned.eq(merged.pinned),
+                        is_reminder_on.eq(merged.is_reminder_on),
+                        due_date.eq(merged.due_date),
+                        start_date.eq(merged.start_date),
+                        reminder_date.eq(merged.reminder_date),
+                        created_date_time.eq(merged.created_date_time),
+                        last_modified_date_time.eq(merged.last_modified_date_time),
+                    ))
+                    .execute(connection)
+                    .context("Failed to update primary task.")?;
+
+                let ids: Vec<String> = others.into_iter().map(|t| t.id_task).collect();
+                let tagged: Vec<String> = task_tags::table
+                    .filter(task_tags::id_task.eq_any(&ids))
+                    .select(task_tags::id_tag)
+                    .load(connection)?;
+                let tagged: Vec<_> = tagged
+                    .iter()
+                    .map(|tag| {
+                        (
+                            task_tags::id_task.eq(&merged.id_task),
+                            task_tags::id_tag.eq(tag),
+                        )
+                    })
+                    .collect();
+                diesel::insert_or_ignore_into(task_tags::table)
+                    .values(&tagged)
+                    .execute(connection)?;
+                diesel::update(comments::table.filter(comments::id_task.eq_any(&ids)))
+                    .set(comments::id_task.eq(&merged.id_task))
+                    .execute(connection)?;
+                diesel::update(attachments::table.filter(attachments::id_task.eq_any(&ids)))
+                    .set(attachments::id_task.eq(&merged.id_task))
+                    .execute(connection)?;
+                // The primary may have a timer running already, and only one
+                // can run per task.
+                diesel::update(
+                    time_entries::table
+                        .filter(time_entries::id_task.eq_any(&ids))
+                        .filter(time_entries::stopped_at.is_null()),
+                )
+                .set(time_entries::stopped_at.eq(Utc::now().naive_utc()))
+                .execute(connection)?;
+                diesel::update(time_entries::table.filter(time_entries::id_task.eq_any(&ids)))
+                    .set(time_entries::id_task.eq(&merged.id_task))
+                    .execute(connection)?;
+                diesel::update(pomodoros::table.filter(pomodoros::id_task.eq_any(&ids)))
+                    .set(pomodoros::id_task.eq(&merged.id_task))
+                    .execute(connection)?;
+                dependencies::reassign(connection, &ids, &merged.id_task)?;
+                let offset = next_subtask_position(connection, &merged.id_task)?;
+                diesel::update(subtasks::table.filter(subtasks::id_task.eq_any(&ids)))
+                    .set((
+                        subtasks::id_task.eq(&merged.id_task),
+                        subtasks::position.eq(subtasks::position + offset),
+                    ))
+                    .execute(connection)?;
+                diesel::delete(tasks.filter(id_task.eq_any(&ids))).execute(connection)?;
+
+                let removed: Vec<QueryableTombstone> = ids
+                    .iter()
+                    .map(|id| QueryableTombstone::new(id.clone(), TASK_ENTITY))
+                    .collect();
+                diesel::replace_into(tombstones::table)
+                    .values(&removed)
+                    .execute(connection)?;
+
+                Ok((merged, ids))
+            })?;
+            Ok((merged.into(), ids))
+        };
+
+        match retry_busy(send_request) {
+            Ok((value, ids)) => {
+                for id in ids {
+                    self.changes.id(ChangeKind::TaskDeleted, id);
+                }
+                self.changes.task(ChangeKind::TaskUpdated, value.clone());
+                response.task = Some(with_progress(value).await);
+                response.successful = true;
+                response.message = "Tasks merged successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn reorder_task(
+        &self,
+        request: Request<ReorderTaskRequest>,
+    ) -> Result<Response<ReorderTaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let ReorderTaskRequest {
+            task_id,
+            after_task_id,
+        } = request.into_inner();
+        let mut response = ReorderTaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<Task>> {
+            let mut connection = establish_connection()?;
+            let changed = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                reorder_task(connection, &task_id, after_task_id.as_deref())
+            })?;
+            let mut changed: Vec<Task> = changed.into_iter().map(Task::from).collect();
+            progress::attach(&mut connection, &mut changed)?;
+            Ok(changed)
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                for task in &value {
+                    self.changes.task(ChangeKind::TaskUpdated, task.clone());
+                }
+                response.tasks = value;
+                response.successful = true;
+                response.message = "Task reordered successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type ReadAllListsStream = ReceiverStream<Result<ListResponse, Status>>;
+
+    async fn read_all_lists(
+        &self,
+        request: Request<ReadAllListsRequest>,
+    ) -> Result<Response<Self::ReadAllListsStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let include_archived = request.into_inner().include_archived;
+        let provider_id = self.id.clone();
+        let send_request = move || -> anyhow::Result<Vec<List>> {
+            let mut connection = establish_connection()?;
+            let mut query = lists
+                .order((list_pinned.desc(), list_position.asc()))
+                .into_boxed();
+            if !include_archived {
+                query = query.filter(list_archived.eq(false));
+            }
+            let mut results = query.load::<QueryableList>(&mut connection)?;
+            if inbox::has_tasks(&mut connection)? {
+                results.insert(0, inbox::list(&provider_id, &context.locale));
+            }
+
+            let results: Vec<List> = results.iter().map(|t| t.clone().into()).collect();
+            Ok(results)
+        };
+
+        Ok(Response::new(self.stream_lists(
+            "read_all_lists",
+            options,
+            send_request,
+        )))
+    }
+
+    async fn read_all_list_ids(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ListIdResponse>, Status> {
+        let send_request = || -> anyhow::Result<Vec<String>> {
+            let mut connection = establish_connection()?;
+            let mut result: Vec<String> = lists
+                .select(id_list)
+                .load::<String>(&mut connection)
+                .context("Failed to fetch list of tasks.")?;
+            if inbox::has_tasks(&mut connection)? {
+                result.insert(0, inbox::INBOX_ID.to_string());
+            }
+            Ok(result)
+        };
+
+        let mut response = ListIdResponse {
+            successful: true,
+            message: String::new(),
+            lists: vec![],
+        };
+
+        match retry_busy(send_request) {
+            Ok(result) => {
+                response.successful = true;
+                response.lists = result;
+            }
+            Err(_) => response.message = "Failed to fetch list of tasks".to_string(),
+        }
+
+        Ok(Response::new(response))
+    }
+
+    async fn read_list_counters(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<ListCountersResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let mut response = ListCountersResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<ListCounters>> {
+            list_counters(&mut establish_connection()?, &context, None)
+        };
+
+        match retry_busy(send_request) {
+            Ok(counters) => {
+                response.successful = true;
+                response.message = "List counters fetched successfully.".to_string();
+                response.counters = counters.into_iter().map(ListCounter::from).collect();
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_list_statistics(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListStatisticsResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let id = request.into_inner();
+        let mut response = ListStatisticsResponse::default();
+
+        let send_request = || -> anyhow::Result<ListStatistics> {
+            statistics::read(&mut establish_connection()?, &context, &id)
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.statistics = Some(value);
+                response.successful = true;
+                response.message = "List statistics fetched successfully.".to_string();
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type ReadAllListsWithCountsStream = ReceiverStream<Result<ListWithCount, Status>>;
+
+    async fn read_all_lists_with_counts(
+        &self,
+        request: Request<ReadAllListsRequest>,
+    ) -> Result<Response<Self::ReadAllListsWithCountsStream>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let StreamOptions { deadline, .. } = self.stream_options(request.metadata());
+        let include_archived = request.into_inner().include_archived;
+        let provider_id = self.id.clone();
+        let (tx, rx) = self.channel();
+
+        let send_request = move || -> anyhow::Result<Vec<(QueryableList, ListCounters)>> {
+            lists_with_counters(
+                &mut establish_connection()?,
+                &context,
+                &provider_id,
+                include_archived,
+            )
+        };
+
+        let method = "read_all_lists_with_counts";
+        self.supervisor.spawn_stream(method, async move {
+            let responses = match fetch(&tx, deadline, send_request).await? {
+                Some(Ok(value)) => value
+                    .into_iter()
+                    .map(|(list, counter)| ListWithCount {
+                        successful: true,
+                        message: "List fetched successfully.".to_string(),
+                        list: Some(list.into()),
+                        counter: Some(counter.into()),
+                    })
+                    .collect(),
+                Some(Err(err)) => vec![ListWithCount {
+                    successful: false,
+                    message: err.to_string(),
+                    ..Default::default()
+                }],
+                None => return Ok(()),
+            };
+            send_all(method, &tx, deadline, responses).await;
+            Ok(())
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn list_available_icons(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<IconsResponse>, Status> {
+        let mut response = IconsResponse::default();
+
+        // The first call walks the icon themes on disk.
+        match tokio::task::spawn_blocking(icons::available).await {
+            Ok(value) => {
+                response.icons = value;
+                response.successful = true;
+                response.message = "Icons fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn create_list(&self, request: Request<List>) -> Result<Response<ListResponse>, Status> {
+        let key = idempotency_key(request.metadata());
+        let list = request.into_inner();
+        list.validate()?;
+        let hash = payload_hash(&list);
+        if let Some(value) = list.icon.as_deref().filter(|value| !installed_icon(value)) {
+            return Err(Status::invalid_argument(format!(
+                "`icon` must be an emoji or an installed icon, got '{value}'"
+            )));
+        }
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<(List, bool)> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                if let Some(key) = &key {
+                    if let Some(id) = replayed(connection, key, "create_list", &hash)? {
+                        let original: QueryableList = lists
+                            .find(id)
+                            .first(connection)
+                            .context("The list created by this request no longer exists.")?;
+                        return Ok((original.into(), false));
+                    }
+                }
+
+                let mut list: QueryableList = list.clone().into();
+                list.position = next_list_position(connection)?;
+                if let Some(group) = &list.id_group {
+                    groups::exists(connection, group)?;
+                }
+
+                diesel::insert_into(lists)
+                    .values(&list)
+                    .execute(connection)?;
+                if let Some(key) = &key {
+                    remember(connection, key, "create_list", &hash, &list.id_list)?;
+                }
+
+                Ok((list.into(), true))
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok((list, created)) => {
+                if created {
+                    self.changes.list(ChangeKind::ListCreated, list);
+                }
+                response.list = None;
+                response.successful = true;
+                response.message = "List added succesfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_list(&self, request: Request<String>) -> Result<Response<ListResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let user_action = is_user_action(request.metadata());
+        let id = request.into_inner();
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<List> {
+            let mut connection = establish_connection()?;
+            let result: QueryableList = if inbox::is_inbox(&id) {
+                inbox::list(&self.id, &context.locale)
+            } else {
+                lists.find(&id).first(&mut connection)?
+            };
+            if user_action {
+                if let Err(err) = record_access(&mut connection, &id, LIST_ENTITY) {
+                    tracing::warn!("Failed to record list access: {err}");
+                }
+            }
+            Ok(result.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.list = Some(value);
+                response.successful = true;
+                response.message = "List fetched succesfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn read_list_with_tasks(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListWithTasksResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let user_action = is_user_action(request.metadata());
+        let id = request.into_inner();
+        let mut response = ListWithTasksResponse::default();
+
+        let send_request = || -> anyhow::Result<(List, Vec<Task>)> {
+            let mut connection = establish_connection()?;
+            let (list, children) = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let list: QueryableList = if inbox::is_inbox(&id) {
+                    inbox::list(&self.id, &context.locale)
+                } else {
+                    lists
+                        .find(&id)
+                        .first(connection)
+                        .context("Failed to fetch list.")?
+                };
+                let children: Vec<QueryableTask> = tasks
+                    .filter(task_archived.eq(false))
+                    .filter(parent_list.eq(&id))
+                    .order((
+                        task_pinned.desc(),
+                        task_position.asc(),
+                        created_date_time.asc(),
+                    ))
+                    .load(connection)
+                    .context("Failed to fetch list of tasks.")?;
+                Ok((list, children))
+            })?;
+            if user_action {
+                if let Err(err) = record_access(&mut connection, &id, LIST_ENTITY) {
+                    tracing::warn!("Failed to record list access: {err}");
+                }
+            }
+            let mut children: Vec<Task> = children.into_iter().map(Task::from).collect();
+            progress::attach(&mut connection, &mut children)?;
+            Ok((list.into(), children))
+        };
+
+        match retry_busy(send_request) {
+            Ok((list, children)) => {
+                response.list = Some(list);
+                response.tasks = children;
+                response.successful = true;
+                response.message = "List fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    type DuplicateListStream = ReceiverStream<Result<DuplicateListResponse, Status>>;
+
+    async fn duplicate_list(
+        &self,
+        request: Request<DuplicateListRequest>,
+    ) -> Result<Response<Self::DuplicateListStream>, Status> {
+        request.get_ref().validate()?;
+        let options = self.stream_options(request.metadata());
+        let (tx, rx) = self.channel();
+        let DuplicateListRequest { list_id, new_name } = request.into_inner();
+
+        let send_request = move || -> anyhow::Result<(QueryableList, Vec<String>)> {
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let original: QueryableList = lists
+                    .find(&list_id)
+                    .first(connection)
+                    .context("Failed to fetch list.")?;
+                let mut list = original.duplicate(&new_name);
+                list.position = next_list_position(connection)?;
+                diesel::insert_into(lists)
+                    .values(&list)
+                    .execute(connection)?;
+
+                let originals: Vec<QueryableTask> = tasks
+                    .filter(parent_list.eq(&list_id))
+                    .load::<QueryableTask>(connection)?;
+                let copies: Vec<QueryableTask> = originals
+                    .iter()
+                    .map(|task| task.copy_to_list(&list.id_list))
+                    .collect();
+                diesel::insert_into(tasks)
+                    .values(&copies)
+                    .execute(connection)?;
+                let pairs: Vec<(String, String)> = originals
+                    .into_iter()
+                    .zip(&copies)
+                    .map(|(original, copy)| (original.id_task, copy.id_task.clone()))
+                    .collect();
+                copy_task_details(connection, &pairs)?;
+
+                Ok((list, copies.into_iter().map(|t| t.id_task).collect()))
+            })
+        };
+
+        let changes = self.changes.clone();
+        self.supervisor.spawn_stream("duplicate_list", async move {
+            let responses = match fetch(&tx, options.deadline, send_request).await? {
+                Some(Ok((list, ids))) => {
+                    changes.list(ChangeKind::ListCreated, list.clone().into());
+                    for id in &ids {
+                        changes.id(ChangeKind::TaskCreated, id.clone());
+                    }
+                    let mut responses = vec![DuplicateListResponse {
+                        successful: true,
+                        message: "List duplicated successfully.".to_string(),
+                        list: Some(list.into()),
+                        task_id: None,
+                    }];
+                    responses.extend(ids.into_iter().map(|id| DuplicateListResponse {
+                        successful: true,
+                        message: "Task duplicated successfully.".to_string(),
+                        list: None,
+                        task_id: Some(id),
+                    }));
+                    responses
+                }
+                Some(Err(err)) => vec![DuplicateListResponse {
+                    successful: false,
+                    message: err.to_string(),
+                    list: None,
+                    task_id: None,
+                }],
+                None => return Ok(()),
+            };
+            send_all("duplicate_list", &tx, options.deadline, responses).await;
+            Ok(())
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    async fn merge_lists(
+        &self,
+        request: Request<MergeListsRequest>,
+    ) -> Result<Response<MergeListsResponse>, Status> {
+        request.get_ref().validate()?;
+        let MergeListsRequest {
+            source_id,
+            target_id,
+        } = request.into_inner();
+        let mut response = MergeListsResponse::default();
+
+        let send_request = || -> anyhow::Result<(List, i64)> {
+            if source_id == target_id {
+                anyhow::bail!("A list can't be merged into itself.");
+            }
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                check_unprotected(connection, &source_id)?;
+                let target: QueryableList = lists
+                    .find(&target_id)
+                    .first(connection)
+                    .context("The target list doesn't exist.")?;
+
+                let offset = next_task_position(connection, &target_id)?;
+                let moved = diesel::update(tasks.filter(parent_list.eq(&source_id)))
+                    .set((
+                        parent_list.eq(&target_id),
+                        task_position.eq(task_position + offset),
+                        last_modified_date_time.eq(Utc::now().naive_utc()),
+                    ))
+                    .execute(connection)
+                    .context("Failed to move tasks.")?;
+
+                let removed =
+                    diesel::delete(lists.filter(id_list.eq(&source_id))).execute(connection)?;
+                if removed == 0 {
+                    anyhow::bail!("The source list doesn't exist.");
+                }
+                diesel::replace_into(tombstones::table)
+                    .values(&QueryableTombstone::new(source_id.clone(), LIST_ENTITY))
+                    .execute(connection)?;
+
+                Ok((target.into(), moved as i64))
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok((list, moved)) => {
+                self.changes.id(ChangeKind::ListDeleted, source_id);
+                self.changes.list(ChangeKind::ListUpdated, list.clone());
+                response.list = Some(list);
+                response.moved_tasks = moved;
+                response.successful = true;
+                response.message = "Lists merged successfully.".to_string()
+            }
+            Err(err) if err.is::<ProtectedList>() => {
+                return Err(Status::failed_precondition(err.to_string()))
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn update_list(&self, request: Request<List>) -> Result<Response<ListResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let list = request.into_inner();
+        list.validate()?;
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<()> {
+            let list: QueryableList = list.clone().into();
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                // Lists keep icons that were uninstalled since they were picked.
+                let previous_icon: Option<String> = lists
+                    .find(&list.id_list)
+                    .select(icon_name)
+                    .first(connection)
+                    .context("The list doesn't exist.")?;
+                if let Some(value) = list.icon_name.as_deref().filter(|value| {
+                    previous_icon.as_deref() != Some(*value) && !installed_icon(value)
+                }) {
+                    anyhow::bail!("`icon` must be an emoji or an installed icon, got '{value}'");
+                }
+
+                // Archiving and pinning have calls of their own.
+                let changes = ListChanges {
+                    archived: None,
+                    pinned: None,
+                    ..ListChanges::new(&list, context.api_version)
+                };
+                if let Some(Some(group)) = &changes.id_group {
+                    groups::exists(connection, group)?;
+                }
+                diesel::update(lists.find(&list.id_list))
+                    .set(&changes)
+                    .execute(connection)
+                    .context("Failed to update list.")?;
+                Ok(())
+            })
+        };
+
+        match retry_busy(send_request) {
+            Ok(()) => {
+                self.changes.list(ChangeKind::ListUpdated, list);
+                response.list = None;
+                response.successful = true;
+                response.message = "List updated succesfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn reorder_lists(
+        &self,
+        request: Request<ReorderListsRequest>,
+    ) -> Result<Response<ReorderListsResponse>, Status> {
+        request.get_ref().validate()?;
+        let ordered_ids = request.into_inner().ordered_ids;
+        let mut response = ReorderListsResponse::default();
+
+        let send_request = || -> anyhow::Result<Vec<List>> {
+            let mut connection = establish_connection()?;
+            let ordered = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                reorder_lists(connection, &ordered_ids)
+            })?;
+            Ok(ordered.into_iter().map(List::from).collect())
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                for list in &value {
+                    self.changes.list(ChangeKind::ListUpdated, list.clone());
+                }
+                response.lists = value;
+                response.successful = true;
+                response.message = "Lists reordered successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn archive_list(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListResponse>, Status> {
+        let id = request.into_inner();
+        Ok(Response::new(self.set_archived(id, true)))
+    }
+
+    async fn unarchive_list(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListResponse>, Status> {
+        let id = request.into_inner();
+        Ok(Response::new(self.set_archived(id, false)))
+    }
+
+    async fn archive_task(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        let id = request.into_inner();
+        Ok(Response::new(self.set_task_archived(id, true)))
+    }
+
+    async fn unarchive_task(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        let id = request.into_inner();
+        Ok(Response::new(self.set_task_archived(id, false)))
+    }
+
+    async fn set_task_pinned(
+        &self,
+        request: Request<SetTaskPinnedRequest>,
+    ) -> Result<Response<TaskResponse>, Status> {
+        request.get_ref().validate()?;
+        let SetTaskPinnedRequest {
+            task_id,
+            pinned: value,
+        } = request.into_inner();
+        let mut response = TaskResponse::default();
+
+        let send_request = || -> anyhow::Result<Task> {
+            let mut connection = establish_connection()?;
+            let updated = diesel::update(tasks.filter(id_task.eq(&task_id)))
+                .set((
+                    task_pinned.eq(value),
+                    last_modified_date_time.eq(Utc::now().naive_utc()),
+                ))
+                .execute(&mut connection)
+                .context("Failed to update task.")?;
+            if updated == 0 {
+                anyhow::bail!("The task doesn't exist.");
+            }
+            let task: QueryableTask = tasks.find(&task_id).first(&mut connection)?;
+            Ok(task.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(task) => {
+                self.changes.task(ChangeKind::TaskUpdated, task.clone());
+                response.task = Some(with_progress(task).await);
+                response.successful = true;
+                response.message = "Task updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn set_list_pinned(
+        &self,
+        request: Request<SetListPinnedRequest>,
+    ) -> Result<Response<ListResponse>, Status> {
+        request.get_ref().validate()?;
+        let SetListPinnedRequest {
+            list_id,
+            pinned: value,
+        } = request.into_inner();
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<List> {
+            let mut connection = establish_connection()?;
+            let updated = diesel::update(lists.filter(id_list.eq(&list_id)))
+                .set(list_pinned.eq(value))
+                .execute(&mut connection)
+                .context("Failed to update list.")?;
+            if updated == 0 {
+                anyhow::bail!("The list doesn't exist.");
+            }
+            let list: QueryableList = lists.find(&list_id).first(&mut connection)?;
+            Ok(list.into())
+        };
+
+        match retry_busy(send_request) {
+            Ok(list) => {
+                self.changes.list(ChangeKind::ListUpdated, list.clone());
+                response.list = Some(list);
+                response.successful = true;
+                response.message = "List updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn set_list_protected(
+        &self,
+        request: Request<SetListProtectedRequest>,
+    ) -> Result<Response<ListResponse>, Status> {
+        request.get_ref().validate()?;
+        let SetListProtectedRequest {
+            list_id,
+            protected: value,
+        } = request.into_inner();
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<List> {
+            let mut connection = establish_connection()?;
+            let updated = diesel::update(lists.filter(id_list.eq(&list_id)))
+                .set(protected.eq(value))
+                .execute(&mut connection)
+                .context("Failed to update list.")?;
+            if updated == 0 {
+                anyhow::bail!("The list doesn't exist.");
             }
-        });
+            let list: QueryableList = lists.find(&list_id).first(&mut connection)?;
+            Ok(list.into())
+        };
 
-        Ok(Response::new(ReceiverStream::new(rx)))
+        match retry_busy(send_request) {
+            Ok(list) => {
+                self.changes.list(ChangeKind::ListUpdated, list.clone());
+                response.list = Some(list);
+                response.successful = true;
+                response.message = "List updated successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
     }
 
-    type ReadTasksFromListStream = ReceiverStream<Result<TaskResponse, Status>>;
-
-    async fn read_tasks_from_list(
+    async fn export_list_bundle(
         &self,
         request: Request<String>,
-    ) -> Result<Response<Self::ReadTasksFromListStream>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let (tx, rx) = tokio::sync::mpsc::channel(4);
+    ) -> Result<Response<BundleResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
         let id = request.into_inner();
+        let mut response = BundleResponse::default();
 
-        let send_request = || -> anyhow::Result<Vec<Task>> {
-            let result: Vec<QueryableTask> = tasks
-                .filter(parent_list.eq(id))
-                .load::<QueryableTask>(&mut establish_connection()?)
-                .context("Failed to fetch list of tasks.")?;
-            let results: Vec<Task> = result.iter().map(|t| t.clone().into()).collect();
-            Ok(results)
+        let send_request = || -> anyhow::Result<String> {
+            bundle::export(&mut establish_connection()?, &id, &self.id, &context.locale)
         };
 
-        let mut response = TaskResponse::default();
-
-        tokio::spawn(async move {
-            match send_request() {
-                Ok(value) => {
-                    response.successful = true;
-                    for task in &value[..] {
-                        let response = TaskResponse {
-                            successful: true,
-                            message: "Task fetched successfully".to_string(),
-                            task: Some(task.clone()),
-                        };
-                        tx.send(Ok(response)).await.unwrap();
-                    }
-                }
-                Err(err) => response.message = err.to_string(),
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.bundle = value;
+                response.successful = true;
+                response.message = "List exported successfully.".to_string()
             }
-        });
-
-        Ok(Response::new(ReceiverStream::new(rx)))
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
     }
 
-    async fn read_task_ids_from_list(
+    async fn export_list_ical(
         &self,
         request: Request<String>,
-    ) -> Result<Response<TaskIdResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let send_request = || -> anyhow::Result<Vec<String>> {
-            let result: Vec<String> = tasks
-                .select(id_task)
-                .filter(parent_list.eq(request.into_inner()))
-                .load::<String>(&mut establish_connection()?)
-                .context("Failed to fetch list of tasks.")?;
-            Ok(result)
-        };
+    ) -> Result<Response<IcalResponse>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let id = request.into_inner();
+        let mut response = IcalResponse::default();
 
-        let mut response = TaskIdResponse {
-            successful: true,
-            message: String::new(),
-            tasks: vec![],
+        let send_request = || -> anyhow::Result<String> {
+            ical::export(&mut establish_connection()?, &id, &self.id, &context.locale)
         };
 
-        match send_request() {
-            Ok(result) => {
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.calendar = value;
                 response.successful = true;
-                response.tasks = result;
+                response.message = "List exported successfully.".to_string()
             }
-            Err(_) => response.message = "Failed to fetch list of tasks".to_string(),
+            Err(err) => response.message = err.to_string(),
         }
+        Ok(Response::new(response))
+    }
+
+    async fn import_list_bundle(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListResponse>, Status> {
+        let contents = request.into_inner();
+        let mut response = ListResponse::default();
+
+        let send_request = || -> anyhow::Result<(List, Vec<Task>)> {
+            let (list, children) = bundle::import(&mut establish_connection()?, &contents)?;
+            Ok((list.into(), children.into_iter().map(Task::from).collect()))
+        };
 
+        match retry_busy(send_request) {
+            Ok((list, children)) => {
+                self.changes.list(ChangeKind::ListCreated, list.clone());
+                for task in children {
+                    self.changes.task(ChangeKind::TaskCreated, task);
+                }
+                response.list = Some(list);
+                response.successful = true;
+                response.message = "List imported successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
         Ok(Response::new(response))
     }
 
-    async fn read_task_count_from_list(
+    async fn get_list_settings(
         &self,
         request: Request<String>,
-    ) -> Result<Response<CountResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
+    ) -> Result<Response<ListSettingsResponse>, Status> {
         let id = request.into_inner();
-        let mut response = CountResponse::default();
+        let mut response = ListSettingsResponse::default();
 
-        let send_request = || -> anyhow::Result<i64> {
-            let count: i64 = tasks
-                .filter(id_task.eq(id))
-                .count()
-                .get_result(&mut establish_connection()?)?;
-            Ok(count)
+        let send_request = || -> anyhow::Result<ListSettings> {
+            list_settings::read(&mut establish_connection()?, &id)
         };
 
-        match send_request() {
+        match retry_busy(send_request) {
             Ok(value) => {
-                response.count = value;
+                response.settings = Some(value);
                 response.successful = true;
+                response.message = "Settings fetched successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    async fn create_task(&self, request: Request<Task>) -> Result<Response<TaskResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let task = request.into_inner();
-        let mut response = TaskResponse::default();
-
-        let send_request = || -> anyhow::Result<()> {
-            let queryable_task: QueryableTask = task.clone().into();
-
-            diesel::insert_into(tasks)
-                .values(&queryable_task)
-                .execute(&mut establish_connection()?)?;
+    async fn set_list_settings(
+        &self,
+        request: Request<ListSettings>,
+    ) -> Result<Response<ListSettingsResponse>, Status> {
+        let value = request.into_inner();
+        value.validate()?;
+        let mut response = ListSettingsResponse::default();
 
-            Ok(())
-        };
+        let send_request =
+            || -> anyhow::Result<()> { list_settings::write(&mut establish_connection()?, &value) };
 
-        match send_request() {
+        match retry_busy(send_request) {
             Ok(()) => {
-                response.task = Some(task);
+                self.changes
+                    .id(ChangeKind::ListUpdated, value.list_id.clone());
+                response.settings = Some(value);
                 response.successful = true;
-                response.message = "Task added successfully.".to_string()
+                response.message = "Settings updated successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    async fn read_task(&self, request: Request<String>) -> Result<Response<TaskResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let id = request.into_inner();
-        let mut response = TaskResponse::default();
+    async fn create_list_group(
+        &self,
+        request: Request<ListGroup>,
+    ) -> Result<Response<ListGroupResponse>, Status> {
+        let group = request.into_inner();
+        group.validate()?;
+        let mut response = ListGroupResponse::default();
 
-        let send_request = || -> anyhow::Result<Task> {
-            let result: QueryableTask = tasks
-                .find(id)
-                .first(&mut establish_connection()?)
-                .context("Failed to fetch list of tasks.")?;
-            Ok(result.into())
+        let send_request = || -> anyhow::Result<ListGroup> {
+            let group = groups::create(&mut establish_connection()?, group.clone().into())?;
+            Ok(group.into())
         };
 
-        match send_request() {
+        match retry_busy(send_request) {
             Ok(value) => {
-                response.task = Some(value);
+                response.group = Some(value);
                 response.successful = true;
-                response.message = "Task fetched successfully.".to_string()
+                response.message = "Group added successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    async fn update_task(&self, request: Request<Task>) -> Result<Response<TaskResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let task = request.into_inner();
-        let mut response = TaskResponse::default();
-
-        let send_request = || -> anyhow::Result<()> {
-            let task: QueryableTask = task.into();
-
-            diesel::update(tasks.filter(id_task.eq(task.id_task.clone())))
-                .set((
-                    id_task.eq(task.id_task),
-                    title.eq(task.title),
-                    body.eq(task.body),
-                    completed_on.eq(task.completed_on),
-                    due_date.eq(task.due_date),
-                    importance.eq(task.importance),
-                    favorite.eq(task.favorite),
-                    is_reminder_on.eq(task.is_reminder_on),
-                    reminder_date.eq(task.reminder_date),
-                    status.eq(task.status),
-                    created_date_time.eq(task.created_date_time),
-                    last_modified_date_time.eq(task.last_modified_date_time),
-                ))
-                .execute(&mut establish_connection()?)
-                .context("Failed to update task.")?;
+    async fn read_list_groups(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ListGroupsResponse>, Status> {
+        let mut response = ListGroupsResponse::default();
 
-            Ok(())
+        let send_request = || -> anyhow::Result<Vec<ListGroup>> {
+            let all = groups::read_all(&mut establish_connection()?)
+                .context("Failed to fetch groups.")?;
+            Ok(all.into_iter().map(ListGroup::from).collect())
         };
 
-        match send_request() {
-            Ok(()) => {
-                response.task = None;
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.groups = value;
                 response.successful = true;
-                response.message = "Task updated successfully.".to_string()
+                response.message = "Groups fetched successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    async fn delete_task(
+    async fn update_list_group(
         &self,
-        request: Request<String>,
-    ) -> Result<Response<TaskResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let id = request.into_inner();
-        let mut response = TaskResponse::default();
+        request: Request<ListGroup>,
+    ) -> Result<Response<ListGroupResponse>, Status> {
+        let group = request.into_inner();
+        group.validate()?;
+        let mut response = ListGroupResponse::default();
 
-        let send_request = || -> anyhow::Result<()> {
-            diesel::delete(tasks.filter(id_task.eq(id))).execute(&mut establish_connection()?)?;
-
-            Ok(())
+        let send_request = || -> anyhow::Result<ListGroup> {
+            let mut connection = establish_connection()?;
+            let updated = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                groups::update(connection, &group.clone().into())
+            })?;
+            Ok(updated.into())
         };
 
-        match send_request() {
-            Ok(()) => {
-                response.task = None;
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.group = Some(value);
                 response.successful = true;
-                response.message = "Task removed successfully.".to_string()
+                response.message = "Group updated successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    type ReadAllListsStream = ReceiverStream<Result<ListResponse, Status>>;
-
-    async fn read_all_lists(
+    async fn delete_list_group(
         &self,
-        request: Request<Empty>,
-    ) -> Result<Response<Self::ReadAllListsStream>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let (tx, rx) = tokio::sync::mpsc::channel(4);
+        request: Request<String>,
+    ) -> Result<Response<ListGroupResponse>, Status> {
+        let id = request.into_inner();
+        let mut response = ListGroupResponse::default();
 
+        // Returns the lists that moved up a level.
         let send_request = || -> anyhow::Result<Vec<List>> {
-            let results = lists.load::<QueryableList>(&mut establish_connection()?)?;
-
-            let results: Vec<List> = results.iter().map(|t| t.clone().into()).collect();
-            Ok(results)
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                let moved = groups::remove(connection, &id)?;
+                let moved: Vec<QueryableList> =
+                    lists.filter(id_list.eq_any(&moved)).load(connection)?;
+                Ok(moved.into_iter().map(List::from).collect())
+            })
         };
 
-        let mut response = ListResponse::default();
-
-        tokio::spawn(async move {
-            match send_request() {
-                Ok(value) => {
-                    response.successful = true;
-                    for list in &value[..] {
-                        let response = ListResponse {
-                            successful: true,
-                            message: "List fetched succesfully.".to_string(),
-                            list: Some(list.clone()),
-                        };
-                        tx.send(Ok(response)).await.unwrap();
-                    }
+        match retry_busy(send_request) {
+            Ok(moved) => {
+                for list in moved {
+                    self.changes.list(ChangeKind::ListUpdated, list);
                 }
-                Err(err) => response.message = err.to_string(),
+                response.group = None;
+                response.successful = true;
+                response.message = "Group removed successfully.".to_string()
             }
-        });
-
-        Ok(Response::new(ReceiverStream::new(rx)))
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
     }
 
-    async fn read_all_list_ids(
+    async fn reorder_list_groups(
         &self,
-        request: Request<Empty>,
-    ) -> Result<Response<ListIdResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let send_request = || -> anyhow::Result<Vec<String>> {
-            let result: Vec<String> = lists
-                .select(id_list)
-                .load::<String>(&mut establish_connection()?)
-                .context("Failed to fetch list of tasks.")?;
-            Ok(result)
-        };
+        request: Request<ReorderListGroupsRequest>,
+    ) -> Result<Response<ListGroupsResponse>, Status> {
+        request.get_ref().validate()?;
+        let ordered_ids = request.into_inner().ordered_ids;
+        let mut response = ListGroupsResponse::default();
 
-        let mut response = ListIdResponse {
-            successful: true,
-            message: String::new(),
-            lists: vec![],
+        let send_request = || -> anyhow::Result<Vec<ListGroup>> {
+            let mut connection = establish_connection()?;
+            let ordered = connection.transaction::<_, anyhow::Error, _>(|connection| {
+                reorder_groups(connection, &ordered_ids)
+            })?;
+            Ok(ordered.into_iter().map(ListGroup::from).collect())
         };
 
-        match send_request() {
-            Ok(result) => {
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.groups = value;
                 response.successful = true;
-                response.lists = result;
+                response.message = "Groups reordered successfully.".to_string()
             }
-            Err(_) => response.message = "Failed to fetch list of tasks".to_string(),
+            Err(err) => response.message = err.to_string(),
         }
-
         Ok(Response::new(response))
     }
 
-    async fn create_list(&self, request: Request<List>) -> Result<Response<ListResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let list = request.into_inner();
+    async fn get_default_list(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<ListResponse>, Status> {
         let mut response = ListResponse::default();
 
-        let send_request = || -> anyhow::Result<()> {
-            let list: QueryableList = list.into();
-
-            diesel::insert_into(lists)
-                .values(&list)
-                .execute(&mut establish_connection()?)?;
-
-            Ok(())
+        let send_request = || -> anyhow::Result<Option<List>> {
+            let list = settings::default_list(&mut establish_connection()?)?;
+            Ok(list.map(List::from))
         };
 
-        match send_request() {
-            Ok(()) => {
-                response.list = None;
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.list = value;
                 response.successful = true;
-                response.message = "List added succesfully.".to_string()
+                response.message = "List fetched successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    async fn read_list(&self, request: Request<String>) -> Result<Response<ListResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
+    async fn set_default_list(
+        &self,
+        request: Request<String>,
+    ) -> Result<Response<ListResponse>, Status> {
         let id = request.into_inner();
         let mut response = ListResponse::default();
 
         let send_request = || -> anyhow::Result<List> {
-            let result: QueryableList = lists.find(id).first(&mut establish_connection()?)?;
-            Ok(result.into())
+            let mut connection = establish_connection()?;
+            let list: QueryableList = lists
+                .find(&id)
+                .first(&mut connection)
+                .context("The list doesn't exist.")?;
+            settings::set(&mut connection, settings::DEFAULT_LIST_KEY, &id)?;
+            Ok(list.into())
         };
 
-        match send_request() {
+        match retry_busy(send_request) {
             Ok(value) => {
                 response.list = Some(value);
                 response.successful = true;
-                response.message = "List fetched succesfully.".to_string()
+                response.message = "Default list set successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
 
-    async fn update_list(&self, request: Request<List>) -> Result<Response<ListResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
-        let list = request.into_inner();
-        let mut response = ListResponse::default();
+    async fn get_completion_settings(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<CompletionSettingsResponse>, Status> {
+        let mut response = CompletionSettingsResponse::default();
 
-        let send_request = || -> anyhow::Result<()> {
-            let list: QueryableList = list.into();
+        let send_request = || -> anyhow::Result<CompletionSettings> {
+            completion::read(&mut establish_connection()?)
+        };
 
-            diesel::update(lists.filter(id_list.eq(list.id_list.clone())))
-                .set((
-                    name.eq(list.name.clone()),
-                    is_owner.eq(list.is_owner),
-                    icon_name.eq(list.icon_name),
-                    provider.eq(list.provider),
-                ))
-                .execute(&mut establish_connection()?)
-                .context("Failed to update list.")?;
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.settings = Some(value);
+                response.successful = true;
+                response.message = "Settings fetched successfully.".to_string()
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
 
-            Ok(())
-        };
+    async fn set_completion_settings(
+        &self,
+        request: Request<CompletionSettings>,
+    ) -> Result<Response<CompletionSettingsResponse>, Status> {
+        let value = request.into_inner();
+        let mut response = CompletionSettingsResponse::default();
+
+        let send_request =
+            || -> anyhow::Result<()> { completion::write(&mut establish_connection()?, &value) };
 
-        match send_request() {
+        match retry_busy(send_request) {
             Ok(()) => {
-                response.list = None;
+                response.settings = Some(value);
                 response.successful = true;
-                response.message = "List updated succesfully.".to_string()
+                response.message = "Settings updated successfully.".to_string()
             }
             Err(err) => response.message = err.to_string(),
         }
@@ -443,24 +4440,202 @@ impl Provider for LocalService {
         &self,
         request: Request<String>,
     ) -> Result<Response<ListResponse>, Status> {
-        tracing::info!("Request received: {request:?}");
         let id = request.into_inner();
         let mut response = ListResponse::default();
 
         let send_request = || -> anyhow::Result<()> {
-            diesel::delete(lists.filter(id_list.eq(id))).execute(&mut establish_connection()?)?;
-
-            Ok(())
+            let mut connection = establish_connection()?;
+            connection.transaction::<_, anyhow::Error, _>(|connection| {
+                check_unprotected(connection, &id)?;
+                diesel::delete(lists.filter(id_list.eq(&id))).execute(connection)?;
+                diesel::replace_into(tombstones::table)
+                    .values(&QueryableTombstone::new(id.clone(), LIST_ENTITY))
+                    .execute(connection)?;
+                Ok(())
+            })
         };
 
-        match send_request() {
+        match retry_busy(send_request) {
             Ok(()) => {
+                self.changes.id(ChangeKind::ListDeleted, id);
                 response.list = None;
                 response.successful = true;
                 response.message = "List removed succesfully.".to_string()
             }
+            Err(err) if err.is::<ProtectedList>() => {
+                return Err(Status::failed_precondition(err.to_string()))
+            }
+            Err(err) => response.message = err.to_string(),
+        }
+        Ok(Response::new(response))
+    }
+
+    async fn remove_orphaned_data(
+        &self,
+        _request: Request<Empty>,
+    ) -> Result<Response<OrphanCleanupResponse>, Status> {
+        let mut response = OrphanCleanupResponse::default();
+
+        let send_request = || -> anyhow::Result<HashMap<String, i64>> {
+            remove_orphans(&mut establish_connection()?)
+        };
+
+        match retry_busy(send_request) {
+            Ok(value) => {
+                response.removed = value;
+                response.successful = true;
+                response.message = "Orphaned data removed successfully.".to_string()
+            }
             Err(err) => response.message = err.to_string(),
         }
         Ok(Response::new(response))
     }
+
+    type WatchChangesStream = ReceiverStream<Result<ChangeEvent, Status>>;
+
+    async fn watch_changes(
+        &self,
+        request: Request<Empty>,
+    ) -> Result<Response<Self::WatchChangesStream>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        // Older hosts would treat the unknown kind as a task change.
+        let reminders = version::supports(context.api_version, "reminders");
+        // Changes arrive in bursts, this stream keeps its own larger buffer
+        // so subscribers don't fall behind the broadcast.
+        let (tx, rx) = tokio::sync::mpsc::channel(16);
+        let mut receiver = self.changes.subscribe();
+
+        self.supervisor.spawn_stream("watch_changes", async move {
+            loop {
+                let event = tokio::select! {
+                    event = receiver.recv() => event,
+                    _ = tx.closed() => break,
+                };
+                match event {
+                    Ok(event) if !reminders && event.kind == ChangeKind::ReminderFired as i32 => {}
+                    Ok(event) => {
+                        if tx.send(Ok(event)).await.is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvError::Lagged(missed)) => {
+                        let error = Status::data_loss(format!(
+                            "Missed {missed} changes, read the data again and resubscribe."
+                        ));
+                        tx.send(Err(error)).await.ok();
+                        break;
+                    }
+                    Err(RecvError::Closed) => break,
+                }
+            }
+            Ok(())
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    type SyncStream = ReceiverStream<Result<ChangeSet, Status>>;
+
+    async fn sync(
+        &self,
+        request: Request<Streaming<ChangeSet>>,
+    ) -> Result<Response<Self::SyncStream>, Status> {
+        let context = RequestContext::from_metadata(request.metadata(), self.timezone)?;
+        let deadline = deadline(request.metadata());
+        let (tx, rx) = self.channel();
+        let mut incoming = request.into_inner();
+        let changes = self.changes.clone();
+
+        self.supervisor.spawn_stream("sync", async move {
+            while let Some(change_set) = incoming.message().await? {
+                if let Err(error) = change_set.validate() {
+                    tx.send(Err(error)).await?;
+                    break;
+                }
+                let changes = changes.clone();
+                let send_request = move || {
+                    sync(
+                        &mut establish_connection()?,
+                        change_set.clone(),
+                        &changes,
+                        context.api_version,
+                    )
+                };
+                let reply = match fetch(&tx, deadline, send_request).await? {
+                    Some(Ok(reply)) => Ok(reply),
+                    Some(Err(err)) => Err(Status::aborted(err.to_string())),
+                    None => break,
+                };
+                let failed = reply.is_err();
+                tx.send(reply).await?;
+                if failed {
+                    break;
+                }
+            }
+            Ok(())
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+
+    type ReadRecentItemsStream = ReceiverStream<Result<RecentItemResponse, Status>>;
+
+    async fn read_recent_items(
+        &self,
+        request: Request<u32>,
+    ) -> Result<Response<Self::ReadRecentItemsStream>, Status> {
+        let options = self.stream_options(request.metadata());
+        let (tx, rx) = self.channel();
+        let limit = request.into_inner() as usize;
+
+        let send_request = move || -> anyhow::Result<Vec<RecentItemResponse>> {
+            let mut connection = establish_connection()?;
+            let mut results = vec![];
+            for (item, score) in recent_items(&mut connection, limit)? {
+                let mut response = RecentItemResponse {
+                    successful: true,
+                    message: "Item fetched successfully.".to_string(),
+                    id: item.id_item.clone(),
+                    kind: item.kind.clone(),
+                    score,
+                    last_visited: item.last_visited.timestamp(),
+                    ..Default::default()
+                };
+                if item.kind == TASK_ENTITY {
+                    let task: Option<QueryableTask> = tasks
+                        .find(&item.id_item)
+                        .first(&mut connection)
+                        .optional()?;
+                    response.task = task.map(|t| t.into());
+                } else if item.kind == LIST_ENTITY {
+                    let list: Option<QueryableList> = lists
+                        .find(&item.id_item)
+                        .first(&mut connection)
+                        .optional()?;
+                    response.list = list.map(|l| l.into());
+                }
+                if response.task.is_some() || response.list.is_some() {
+                    results.push(response);
+                }
+            }
+            Ok(results)
+        };
+
+        self.supervisor
+            .spawn_stream("read_recent_items", async move {
+                let responses = match fetch(&tx, options.deadline, send_request).await? {
+                    Some(Ok(value)) => value,
+                    Some(Err(err)) => vec![RecentItemResponse {
+                        successful: false,
+                        message: err.to_string(),
+                        ..Default::default()
+                    }],
+                    None => return Ok(()),
+                };
+                send_all("read_recent_items", &tx, options.deadline, responses).await;
+                Ok(())
+            });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
 }
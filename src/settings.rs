@@ -0,0 +1,44 @@
+use anyhow::Result;
+use diesel::{ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::models::QueryableList;
+use crate::schema::{lists, settings};
+
+/// Id of the list tasks land in when they're added without one.
+pub const DEFAULT_LIST_KEY: &str = "default_list";
+
+pub fn get(connection: &mut SqliteConnection, key: &str) -> Result<Option<String>> {
+    let value = settings::table
+        .find(key)
+        .select(settings::value)
+        .first(connection)
+        .optional()?;
+    Ok(value)
+}
+
+pub fn set(connection: &mut SqliteConnection, key: &str, value: &str) -> Result<()> {
+    diesel::replace_into(settings::table)
+        .values((settings::key.eq(key), settings::value.eq(value)))
+        .execute(connection)?;
+    Ok(())
+}
+
+/// The list chosen with `set_default_list`, or the first active list when
+/// none was chosen or it has since been deleted.
+pub fn default_list(connection: &mut SqliteConnection) -> Result<Option<QueryableList>> {
+    if let Some(id) = get(connection, DEFAULT_LIST_KEY)? {
+        let list = lists::table
+            .find(id)
+            .first::<QueryableList>(connection)
+            .optional()?;
+        if list.is_some() {
+            return Ok(list);
+        }
+    }
+    let first = lists::table
+        .filter(lists::archived.eq(false))
+        .order((lists::pinned.desc(), lists::position.asc()))
+        .first::<QueryableList>(connection)
+        .optional()?;
+    Ok(first)
+}
@@ -1,6 +1,51 @@
+#[cfg(not(feature = "otel"))]
 pub fn init() {
     tracing_subscriber::fmt()
-        .with_span_events(tracing_subscriber::fmt::format::FmtSpan::FULL)
         .with_max_level(tracing::Level::INFO)
         .init();
 }
+
+/// Logs to stdout and exports every span to the OTLP collector configured
+/// through the standard `OTEL_EXPORTER_OTLP_*` environment variables. When
+/// the exporter can't be set up, the plugin still starts, without tracing.
+#[cfg(feature = "otel")]
+pub fn init() {
+    use opentelemetry::sdk::{trace, Resource};
+    use opentelemetry::KeyValue;
+    use opentelemetry_otlp::WithExportConfig;
+    use tracing_subscriber::filter::LevelFilter;
+    use tracing_subscriber::prelude::*;
+
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(opentelemetry_otlp::new_exporter().tonic().with_env())
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                "local-plugin",
+            )])),
+        )
+        .install_batch(opentelemetry::runtime::Tokio);
+
+    let (otel, error) = match tracer {
+        Ok(tracer) => (
+            Some(tracing_opentelemetry::layer().with_tracer(tracer)),
+            None,
+        ),
+        Err(err) => (None, Some(err)),
+    };
+    tracing_subscriber::registry()
+        .with(LevelFilter::INFO)
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel)
+        .init();
+    if let Some(err) = error {
+        tracing::warn!("Failed to install the OTLP pipeline, tracing is off: {err}");
+    }
+}
+
+/// Flushes the spans that haven't been exported yet.
+pub fn shutdown() {
+    #[cfg(feature = "otel")]
+    opentelemetry::global::shutdown_tracer_provider();
+}
@@ -0,0 +1,77 @@
+use anyhow::{Context, Result};
+use chrono::{Datelike, Duration, Utc};
+use diesel::sql_types::{BigInt, Double, Integer, Nullable, Text, Timestamp};
+use diesel::{OptionalExtension, QueryableByName, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::{ListStatistics, TaskStatus};
+
+use crate::metadata::RequestContext;
+
+/// Archived tasks are left out of everything but the tasks completed this
+/// week, so archiving finished work doesn't erase it from the week.
+/// The inbox, which isn't stored, has its figures like any list.
+const STATISTICS_QUERY: &str = "
+    SELECT lists.id_list AS id_list,
+           COALESCE(SUM(NOT tasks.archived), 0) AS total,
+           COALESCE(SUM(NOT tasks.archived AND tasks.status = ?1), 0) AS completed,
+           AVG(CASE WHEN NOT tasks.archived AND tasks.status != ?1
+                    THEN (julianday(?2) - julianday(tasks.created_date_time)) * 86400 END)
+               AS average_age,
+           COALESCE(SUM(tasks.status = ?1 AND tasks.completed_on >= ?3), 0)
+               AS completed_this_week,
+           COALESCE(SUM(NOT tasks.archived AND tasks.status != ?1 AND tasks.due_date < ?4), 0)
+               AS overdue
+    FROM (SELECT id_list FROM lists UNION ALL SELECT '') AS lists
+    LEFT JOIN tasks ON tasks.parent_list = lists.id_list
+    WHERE lists.id_list = ?5
+    GROUP BY lists.id_list";
+
+#[derive(Debug, QueryableByName)]
+struct Row {
+    #[diesel(sql_type = Text)]
+    id_list: String,
+    #[diesel(sql_type = BigInt)]
+    total: i64,
+    #[diesel(sql_type = BigInt)]
+    completed: i64,
+    #[diesel(sql_type = Nullable<Double>)]
+    average_age: Option<f64>,
+    #[diesel(sql_type = BigInt)]
+    completed_this_week: i64,
+    #[diesel(sql_type = BigInt)]
+    overdue: i64,
+}
+
+/// Figures for the dashboard of `list`, computed in a single aggregate
+/// query. Weeks start on Monday in the user's timezone.
+pub fn read(
+    connection: &mut SqliteConnection,
+    context: &RequestContext,
+    list: &str,
+) -> Result<ListStatistics> {
+    let today = context.today();
+    let week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let row = diesel::sql_query(STATISTICS_QUERY)
+        .bind::<Integer, _>(TaskStatus::Completed as i32)
+        .bind::<Timestamp, _>(Utc::now().naive_utc())
+        .bind::<Timestamp, _>(context.start_of_day(week))
+        .bind::<Timestamp, _>(context.start_of_day(today))
+        .bind::<Text, _>(list)
+        .get_result::<Row>(connection)
+        .optional()?
+        .context("The list doesn't exist.")?;
+
+    let completion_rate = if row.total == 0 {
+        0.0
+    } else {
+        row.completed as f64 / row.total as f64
+    };
+    Ok(ListStatistics {
+        list_id: row.id_list,
+        total: row.total,
+        completed: row.completed,
+        completion_rate,
+        average_age: row.average_age.map(|seconds| seconds.round() as i64),
+        completed_this_week: row.completed_this_week,
+        overdue: row.overdue,
+    })
+}
@@ -0,0 +1,134 @@
+use anyhow::{bail, Context, Result};
+use chrono::Utc;
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection,
+};
+use proto_rust::provider::TaskStatus;
+
+use crate::completion;
+use crate::models::{QueryableStatus, QueryableTask};
+use crate::schema::{lists, statuses, tasks};
+
+/// The board columns of `list`, left to right.
+pub fn read(connection: &mut SqliteConnection, list: &str) -> Result<Vec<QueryableStatus>> {
+    let columns = statuses::table
+        .filter(statuses::parent_list.eq(list))
+        .order(statuses::position.asc())
+        .load(connection)?;
+    Ok(columns)
+}
+
+/// Replaces the columns of `list` with `columns`, in that order. Columns
+/// that still hold tasks can't be removed.
+pub fn replace(
+    connection: &mut SqliteConnection,
+    list: &str,
+    columns: Vec<QueryableStatus>,
+) -> Result<()> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        lists::table
+            .find(list)
+            .select(lists::id_list)
+            .first::<String>(connection)
+            .context("The list doesn't exist.")?;
+        let kept: Vec<&str> = columns
+            .iter()
+            .map(|column| column.id_status.as_str())
+            .collect();
+        let elsewhere: Option<String> = statuses::table
+            .filter(statuses::id_status.eq_any(&kept))
+            .filter(statuses::parent_list.ne(list))
+            .select(statuses::name)
+            .first(connection)
+            .optional()?;
+        if let Some(name) = elsewhere {
+            bail!("The column {name} belongs to another list.");
+        }
+        let removed: Vec<(String, String)> = statuses::table
+            .filter(statuses::parent_list.eq(list))
+            .filter(statuses::id_status.ne_all(&kept))
+            .select((statuses::id_status, statuses::name))
+            .load(connection)?;
+        let in_use: Option<Option<String>> = tasks::table
+            .filter(tasks::id_status.eq_any(removed.iter().map(|(id, _)| id)))
+            .select(tasks::id_status)
+            .first(connection)
+            .optional()?;
+        if let Some((_, name)) = removed.iter().find(|(id, _)| {
+            in_use
+                .as_ref()
+                .is_some_and(|used| used.as_ref() == Some(id))
+        }) {
+            bail!("Tasks are still in the column {name}.");
+        }
+
+        // Kept columns are updated in place, deleting them would take their
+        // tasks off the board.
+        let removed: Vec<&String> = removed.iter().map(|(id, _)| id).collect();
+        diesel::delete(statuses::table.filter(statuses::id_status.eq_any(removed)))
+            .execute(connection)?;
+        for (position, column) in columns.into_iter().enumerate() {
+            let column = QueryableStatus {
+                parent_list: list.to_string(),
+                position: position as i64,
+                ..column
+            };
+            diesel::insert_into(statuses::table)
+                .values(&column)
+                .on_conflict(statuses::id_status)
+                .do_update()
+                .set(&column)
+                .execute(connection)?;
+        }
+        Ok(())
+    })
+}
+
+/// Moves `task` to the column `status` of its list. Moving it into a done
+/// column completes it and moving it out reopens it.
+///
+/// Returns the task and the next occurrence when completing a repeating
+/// one.
+pub fn move_task(
+    connection: &mut SqliteConnection,
+    task: &str,
+    status: &str,
+) -> Result<(QueryableTask, Option<QueryableTask>)> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let moved: QueryableTask = tasks::table
+            .find(task)
+            .first(connection)
+            .context("The task doesn't exist.")?;
+        let column: QueryableStatus = statuses::table
+            .find(status)
+            .first(connection)
+            .context("The column doesn't exist.")?;
+        if column.parent_list != moved.parent_list {
+            bail!("The column belongs to another list.");
+        }
+
+        let now = Utc::now().naive_utc();
+        let was_completed = moved.status == TaskStatus::Completed as i32;
+        let (new_status, completed_on) = match (column.done, was_completed) {
+            (true, true) => (moved.status, moved.completed_on),
+            (true, false) => (TaskStatus::Completed as i32, Some(now)),
+            (false, true) => (TaskStatus::NotStarted as i32, None),
+            (false, false) => (moved.status, None),
+        };
+        diesel::update(tasks::table.find(task))
+            .set((
+                tasks::id_status.eq(status),
+                tasks::status.eq(new_status),
+                tasks::completed_on.eq(completed_on),
+                tasks::last_modified_date_time.eq(now),
+            ))
+            .execute(connection)?;
+        let moved: QueryableTask = tasks::table.find(task).first(connection)?;
+
+        if !column.done || was_completed {
+            return Ok((moved, None));
+        }
+        let next = completion::completed(connection, &moved)?;
+        Ok((moved, next))
+    })
+}
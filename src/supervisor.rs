@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use chrono::{NaiveDateTime, Utc};
+use tokio_util::sync::CancellationToken;
+use tokio_util::task::TaskTracker;
+use tracing::Instrument;
+
+use crate::metrics;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskKind {
+    /// Feeds a server stream opened by a client.
+    Stream,
+    /// Background job owned by the server.
+    Job,
+}
+
+impl TaskKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TaskKind::Stream => "stream",
+            TaskKind::Job => "job",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ActiveTask {
+    pub id: u64,
+    pub name: &'static str,
+    pub kind: TaskKind,
+    pub started_at: NaiveDateTime,
+}
+
+/// Owns every task the server spawns so they can be listed while they run
+/// and cancelled and awaited on shutdown.
+#[derive(Debug, Clone, Default)]
+pub struct Supervisor {
+    tracker: TaskTracker,
+    streams: CancellationToken,
+    jobs: CancellationToken,
+    active: Arc<Mutex<HashMap<u64, ActiveTask>>>,
+    next_id: Arc<AtomicU64>,
+}
+
+impl Supervisor {
+    /// Spawns a task that feeds the stream of the `name` RPC.
+    pub fn spawn_stream<F>(&self, name: &'static str, future: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.spawn(name, TaskKind::Stream, future)
+    }
+
+    /// Spawns a background job that runs until the server shuts down.
+    pub fn spawn_job<F>(&self, name: &'static str, future: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        self.spawn(name, TaskKind::Job, future)
+    }
+
+    fn spawn<F>(&self, name: &'static str, kind: TaskKind, future: F)
+    where
+        F: Future<Output = anyhow::Result<()>> + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.active.lock().unwrap().insert(
+            id,
+            ActiveTask {
+                id,
+                name,
+                kind,
+                started_at: Utc::now().naive_utc(),
+            },
+        );
+
+        let token = match kind {
+            TaskKind::Stream => self.streams.clone(),
+            TaskKind::Job => self.jobs.clone(),
+        };
+        let active = self.active.clone();
+        if kind == TaskKind::Stream {
+            metrics::stream_started();
+        }
+        // Streams keep the span of the request that opened them.
+        let span = tracing::Span::current();
+        self.tracker.spawn(
+            async move {
+                tokio::select! {
+                    result = future => {
+                        if let Err(err) = result {
+                            tracing::error!("{} {name} failed: {err:#}", kind.as_str());
+                        }
+                    }
+                    _ = token.cancelled() => {
+                        tracing::info!("{} {name} cancelled by shutdown", kind.as_str());
+                    }
+                }
+                active.lock().unwrap().remove(&id);
+                if kind == TaskKind::Stream {
+                    metrics::stream_finished();
+                }
+            }
+            .instrument(span),
+        );
+    }
+
+    /// Tasks that are still running, oldest first.
+    pub fn active(&self) -> Vec<ActiveTask> {
+        let mut active: Vec<ActiveTask> = self.active.lock().unwrap().values().cloned().collect();
+        active.sort_by_key(|task| task.id);
+        active
+    }
+
+    /// Cancels background jobs right away and gives streams up to
+    /// `grace_period` to finish before cancelling them too.
+    pub async fn shutdown(&self, grace_period: Duration) {
+        self.jobs.cancel();
+        self.tracker.close();
+        if tokio::time::timeout(grace_period, self.tracker.wait())
+            .await
+            .is_err()
+        {
+            self.streams.cancel();
+            self.tracker.wait().await;
+        }
+    }
+}
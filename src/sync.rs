@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+use anyhow::{Context, Result};
+use chrono::{NaiveDateTime, Utc};
+use diesel::{
+    Connection, ExpressionMethods, OptionalExtension, QueryDsl, RunQueryDsl, SqliteConnection,
+};
+use proto_rust::provider::{ChangeKind, ChangeSet, List, Task};
+
+use crate::assignees;
+use crate::events::Changes;
+use crate::models::{
+    ListChanges, QueryableList, QueryableTask, QueryableTombstone, TaskChanges, LIST_ENTITY,
+    TASK_ENTITY,
+};
+use crate::priorities;
+use crate::schema::{lists, tasks, tombstones};
+
+/// Change to publish once the incoming set has been committed.
+enum Event {
+    Task(ChangeKind, Box<Task>),
+    List(List),
+    Deleted(ChangeKind, String),
+}
+
+/// Applies the changes a replica sent and returns everything that changed
+/// here since the replica's last sync.
+///
+/// Tasks are resolved with last-writer-wins on their modification date.
+/// Lists carry no modification date, so the incoming copy always wins.
+/// Deletions are applied unless the entity was modified after it was deleted,
+/// protected lists are never deleted this way.
+/// Columns of features the replica's `api_version` predates keep their
+/// stored values. Subtasks aren't part of change sets, the `ChangeSet`
+/// message has no field for them.
+pub fn sync(
+    connection: &mut SqliteConnection,
+    incoming: ChangeSet,
+    changes: &Changes,
+    api_version: u32,
+) -> Result<ChangeSet> {
+    let now = Utc::now();
+    let since =
+        NaiveDateTime::from_timestamp_opt(incoming.since, 0).context("Invalid timestamp.")?;
+
+    let mut events = vec![];
+    let applied = connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let mut applied = HashSet::new();
+
+        for list in incoming.lists {
+            let list: QueryableList = list.into();
+            diesel::insert_into(lists::table)
+                .values(&list)
+                .on_conflict(lists::id_list)
+                .do_update()
+                .set(&ListChanges::new(&list, api_version))
+                .execute(connection)?;
+            let list: QueryableList = lists::table.find(&list.id_list).first(connection)?;
+            applied.insert(list.id_list.clone());
+            events.push(Event::List(list.into()));
+        }
+
+        for task in incoming.tasks {
+            let task: QueryableTask = task.into();
+            let current: Option<NaiveDateTime> = tasks::table
+                .find(&task.id_task)
+                .select(tasks::last_modified_date_time)
+                .first(connection)
+                .optional()?;
+            if current.is_some_and(|current| current > task.last_modified_date_time) {
+                continue;
+            }
+            priorities::check(connection, task.importance)?;
+            assignees::check(connection, task.assigned_to.as_deref())?;
+            diesel::insert_into(tasks::table)
+                .values(&task)
+                .on_conflict(tasks::id_task)
+                .do_update()
+                .set(&TaskChanges::new(&task, api_version))
+                .execute(connection)?;
+            let task: QueryableTask = tasks::table.find(&task.id_task).first(connection)?;
+            applied.insert(task.id_task.clone());
+            let kind = match current {
+                Some(_) => ChangeKind::TaskUpdated,
+                None => ChangeKind::TaskCreated,
+            };
+            events.push(Event::Task(kind, Box::new(task.into())));
+        }
+
+        for tombstone in incoming.deleted {
+            let tombstone: QueryableTombstone = tombstone.into();
+            let kind = match tombstone.entity.as_str() {
+                TASK_ENTITY => {
+                    let modified: Option<NaiveDateTime> = tasks::table
+                        .find(&tombstone.id_entity)
+                        .select(tasks::last_modified_date_time)
+                        .first(connection)
+                        .optional()?;
+                    if modified.is_some_and(|modified| modified > tombstone.deleted_on) {
+                        continue;
+                    }
+                    diesel::delete(tasks::table.find(&tombstone.id_entity)).execute(connection)?;
+                    ChangeKind::TaskDeleted
+                }
+                LIST_ENTITY => {
+                    let protected: Option<bool> = lists::table
+                        .find(&tombstone.id_entity)
+                        .select(lists::protected)
+                        .first(connection)
+                        .optional()?;
+                    if protected == Some(true) {
+                        continue;
+                    }
+                    diesel::delete(lists::table.find(&tombstone.id_entity)).execute(connection)?;
+                    ChangeKind::ListDeleted
+                }
+                _ => continue,
+            };
+            diesel::replace_into(tombstones::table)
+                .values(&tombstone)
+                .execute(connection)?;
+            applied.insert(tombstone.id_entity.clone());
+            events.push(Event::Deleted(kind, tombstone.id_entity));
+        }
+
+        Ok(applied)
+    })?;
+
+    for event in events {
+        match event {
+            Event::Task(kind, task) => changes.task(kind, *task),
+            Event::List(list) => changes.list(ChangeKind::ListUpdated, list),
+            Event::Deleted(kind, id) => changes.id(kind, id),
+        }
+    }
+
+    let changed_tasks: Vec<QueryableTask> = tasks::table
+        .filter(tasks::last_modified_date_time.ge(since))
+        .load(connection)?;
+    let all_lists: Vec<QueryableList> = lists::table.load(connection)?;
+    let deleted: Vec<QueryableTombstone> = tombstones::table
+        .filter(tombstones::deleted_on.ge(since))
+        .load(connection)?;
+
+    Ok(ChangeSet {
+        since: now.timestamp(),
+        tasks: changed_tasks
+            .into_iter()
+            .filter(|task| !applied.contains(&task.id_task))
+            .map(|task| task.into())
+            .collect(),
+        lists: all_lists
+            .into_iter()
+            .filter(|list| !applied.contains(&list.id_list))
+            .map(|list| list.into())
+            .collect(),
+        deleted: deleted
+            .into_iter()
+            .filter(|tombstone| !applied.contains(&tombstone.id_entity))
+            .map(|tombstone| tombstone.into())
+            .collect(),
+    })
+}
@@ -0,0 +1,215 @@
+use anyhow::{bail, Context, Result};
+use chrono::{Duration, Utc};
+use chrono_tz::Tz;
+use diesel::{Connection, ExpressionMethods, QueryDsl, RunQueryDsl, SqliteConnection};
+use proto_rust::provider::Template;
+use uuid::Uuid;
+
+use crate::models::{QueryableSubtask, QueryableTask, QueryableTemplate};
+use crate::ordering::next_task_position;
+use crate::priorities;
+use crate::schema::{
+    list_template_tasks, lists, subtasks, tags, task_tags, tasks, template_subtasks, template_tags,
+    templates,
+};
+
+/// Saves `task`, with its checklist and tags, as a template called `name`.
+pub fn save(
+    connection: &mut SqliteConnection,
+    task: &str,
+    name: String,
+    due_offset: Option<i64>,
+) -> Result<Template> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let task: QueryableTask = tasks::table
+            .find(task)
+            .first(connection)
+            .context("The task doesn't exist.")?;
+        let template = capture(connection, &task, name, due_offset)?;
+        to_proto(connection, template)
+    })
+}
+
+/// Stores `task` as a template, run inside the caller's transaction.
+pub fn capture(
+    connection: &mut SqliteConnection,
+    task: &QueryableTask,
+    name: String,
+    due_offset: Option<i64>,
+) -> Result<QueryableTemplate> {
+    let template = QueryableTemplate::from_task(task, name, due_offset);
+    diesel::insert_into(templates::table)
+        .values(&template)
+        .execute(connection)?;
+
+    let checklist: Vec<(i64, String)> = subtasks::table
+        .filter(subtasks::id_task.eq(&task.id_task))
+        .order(subtasks::position.asc())
+        .select((subtasks::position, subtasks::title))
+        .load(connection)?;
+    let checklist: Vec<_> = checklist
+        .into_iter()
+        .enumerate()
+        .map(|(position, (_, subtask_title))| {
+            (
+                template_subtasks::id_template.eq(&template.id_template),
+                template_subtasks::position.eq(position as i64),
+                template_subtasks::title.eq(subtask_title),
+            )
+        })
+        .collect();
+    diesel::insert_into(template_subtasks::table)
+        .values(&checklist)
+        .execute(connection)?;
+
+    let tagged: Vec<String> = task_tags::table
+        .filter(task_tags::id_task.eq(&task.id_task))
+        .select(task_tags::id_tag)
+        .load(connection)?;
+    let tagged: Vec<_> = tagged
+        .iter()
+        .map(|tag| {
+            (
+                template_tags::id_template.eq(&template.id_template),
+                template_tags::id_tag.eq(tag),
+            )
+        })
+        .collect();
+    diesel::insert_into(template_tags::table)
+        .values(&tagged)
+        .execute(connection)?;
+
+    Ok(template)
+}
+
+/// Every template, by name. The ones that make up list templates are left
+/// out, they are listed with their list template.
+pub fn read_all(connection: &mut SqliteConnection) -> Result<Vec<Template>> {
+    let all: Vec<QueryableTemplate> = templates::table
+        .filter(
+            templates::id_template
+                .ne_all(list_template_tasks::table.select(list_template_tasks::id_template)),
+        )
+        .order(templates::name.asc())
+        .load(connection)?;
+    all.into_iter()
+        .map(|template| to_proto(connection, template))
+        .collect()
+}
+
+/// Deletes a template along with its checklist and tags.
+pub fn remove(connection: &mut SqliteConnection, template: &str) -> Result<()> {
+    let removed = diesel::delete(templates::table.find(template)).execute(connection)?;
+    if removed == 0 {
+        bail!("The template doesn't exist.");
+    }
+    Ok(())
+}
+
+/// Creates a task in `list` from a template, with its dates counted from
+/// now.
+pub fn instantiate(
+    connection: &mut SqliteConnection,
+    template: &str,
+    list: &str,
+    timezone: Tz,
+) -> Result<QueryableTask> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        let template: QueryableTemplate = templates::table
+            .find(template)
+            .first(connection)
+            .context("The template doesn't exist.")?;
+        lists::table
+            .find(list)
+            .select(lists::id_list)
+            .first::<String>(connection)
+            .context("The list doesn't exist.")?;
+
+        let now = Utc::now().naive_utc();
+        let mut task = QueryableTask::new(template.title.clone(), list.to_string());
+        task.body = template.body.clone();
+        task.notes_format = template.notes_format;
+        task.timezone = Some(timezone.name().to_string());
+        task.importance = template.importance;
+        // The scheme may have changed since the template was saved.
+        priorities::check(connection, task.importance)?;
+        task.due_date = template
+            .due_offset
+            .map(|offset| now + Duration::seconds(offset));
+        task.reminder_date = template
+            .reminder_offset
+            .map(|offset| task.due_date.unwrap_or(now) + Duration::seconds(offset));
+        task.is_reminder_on = task.reminder_date.is_some();
+        task.position = next_task_position(connection, list)?;
+        diesel::insert_into(tasks::table)
+            .values(&task)
+            .execute(connection)?;
+
+        let checklist: Vec<QueryableSubtask> = template_subtasks::table
+            .filter(template_subtasks::id_template.eq(&template.id_template))
+            .order(template_subtasks::position.asc())
+            .load::<(String, i64, String)>(connection)?
+            .into_iter()
+            .map(|(_, position, subtask_title)| QueryableSubtask {
+                id_subtask: Uuid::new_v4().to_string(),
+                id_task: task.id_task.clone(),
+                title: subtask_title,
+                completed: false,
+                position,
+                created_date_time: now,
+                last_modified_date_time: now,
+            })
+            .collect();
+        diesel::insert_into(subtasks::table)
+            .values(&checklist)
+            .execute(connection)?;
+
+        let tagged: Vec<String> = template_tags::table
+            .filter(template_tags::id_template.eq(&template.id_template))
+            .filter(template_tags::id_tag.eq_any(tags::table.select(tags::id_tag)))
+            .select(template_tags::id_tag)
+            .load(connection)?;
+        let tagged: Vec<_> = tagged
+            .iter()
+            .map(|tag| {
+                (
+                    task_tags::id_task.eq(&task.id_task),
+                    task_tags::id_tag.eq(tag),
+                )
+            })
+            .collect();
+        diesel::insert_into(task_tags::table)
+            .values(&tagged)
+            .execute(connection)?;
+
+        Ok(task)
+    })
+}
+
+pub fn to_proto(
+    connection: &mut SqliteConnection,
+    template: QueryableTemplate,
+) -> Result<Template> {
+    let checklist: Vec<String> = template_subtasks::table
+        .filter(template_subtasks::id_template.eq(&template.id_template))
+        .order(template_subtasks::position.asc())
+        .select(template_subtasks::title)
+        .load(connection)?;
+    let tag_ids: Vec<String> = template_tags::table
+        .filter(template_tags::id_template.eq(&template.id_template))
+        .select(template_tags::id_tag)
+        .load(connection)?;
+    Ok(Template {
+        id: template.id_template,
+        name: template.name,
+        title: template.title,
+        body: template.body,
+        notes_format: template.notes_format,
+        importance: template.importance,
+        due_offset: template.due_offset,
+        reminder_offset: template.reminder_offset,
+        subtasks: checklist,
+        tag_ids,
+        created_date_time: template.created_date_time.timestamp(),
+    })
+}
@@ -0,0 +1,69 @@
+use std::os::unix::fs::{MetadataExt, PermissionsExt};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use tokio::net::{UnixListener, UnixStream};
+use tokio_stream::wrappers::UnixListenerStream;
+use tokio_stream::{Stream, StreamExt};
+
+/// Command line flag that makes the server listen on a Unix domain socket
+/// instead of TCP, e.g. `--uds /run/user/1000/local-plugin.sock`.
+const UDS_FLAG: &str = "--uds";
+
+const USAGE: &str = "usage: local-plugin [--uds <path>]";
+
+/// Socket path passed on the command line, if any. Fails with the usage
+/// when the flag is given without a path.
+pub fn uds_path() -> Result<Option<PathBuf>> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let path = if arg == UDS_FLAG {
+            args.next()
+        } else if let Some(path) = arg.strip_prefix("--uds=") {
+            Some(path.to_string())
+        } else {
+            continue;
+        };
+        return match path.filter(|path| !path.is_empty() && !path.starts_with('-')) {
+            Some(path) => Ok(Some(PathBuf::from(path))),
+            None => anyhow::bail!("{UDS_FLAG} needs a socket path\n{USAGE}"),
+        };
+    }
+    Ok(None)
+}
+
+/// Binds a socket only the owning user can connect to.
+///
+/// The socket file is created with `0600` permissions and every connection
+/// whose peer runs as a different user is dropped.
+pub fn bind_uds(path: &Path) -> Result<impl Stream<Item = std::io::Result<UnixStream>>> {
+    if path.exists() {
+        std::fs::remove_file(path).context("Failed to remove stale socket")?;
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let listener = UnixListener::bind(path).context("Failed to bind socket")?;
+    std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600))?;
+    let owner = std::fs::metadata(path)?.uid();
+
+    Ok(UnixListenerStream::new(listener).filter(move |connection| {
+        let Ok(stream) = connection else {
+            return true;
+        };
+        match stream.peer_cred() {
+            Ok(credentials) if credentials.uid() == owner => true,
+            Ok(credentials) => {
+                tracing::warn!(
+                    "Rejected connection from user {} on the socket",
+                    credentials.uid()
+                );
+                false
+            }
+            Err(err) => {
+                tracing::warn!("Rejected connection without credentials: {err}");
+                false
+            }
+        }
+    }))
+}
@@ -0,0 +1,699 @@
+use std::collections::HashSet;
+
+use chrono::NaiveDateTime;
+use chrono_tz::Tz;
+use proto_rust::provider::{
+    Attachment, BoardStatus, ChangeSet, Comment, CreateListFromTemplateRequest, DependencyRequest,
+    DuplicateListRequest, InstantiateTemplateRequest, List, ListGroup, ListGrouping, ListSettings,
+    ListSortOrder, MergeListsRequest, MergeTasksRequest, MoveTaskRequest, MoveToStatusRequest,
+    NearbyTasksRequest, NextOccurrencesRequest, NotesFormat, Person, Priority, QuickAddRequest,
+    ReadTasksFromListRequest, ReorderListGroupsRequest, ReorderListsRequest, ReorderSubtaskRequest,
+    ReorderTaskRequest, SaveListTemplateRequest, SaveTemplateRequest, SetAllTasksCompletedRequest,
+    SetListPinnedRequest, SetListProtectedRequest, SetTaskPinnedRequest, SnoozeReminderRequest,
+    StartPomodoroRequest, Subtask, Tag, TagTasksRequest, Task, TaskStatus, TaskTagRequest,
+    Tombstone, UpdatePrioritiesRequest, UpdateStatusesRequest,
+};
+use reqwest::Url;
+use tonic::Status;
+use uuid::Uuid;
+
+use crate::agenda::MAX_DURATION_MINUTES;
+use crate::auto_archive::MAX_ARCHIVE_AFTER_DAYS;
+use crate::icons;
+use crate::inbox::is_inbox;
+use crate::list_settings::MAX_OFFSET;
+use crate::models::{LIST_ENTITY, TASK_ENTITY};
+use crate::recurrence::Rule;
+
+/// Longest a reminder can be snoozed for, a year.
+const MAX_SNOOZE: i64 = 366 * 24 * 60 * 60;
+
+/// Code points in the longest emoji sequences, such as families joined
+/// with zero width joiners.
+const MAX_EMOJI_CHARS: usize = 8;
+
+/// Widest search for nearby tasks, a thousand kilometers.
+const MAX_RADIUS: f64 = 1_000_000.0;
+
+/// Longest focus session, four hours.
+const MAX_POMODORO: i32 = 4 * 60 * 60;
+
+/// Most tasks tagged or untagged at once, well below the number of
+/// parameters SQLite allows in one statement.
+const MAX_TAG_BATCH: usize = 500;
+
+/// Furthest ahead upcoming tasks can be read, ten years.
+const MAX_UPCOMING_DAYS: u32 = 10 * 366;
+
+/// Checks incoming messages before they reach the database, so malformed
+/// data is rejected with `INVALID_ARGUMENT` instead of failing (or
+/// panicking) halfway through a query.
+pub trait Validate {
+    #[allow(clippy::result_large_err)]
+    fn validate(&self) -> Result<(), Status>;
+}
+
+#[allow(clippy::result_large_err)]
+fn uuid(field: &str, value: &str) -> Result<(), Status> {
+    Uuid::parse_str(value)
+        .map(|_| ())
+        .map_err(|_| Status::invalid_argument(format!("`{field}` must be a UUID, got '{value}'")))
+}
+
+/// A list id, or the empty id of the inbox.
+#[allow(clippy::result_large_err)]
+fn list_or_inbox(field: &str, value: &str) -> Result<(), Status> {
+    if is_inbox(value) {
+        return Ok(());
+    }
+    uuid(field, value)
+}
+
+/// Timestamps travel as seconds since the epoch and have to fit in the
+/// range chrono can represent.
+#[allow(clippy::result_large_err)]
+fn timestamp(field: &str, value: i64) -> Result<(), Status> {
+    NaiveDateTime::from_timestamp_opt(value, 0)
+        .map(|_| ())
+        .ok_or_else(|| Status::invalid_argument(format!("`{field}` is not a valid timestamp")))
+}
+
+/// Colors are `#RRGGBB` hex strings.
+#[allow(clippy::result_large_err)]
+fn color_hex(field: &str, value: &str) -> Result<(), Status> {
+    let valid = value.len() == 7
+        && value.starts_with('#')
+        && value[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(Status::invalid_argument(format!(
+            "`{field}` must be a color like #RRGGBB, got '{value}'"
+        )));
+    }
+    Ok(())
+}
+
+/// A single emoji, possibly built from several code points, and nothing
+/// that reads as text.
+#[allow(clippy::result_large_err)]
+fn emoji(field: &str, value: &str) -> Result<(), Status> {
+    let valid = !value.is_empty()
+        && value.chars().count() <= MAX_EMOJI_CHARS
+        && !value
+            .chars()
+            .any(|c| c.is_alphabetic() || c.is_whitespace());
+    if !valid {
+        return Err(Status::invalid_argument(format!(
+            "`{field}` must be a single emoji, got '{value}'"
+        )));
+    }
+    Ok(())
+}
+
+/// List icons are either an emoji or the name of an icon from the icon
+/// theme. Whether the icon is installed is only checked when a user picks
+/// it, see `installed_icon`, since it may be missing on a replica.
+#[allow(clippy::result_large_err)]
+fn icon(field: &str, value: &str) -> Result<(), Status> {
+    if emoji(field, value).is_ok() || icons::well_formed(value) {
+        return Ok(());
+    }
+    Err(Status::invalid_argument(format!(
+        "`{field}` must be an emoji or an icon name, got '{value}'"
+    )))
+}
+
+/// How many days ahead `read_upcoming_tasks` looks, bounded so the end of
+/// the range stays representable.
+#[allow(clippy::result_large_err)]
+pub fn upcoming_days(value: u32) -> Result<(), Status> {
+    if value > MAX_UPCOMING_DAYS {
+        return Err(Status::invalid_argument(format!(
+            "`days` must be at most {MAX_UPCOMING_DAYS}, got {value}"
+        )));
+    }
+    Ok(())
+}
+
+/// Whether the list icon `value` can be drawn here.
+pub fn installed_icon(value: &str) -> bool {
+    emoji("icon", value).is_ok() || icons::exists(value)
+}
+
+/// Only web pages, other schemes can't be previewed or opened everywhere.
+#[allow(clippy::result_large_err)]
+fn web_link(field: &str, value: &str) -> Result<(), Status> {
+    let valid = Url::parse(value).is_ok_and(|url| ["http", "https"].contains(&url.scheme()));
+    if !valid {
+        return Err(Status::invalid_argument(format!(
+            "`{field}` must be an http or https URL, got '{value}'"
+        )));
+    }
+    Ok(())
+}
+
+#[allow(clippy::result_large_err)]
+fn coordinates(latitude: f64, longitude: f64) -> Result<(), Status> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        return Err(Status::invalid_argument(format!(
+            "`latitude` must be between -90 and 90, got {latitude}"
+        )));
+    }
+    if !(-180.0..=180.0).contains(&longitude) {
+        return Err(Status::invalid_argument(format!(
+            "`longitude` must be between -180 and 180, got {longitude}"
+        )));
+    }
+    Ok(())
+}
+
+#[allow(clippy::result_large_err)]
+fn optional_timestamp(field: &str, value: Option<i64>) -> Result<(), Status> {
+    value.map_or(Ok(()), |value| timestamp(field, value))
+}
+
+impl Validate for Task {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        list_or_inbox("parent", &self.parent)?;
+        // Whether the level is configured is checked against the database.
+        if self.importance < 0 {
+            return Err(Status::invalid_argument(format!(
+                "`importance` can't be negative: {}",
+                self.importance
+            )));
+        }
+        if NotesFormat::from_i32(self.notes_format).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "`notes_format` is out of range: {}",
+                self.notes_format
+            )));
+        }
+        if TaskStatus::from_i32(self.status).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "`status` is out of range: {}",
+                self.status
+            )));
+        }
+        if let Some(rule) = &self.recurrence_rule {
+            rule.parse::<Rule>().map_err(|err| {
+                Status::invalid_argument(format!("`recurrence_rule` is invalid: {err}"))
+            })?;
+        }
+        if self.estimate_minutes.is_some_and(|estimate| estimate < 0) {
+            return Err(Status::invalid_argument(
+                "`estimate_minutes` can't be negative",
+            ));
+        }
+        if self
+            .duration_minutes
+            .is_some_and(|duration| !(0..=MAX_DURATION_MINUTES).contains(&duration))
+        {
+            return Err(Status::invalid_argument(format!(
+                "`duration_minutes` must be between 0 and {MAX_DURATION_MINUTES}"
+            )));
+        }
+        if let Some(color) = &self.color {
+            color_hex("color", color)?;
+        }
+        if let Some(value) = &self.emoji {
+            emoji("emoji", value)?;
+        }
+        if let Some(value) = &self.url {
+            web_link("url", value)?;
+        }
+        if let Some(timezone) = &self.timezone {
+            timezone.parse::<Tz>().map_err(|_| {
+                Status::invalid_argument(format!("`timezone` is not a known timezone: {timezone}"))
+            })?;
+        }
+        if let Some(status) = &self.status_id {
+            uuid("status_id", status)?;
+        }
+        if let Some(person) = &self.assigned_to {
+            uuid("assigned_to", person)?;
+        }
+        match (self.latitude, self.longitude) {
+            (Some(latitude), Some(longitude)) => coordinates(latitude, longitude)?,
+            (None, None) => {}
+            _ => {
+                return Err(Status::invalid_argument(
+                    "`latitude` and `longitude` must be set together",
+                ))
+            }
+        }
+        optional_timestamp("completed_on", self.completed_on)?;
+        optional_timestamp("due_date", self.due_date)?;
+        optional_timestamp("start_date", self.start_date)?;
+        optional_timestamp("reminder_date", self.reminder_date)?;
+        timestamp("created_date_time", self.created_date_time)?;
+        timestamp("last_modified_date_time", self.last_modified_date_time)
+    }
+}
+
+impl Validate for Subtask {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        uuid("task_id", &self.task_id)?;
+        timestamp("created_date_time", self.created_date_time)?;
+        timestamp("last_modified_date_time", self.last_modified_date_time)
+    }
+}
+
+impl Validate for ReorderSubtaskRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("subtask_id", &self.subtask_id)?;
+        if let Some(after) = &self.after_subtask_id {
+            uuid("after_subtask_id", after)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Tag {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Person {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for TaskTagRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        uuid("tag_id", &self.tag_id)
+    }
+}
+
+impl Validate for TagTasksRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("tag_id", &self.tag_id)?;
+        if self.task_ids.len() > MAX_TAG_BATCH {
+            return Err(Status::invalid_argument(format!(
+                "`task_ids` can hold at most {MAX_TAG_BATCH} tasks, got {}",
+                self.task_ids.len()
+            )));
+        }
+        for task in &self.task_ids {
+            uuid("task_ids", task)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Comment {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        uuid("task_id", &self.task_id)?;
+        if self.body.trim().is_empty() {
+            return Err(Status::invalid_argument("`body` can't be empty"));
+        }
+        timestamp("created_date_time", self.created_date_time)
+    }
+}
+
+impl Validate for Attachment {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        uuid("task_id", &self.task_id)?;
+        if self.file_name.trim().is_empty() {
+            return Err(Status::invalid_argument("`file_name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for List {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        if let Some(group) = &self.group_id {
+            uuid("group_id", group)?;
+        }
+        if let Some(color) = &self.color {
+            color_hex("color", color)?;
+        }
+        if let Some(value) = &self.icon {
+            icon("icon", value)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for ListSettings {
+    fn validate(&self) -> Result<(), Status> {
+        list_or_inbox("list_id", &self.list_id)?;
+        if ListSortOrder::from_i32(self.sort_order).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "`sort_order` is out of range: {}",
+                self.sort_order
+            )));
+        }
+        if ListGrouping::from_i32(self.group_by).is_none() {
+            return Err(Status::invalid_argument(format!(
+                "`group_by` is out of range: {}",
+                self.group_by
+            )));
+        }
+        if self
+            .default_due_offset
+            .is_some_and(|offset| !(0..=MAX_OFFSET).contains(&offset))
+        {
+            return Err(Status::invalid_argument(format!(
+                "`default_due_offset` must be between 0 and {MAX_OFFSET} seconds"
+            )));
+        }
+        if self
+            .default_reminder_offset
+            .is_some_and(|offset| !(-MAX_OFFSET..=MAX_OFFSET).contains(&offset))
+        {
+            return Err(Status::invalid_argument(format!(
+                "`default_reminder_offset` must be between -{MAX_OFFSET} and {MAX_OFFSET} seconds"
+            )));
+        }
+        if self
+            .archive_after_days
+            .is_some_and(|days| !(0..=MAX_ARCHIVE_AFTER_DAYS).contains(&days))
+        {
+            return Err(Status::invalid_argument(format!(
+                "`archive_after_days` must be between 0 and {MAX_ARCHIVE_AFTER_DAYS}"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for ListGroup {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        if let Some(parent) = &self.parent_id {
+            uuid("parent_id", parent)?;
+        }
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for ReorderListGroupsRequest {
+    fn validate(&self) -> Result<(), Status> {
+        for id in &self.ordered_ids {
+            uuid("ordered_ids", id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Tombstone {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        if ![TASK_ENTITY, LIST_ENTITY].contains(&self.entity.as_str()) {
+            return Err(Status::invalid_argument(format!(
+                "`entity` must be '{TASK_ENTITY}' or '{LIST_ENTITY}', got '{}'",
+                self.entity
+            )));
+        }
+        timestamp("deleted_on", self.deleted_on)
+    }
+}
+
+impl Validate for ChangeSet {
+    fn validate(&self) -> Result<(), Status> {
+        timestamp("since", self.since)?;
+        self.tasks.iter().try_for_each(Validate::validate)?;
+        self.lists.iter().try_for_each(Validate::validate)?;
+        self.deleted.iter().try_for_each(Validate::validate)
+    }
+}
+
+impl Validate for MoveTaskRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        list_or_inbox("target_list_id", &self.target_list_id)
+    }
+}
+
+impl Validate for NextOccurrencesRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)
+    }
+}
+
+impl Validate for StartPomodoroRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        if !(1..=MAX_POMODORO).contains(&self.length) {
+            return Err(Status::invalid_argument(format!(
+                "`length` must be between 1 and {MAX_POMODORO} seconds"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for Priority {
+    fn validate(&self) -> Result<(), Status> {
+        if self.level < 0 {
+            return Err(Status::invalid_argument("`level` can't be negative"));
+        }
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        if let Some(color) = &self.color {
+            color_hex("color", color)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for UpdatePrioritiesRequest {
+    fn validate(&self) -> Result<(), Status> {
+        if self.priorities.is_empty() {
+            return Err(Status::invalid_argument(
+                "At least one priority is required",
+            ));
+        }
+        let mut levels = HashSet::new();
+        for priority in &self.priorities {
+            priority.validate()?;
+            if !levels.insert(priority.level) {
+                return Err(Status::invalid_argument(format!(
+                    "The priority level {} is defined twice",
+                    priority.level
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for BoardStatus {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("id", &self.id)?;
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        if let Some(color) = &self.color {
+            color_hex("color", color)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for UpdateStatusesRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("list_id", &self.list_id)?;
+        let mut ids = HashSet::new();
+        for status in &self.statuses {
+            status.validate()?;
+            if !ids.insert(&status.id) {
+                return Err(Status::invalid_argument(format!(
+                    "The column {} is defined twice",
+                    status.id
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validate for MoveToStatusRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        uuid("status_id", &self.status_id)
+    }
+}
+
+impl Validate for DependencyRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        uuid("blocker_id", &self.blocker_id)
+    }
+}
+
+impl Validate for QuickAddRequest {
+    fn validate(&self) -> Result<(), Status> {
+        if self.text.trim().is_empty() {
+            return Err(Status::invalid_argument("`text` can't be empty"));
+        }
+        if let Some(list) = &self.default_list {
+            list_or_inbox("default_list", list)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SaveTemplateRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        if self.due_offset.is_some_and(|offset| offset < 0) {
+            return Err(Status::invalid_argument("`due_offset` can't be negative"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for InstantiateTemplateRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("template_id", &self.template_id)?;
+        uuid("list_id", &self.list_id)
+    }
+}
+
+impl Validate for ReadTasksFromListRequest {
+    fn validate(&self) -> Result<(), Status> {
+        list_or_inbox("list_id", &self.list_id)
+    }
+}
+
+impl Validate for SaveListTemplateRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("list_id", &self.list_id)?;
+        if self.name.trim().is_empty() {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for CreateListFromTemplateRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("template_id", &self.template_id)?;
+        if self
+            .name
+            .as_ref()
+            .is_some_and(|name| name.trim().is_empty())
+        {
+            return Err(Status::invalid_argument("`name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for NearbyTasksRequest {
+    fn validate(&self) -> Result<(), Status> {
+        coordinates(self.latitude, self.longitude)?;
+        if !(self.radius > 0.0 && self.radius <= MAX_RADIUS) {
+            return Err(Status::invalid_argument(format!(
+                "`radius` must be between 0 and {MAX_RADIUS} meters"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SnoozeReminderRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        if !(1..=MAX_SNOOZE).contains(&self.duration) {
+            return Err(Status::invalid_argument(format!(
+                "`duration` must be between 1 and {MAX_SNOOZE} seconds"
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for MergeTasksRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("primary", &self.primary)?;
+        for duplicate in &self.duplicates {
+            uuid("duplicates", duplicate)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for ReorderListsRequest {
+    fn validate(&self) -> Result<(), Status> {
+        for id in &self.ordered_ids {
+            uuid("ordered_ids", id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for SetListPinnedRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("list_id", &self.list_id)
+    }
+}
+
+impl Validate for SetListProtectedRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("list_id", &self.list_id)
+    }
+}
+
+impl Validate for SetTaskPinnedRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)
+    }
+}
+
+impl Validate for SetAllTasksCompletedRequest {
+    fn validate(&self) -> Result<(), Status> {
+        list_or_inbox("list_id", &self.list_id)
+    }
+}
+
+impl Validate for ReorderTaskRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("task_id", &self.task_id)?;
+        if let Some(after) = &self.after_task_id {
+            uuid("after_task_id", after)?;
+        }
+        Ok(())
+    }
+}
+
+impl Validate for DuplicateListRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("list_id", &self.list_id)?;
+        if self.new_name.trim().is_empty() {
+            return Err(Status::invalid_argument("`new_name` can't be empty"));
+        }
+        Ok(())
+    }
+}
+
+impl Validate for MergeListsRequest {
+    fn validate(&self) -> Result<(), Status> {
+        uuid("source_id", &self.source_id)?;
+        uuid("target_id", &self.target_id)
+    }
+}
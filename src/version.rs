@@ -0,0 +1,137 @@
+use anyhow::{Context, Result};
+use semver::Version;
+use serde::Deserialize;
+
+use crate::config::UpdateConfig;
+
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+pub const GIT_SHA: &str = env!("GIT_SHA");
+pub const PROTO_REVISION: &str = env!("PROTO_REVISION");
+
+/// Newest API revision this provider speaks. Bump it whenever the proto
+/// gains fields that change how requests must be handled.
+pub const API_VERSION: u32 = 3;
+/// Oldest API revision still served, the one hosts spoke before
+/// negotiation existed.
+pub const MIN_API_VERSION: u32 = 1;
+
+/// Optional capabilities of this provider and the API revision that
+/// introduced them, reported to hosts so they can feature-detect instead of
+/// assuming.
+pub const FEATURES: &[(&str, u32)] = &[
+    ("fts", 2),
+    ("query-language", 2),
+    ("frecency", 2),
+    ("watch", 2),
+    ("sync", 2),
+    ("tombstones", 2),
+    ("list-counters", 2),
+    ("task-ordering", 3),
+    ("list-ordering", 3),
+    ("list-archive", 3),
+    ("list-pinning", 3),
+    ("default-list", 3),
+    ("clear-completed", 3),
+    ("complete-all", 3),
+    ("list-with-tasks", 3),
+    ("lists-with-counts", 3),
+    ("idempotency-keys", 3),
+    ("subtasks", 3),
+    ("tags", 3),
+    ("tag-queries", 3),
+    ("notes-format", 3),
+    ("comments", 3),
+    ("attachments", 3),
+    ("recurrence", 3),
+    ("reminders", 3),
+    ("time-tracking", 3),
+    ("pomodoro", 3),
+    ("estimates", 3),
+    ("priorities", 3),
+    ("dependencies", 3),
+    ("templates", 3),
+    ("quick-add", 3),
+    ("cascade-completion", 3),
+    ("task-appearance", 3),
+    ("links", 3),
+    ("locations", 3),
+    ("boards", 3),
+    ("task-pinning", 3),
+    ("start-dates", 3),
+    ("agenda", 3),
+    ("task-timezones", 3),
+    ("task-progress", 3),
+    ("assignees", 3),
+    ("task-archiving", 3),
+    ("list-groups", 3),
+    ("list-colors", 3),
+    ("list-descriptions", 3),
+    ("list-defaults", 3),
+    ("auto-archive", 3),
+    ("hide-completed", 3),
+    ("protected-lists", 3),
+    ("inbox", 3),
+    ("ical-export", 3),
+];
+
+/// Picks the API revision to use with a host that speaks up to `requested`,
+/// or `None` when the host is too old to be served.
+pub fn negotiate(requested: u32) -> Option<u32> {
+    (requested >= MIN_API_VERSION).then(|| requested.min(API_VERSION))
+}
+
+/// Features available to a host speaking `api_version`.
+pub fn features(api_version: u32) -> Vec<String> {
+    FEATURES
+        .iter()
+        .filter(|(_, since)| *since <= api_version)
+        .map(|(feature, _)| feature.to_string())
+        .collect()
+}
+
+/// Whether a host speaking `api_version` knows about `feature`.
+pub fn supports(api_version: u32, feature: &str) -> bool {
+    FEATURES
+        .iter()
+        .any(|(name, since)| *name == feature && *since <= api_version)
+}
+
+/// Largest message the server accepts, tonic's default.
+pub const MAX_MESSAGE_SIZE: i64 = 4 * 1024 * 1024;
+
+/// Unix timestamp of the moment the binary was built.
+pub fn build_date() -> i64 {
+    env!("BUILD_DATE").parse().unwrap_or_default()
+}
+
+/// Release manifest published next to the plugin binaries.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub version: String,
+    #[serde(default)]
+    pub url: Option<String>,
+}
+
+/// Reads the release manifest and returns it when it announces a newer
+/// version than the one running.
+pub async fn check_for_update(config: &UpdateConfig) -> Result<Option<Release>> {
+    let manifest = &config.manifest;
+    let release: Release = if manifest.starts_with("http://") || manifest.starts_with("https://") {
+        reqwest::get(manifest)
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+            .context("Failed to parse the release manifest")?
+    } else {
+        let contents = tokio::fs::read_to_string(manifest)
+            .await
+            .with_context(|| format!("Failed to read the release manifest {manifest}"))?;
+        serde_json::from_str(&contents).context("Failed to parse the release manifest")?
+    };
+
+    let latest = Version::parse(&release.version)
+        .with_context(|| format!("Invalid version {}", release.version))?;
+    let current = Version::parse(VERSION)?;
+    Ok((latest > current).then_some(release))
+}
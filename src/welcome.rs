@@ -0,0 +1,116 @@
+use anyhow::Result;
+use diesel::dsl::count_star;
+use diesel::{Connection, QueryDsl, RunQueryDsl, SqliteConnection};
+
+use crate::inbox::INBOX_ID;
+use crate::models::{QueryableList, QueryableTask};
+use crate::schema::{lists, tasks};
+use crate::settings;
+
+/// Set once the welcome lists were considered, so lists the user deletes
+/// don't come back on the next start.
+const SEEDED_KEY: &str = "welcome_seeded";
+
+struct Welcome {
+    personal: &'static str,
+    inbox_tasks: &'static [&'static str],
+    personal_tasks: &'static [&'static str],
+}
+
+const ENGLISH: Welcome = Welcome {
+    personal: "Personal",
+    inbox_tasks: &[
+        "Welcome! Tasks added without a list land here",
+        "Check this task off to complete it",
+    ],
+    personal_tasks: &["Create a list for each area of your life"],
+};
+
+const SPANISH: Welcome = Welcome {
+    personal: "Personal",
+    inbox_tasks: &[
+        "¡Bienvenido! Las tareas añadidas sin lista llegan aquí",
+        "Marca esta tarea para completarla",
+    ],
+    personal_tasks: &["Crea una lista para cada área de tu vida"],
+};
+
+const FRENCH: Welcome = Welcome {
+    personal: "Personnel",
+    inbox_tasks: &[
+        "Bienvenue ! Les tâches ajoutées sans liste arrivent ici",
+        "Cochez cette tâche pour la terminer",
+    ],
+    personal_tasks: &["Créez une liste pour chaque domaine de votre vie"],
+};
+
+const GERMAN: Welcome = Welcome {
+    personal: "Persönlich",
+    inbox_tasks: &[
+        "Willkommen! Aufgaben ohne Liste landen hier",
+        "Hake diese Aufgabe ab, um sie zu erledigen",
+    ],
+    personal_tasks: &["Lege für jeden Lebensbereich eine Liste an"],
+};
+
+const PORTUGUESE: Welcome = Welcome {
+    personal: "Pessoal",
+    inbox_tasks: &[
+        "Bem-vindo! Tarefas adicionadas sem lista chegam aqui",
+        "Marque esta tarefa para concluí-la",
+    ],
+    personal_tasks: &["Crie uma lista para cada área da sua vida"],
+};
+
+/// The welcome texts for `language`, English for languages without them.
+fn texts(language: &str) -> &'static Welcome {
+    let language = language.split(['-', '_']).next().unwrap_or_default();
+    match language.to_lowercase().as_str() {
+        "es" => &SPANISH,
+        "fr" => &FRENCH,
+        "de" => &GERMAN,
+        "pt" => &PORTUGUESE,
+        _ => &ENGLISH,
+    }
+}
+
+/// Puts a few onboarding tasks in `language` in the inbox and creates a
+/// personal list with some more, only the first time the plugin starts on an
+/// empty database. Returns whether anything was created.
+pub fn seed(connection: &mut SqliteConnection, language: &str, provider: &str) -> Result<bool> {
+    connection.transaction::<_, anyhow::Error, _>(|connection| {
+        if settings::get(connection, SEEDED_KEY)?.is_some() {
+            return Ok(false);
+        }
+        settings::set(connection, SEEDED_KEY, "true")?;
+
+        let existing: i64 = lists::table.select(count_star()).first(connection)?;
+        let existing_tasks: i64 = tasks::table.select(count_star()).first(connection)?;
+        if existing > 0 || existing_tasks > 0 {
+            return Ok(false);
+        }
+
+        let texts = texts(language);
+        // The inbox isn't stored, its tasks only need an empty parent list.
+        let personal =
+            QueryableList::new(texts.personal, Some("🏠".to_string()), provider.to_string());
+        diesel::insert_into(lists::table)
+            .values(&personal)
+            .execute(connection)?;
+
+        let seeded = [
+            (INBOX_ID, texts.inbox_tasks),
+            (personal.id_list.as_str(), texts.personal_tasks),
+        ];
+        for (list, titles) in seeded {
+            for (task_position, title) in titles.iter().enumerate() {
+                let mut task = QueryableTask::new(title.to_string(), list.to_string());
+                task.position = task_position as i64;
+                diesel::insert_into(tasks::table)
+                    .values(&task)
+                    .execute(connection)?;
+            }
+        }
+        Ok(true)
+    })
+}